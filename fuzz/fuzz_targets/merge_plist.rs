@@ -0,0 +1,7 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    kodegen_bundler_autoconfig::fuzz::fuzz_merge_plist(data);
+});
@@ -0,0 +1,120 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::paths;
+
+/// Distinguishes temp files of concurrent writes within this process
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Replace a file's contents so readers see either the old or the new file, never a partial one
+///
+/// The contents go to a temp file in the same directory, which is synced and
+/// then renamed over the original. The original's permissions (and on Unix
+/// its ownership, where allowed) carry over, and a symlinked config is
+/// written through to its target rather than replaced by a regular file
+/// (except during a machine-wide install).
+pub(crate) fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let target = resolve_symlink(path)?;
+    let dir = match target.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let filename = target
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(filename);
+    temp_name.push(format!(
+        ".kodegen-{}-{}.tmp",
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let temp_path = dir.join(temp_name);
+
+    let result = write_and_rename(&target, &temp_path, contents.as_ref());
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result?;
+
+    sync_dir(dir);
+    Ok(())
+}
+
+/// Write the temp file, carry the original's metadata over and move it into place
+fn write_and_rename(target: &Path, temp_path: &Path, contents: &[u8]) -> io::Result<()> {
+    let existing = fs::symlink_metadata(target)
+        .ok()
+        .filter(fs::Metadata::is_file);
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    // A config holding secrets must never be readable by others, not even briefly
+    #[cfg(unix)]
+    if let Some(metadata) = &existing {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(metadata.permissions().mode() & 0o777);
+    }
+    let mut file = options.open(temp_path)?;
+
+    // The umask may have narrowed the mode; the contents go in once it matches the original
+    if let Some(metadata) = &existing {
+        file.set_permissions(metadata.permissions())?;
+        preserve_owner(temp_path, metadata);
+    }
+
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(temp_path, target)
+}
+
+/// Follow a symlinked config to the file it points at
+///
+/// Not during a machine-wide install, where the symlink belongs to another
+/// user and may point at a file only we are allowed to write.
+pub(crate) fn resolve_symlink(path: &Path) -> io::Result<PathBuf> {
+    if paths::user_home_override().is_some() {
+        return Ok(path.to_path_buf());
+    }
+
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => fs::canonicalize(path),
+        _ => Ok(path.to_path_buf()),
+    }
+}
+
+/// Give the temp file the original's owner (only possible when running elevated)
+#[cfg(unix)]
+fn preserve_owner(temp_path: &Path, original: &fs::Metadata) {
+    use std::os::unix::fs::{MetadataExt, chown};
+
+    let Ok(current) = fs::metadata(temp_path) else {
+        return;
+    };
+    if (current.uid(), current.gid()) == (original.uid(), original.gid()) {
+        return;
+    }
+
+    if let Err(e) = chown(temp_path, Some(original.uid()), Some(original.gid())) {
+        log::debug!("Could not keep the owner of {}: {e}", temp_path.display());
+    }
+}
+
+/// Windows ACLs inherit from the directory, so there is nothing to carry over
+#[cfg(not(unix))]
+fn preserve_owner(_temp_path: &Path, _original: &fs::Metadata) {}
+
+/// Persist the rename itself (best effort; directories can't be synced on Windows)
+fn sync_dir(dir: &Path) {
+    #[cfg(unix)]
+    if let Err(e) = fs::File::open(dir).and_then(|dir| dir.sync_all()) {
+        log::debug!("Could not sync directory {}: {e}", dir.display());
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+}
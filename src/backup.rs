@@ -0,0 +1,418 @@
+use std::cmp::Reverse;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, anyhow, bail};
+use log::{debug, info};
+
+use crate::ClientConfigPlugin;
+use crate::atomic;
+use crate::journal::{EntryState, Journal};
+use crate::lock;
+use crate::settings::Settings;
+use crate::state::StateDirs;
+use crate::store::ConfigStore;
+
+/// Number of backups kept per config file unless `backup_retention` is set
+pub const DEFAULT_RETENTION: usize = 10;
+
+/// A backup autoconfig took of a client config before changing it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    /// Config file the backup was taken of
+    pub config_path: PathBuf,
+    /// Location of the backup in the state directory
+    pub path: PathBuf,
+    /// When the backup was taken, in milliseconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+/// How to revert a change autoconfig made to a config file
+///
+/// Each carries the config text autoconfig wrote, to tell whether the file
+/// was edited since.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Undo {
+    /// Copy the backup taken before the change back over the config
+    RestoreBackup {
+        /// Backup taken before the change
+        backup_path: PathBuf,
+        /// Config text the change left behind
+        written: String,
+    },
+    /// Remove a config file autoconfig created from scratch
+    RemoveConfig {
+        /// Config text autoconfig created the file with
+        written: String,
+    },
+}
+
+impl Undo {
+    /// Get the config text the change left behind
+    #[must_use]
+    pub fn written(&self) -> &str {
+        match self {
+            Self::RestoreBackup { written, .. } | Self::RemoveConfig { written } => written,
+        }
+    }
+}
+
+/// Revert a change autoconfig made to a client's config file
+///
+/// An undo may be requested long after the change (e.g. from a notification
+/// left open). If the config still holds what autoconfig wrote, the backup is
+/// restored (or the created file removed); if it was edited since, only
+/// kodegen's entries are taken out of it, keeping the edits. The file is
+/// journaled as removed by the user afterwards, so the watcher doesn't inject
+/// kodegen straight back into it.
+///
+/// # Errors
+///
+/// Returns an error if the client is unknown, or the config cannot be
+/// restored, edited or removed, or the journal cannot be updated.
+pub fn undo_change(client_id: &str, config_path: &Path, undo: &Undo) -> Result<()> {
+    let client = crate::clients::registry()
+        .into_iter()
+        .find(|client| client.client_id() == client_id)
+        .ok_or_else(|| anyhow!("Unknown client `{client_id}`"))?;
+    let state_dirs = StateDirs::resolve()?;
+    let _lock = lock::lock_config_blocking(&state_dirs, config_path)?;
+
+    let store = ConfigStore::new(client.as_ref(), config_path);
+    let current = match store.read() {
+        Ok(current) => Some(current),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {}", config_path.display()));
+        }
+    };
+
+    match (current, undo) {
+        // Gone already, so there's nothing of ours left in it
+        (None, _) => {}
+        (Some(current), undo) if current != undo.written() => {
+            remove_from_edited(client.as_ref(), store, &current, &state_dirs)?;
+        }
+        (Some(_), Undo::RestoreBackup { backup_path, .. }) => {
+            restore_config(&state_dirs, client_id, config_path, backup_path)?;
+        }
+        (Some(_), Undo::RemoveConfig { .. }) => std::fs::remove_file(config_path)
+            .with_context(|| format!("Failed to remove {}", config_path.display()))?,
+    }
+
+    Journal::default().commit(&state_dirs, client_id, config_path, EntryState::UserRemoved)?;
+
+    info!("↩️  Undid kodegen change to {}", config_path.display());
+    Ok(())
+}
+
+/// Copy a backup back over its config, backing up what it replaces
+fn restore_config(
+    state_dirs: &StateDirs,
+    client_id: &str,
+    config_path: &Path,
+    backup_path: &Path,
+) -> Result<()> {
+    // Read it first: backing up the current config may rotate it out
+    let content = std::fs::read(backup_path)
+        .with_context(|| format!("Failed to read backup {}", backup_path.display()))?;
+
+    // Keep what's being replaced, so restoring can be reverted in turn
+    if config_path.is_file() {
+        create_backup(state_dirs, client_id, config_path)?;
+    }
+
+    atomic::write_atomic(config_path, content).with_context(|| {
+        format!(
+            "Failed to restore {} from {}",
+            config_path.display(),
+            backup_path.display()
+        )
+    })
+}
+
+/// Take kodegen's entries out of a config edited since autoconfig changed it
+fn remove_from_edited(
+    client: &dyn ClientConfigPlugin,
+    store: ConfigStore<'_>,
+    current: &str,
+    state_dirs: &StateDirs,
+) -> Result<()> {
+    let config_path = store.path();
+    let merger = Settings::load()?
+        .merger_for(client)
+        .for_config(client, config_path);
+    let format = merger.config_format(client.config_format());
+
+    let updated = client.remove_servers(current, format, &merger)?;
+    if updated == current {
+        return Ok(());
+    }
+
+    debug!(
+        "{} changed since kodegen was added, removing only its entries",
+        config_path.display()
+    );
+    create_backup(state_dirs, client.client_id(), config_path)?;
+    store
+        .write(&updated)
+        .with_context(|| format!("Failed to write {}", config_path.display()))
+}
+
+/// Back up a config file into the state directory before it is changed
+///
+/// Backups beyond the configured retention are deleted, oldest first.
+/// Returns the location of the new backup.
+///
+/// # Errors
+///
+/// Returns an error if the settings are invalid or the backup cannot be written.
+pub(crate) fn create_backup(
+    state_dirs: &StateDirs,
+    client_id: &str,
+    config_path: &Path,
+) -> Result<PathBuf> {
+    store_backup(state_dirs, client_id, config_path, |backup_path| {
+        std::fs::copy(config_path, backup_path).map(drop)
+    })
+}
+
+/// Back up the contents of a config on a machine reached over SSH
+///
+/// Kept in the local state directory like other backups, below
+/// `ssh/<destination>` so they're never taken for (or restored over) this
+/// machine's configs.
+///
+/// # Errors
+///
+/// Returns an error if the settings are invalid or the backup cannot be written.
+pub(crate) fn create_remote_backup(
+    state_dirs: &StateDirs,
+    client_id: &str,
+    destination: &str,
+    config_path: &Path,
+    content: &str,
+) -> Result<PathBuf> {
+    let mut backed_up = PathBuf::from("ssh").join(destination);
+    backed_up.extend(
+        config_path
+            .components()
+            .filter(|component| matches!(component, Component::Normal(_))),
+    );
+    store_backup(state_dirs, client_id, &backed_up, |backup_path| {
+        std::fs::write(backup_path, content)
+    })
+}
+
+/// Save a backup of `config_path` with `save`, then rotate out old ones
+fn store_backup(
+    state_dirs: &StateDirs,
+    client_id: &str,
+    config_path: &Path,
+    save: impl FnOnce(&Path) -> std::io::Result<()>,
+) -> Result<PathBuf> {
+    let mut timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| {
+            u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX)
+        });
+
+    // Never overwrite a backup taken within the same millisecond
+    let mut backup_path = state_dirs.backup_path(client_id, config_path, timestamp);
+    while backup_path.exists() {
+        timestamp += 1;
+        backup_path = state_dirs.backup_path(client_id, config_path, timestamp);
+    }
+
+    if let Some(parent) = backup_path.parent() {
+        std::fs::create_dir_all(parent).context("Failed to create backup directory")?;
+    }
+    save(&backup_path).context("Failed to create backup")?;
+
+    // Always keep the backup just taken
+    let retention = Settings::load()?.backup_retention().max(1);
+    let backups = config_backups(state_dirs, config_path, client_id);
+    for backup in backups.iter().skip(retention) {
+        std::fs::remove_file(&backup.path)
+            .with_context(|| format!("Failed to remove backup {}", backup.path.display()))?;
+        debug!("Rotated out backup {}", backup.path.display());
+    }
+
+    Ok(backup_path)
+}
+
+/// List the backups of a client's config files, newest first
+///
+/// Covers the client's config locations and every config autoconfig has
+/// written for it (including project configs).
+///
+/// # Errors
+///
+/// Returns an error if the settings, state directory or journal cannot be loaded.
+pub fn list_backups(client_id: &str) -> Result<Vec<Backup>> {
+    let state_dirs = StateDirs::resolve()?;
+    let settings = Settings::load()?;
+
+    let mut config_paths: Vec<PathBuf> = crate::clients::registry()
+        .iter()
+        .filter(|client| client.client_id() == client_id)
+        .flat_map(|client| settings.config_paths_for(client.as_ref()))
+        .map(|config_path| config_path.path)
+        .collect();
+    for (config_path, entry) in Journal::load(&state_dirs)?.entries() {
+        if entry.client_id == client_id && !config_paths.iter().any(|path| path == config_path) {
+            config_paths.push(config_path.to_path_buf());
+        }
+    }
+
+    let mut backups: Vec<Backup> = config_paths
+        .iter()
+        .flat_map(|config_path| config_backups(&state_dirs, config_path, client_id))
+        .collect();
+    backups.sort_by_key(|backup| Reverse(backup.timestamp));
+    Ok(backups)
+}
+
+/// Restore the backups of a client's configs taken at `timestamp`
+///
+/// The configs' current contents are backed up first, and replaced whatever
+/// they hold. Like [`undo_change`], the configs are journaled as removed by
+/// the user so the watcher leaves them as restored. Returns the restored
+/// config paths.
+///
+/// # Errors
+///
+/// Returns an error if no backup was taken at `timestamp` or a config cannot
+/// be restored.
+pub fn restore_backup(client_id: &str, timestamp: u64) -> Result<Vec<PathBuf>> {
+    let backups: Vec<Backup> = list_backups(client_id)?
+        .into_iter()
+        .filter(|backup| backup.timestamp == timestamp)
+        .collect();
+    if backups.is_empty() {
+        bail!("No backup of {client_id} was taken at {timestamp}");
+    }
+
+    let state_dirs = StateDirs::resolve()?;
+    let mut restored = Vec::new();
+    for backup in backups {
+        let _lock = lock::lock_config_blocking(&state_dirs, &backup.config_path)?;
+        restore_config(&state_dirs, client_id, &backup.config_path, &backup.path)?;
+        Journal::default().commit(
+            &state_dirs,
+            client_id,
+            &backup.config_path,
+            EntryState::UserRemoved,
+        )?;
+        restored.push(backup.config_path);
+    }
+
+    Ok(restored)
+}
+
+/// List the backups of a single config file, newest first
+fn config_backups(state_dirs: &StateDirs, config_path: &Path, client_id: &str) -> Vec<Backup> {
+    let template = state_dirs.backup_path(client_id, config_path, 0);
+    let (Some(dir), Some(filename)) = (template.parent(), config_path.file_name()) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}.", filename.to_string_lossy());
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut backups: Vec<Backup> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let timestamp = name
+                .to_str()?
+                .strip_prefix(&prefix)?
+                .strip_suffix(".backup")?
+                .parse()
+                .ok()?;
+            Some(Backup {
+                config_path: config_path.to_path_buf(),
+                path: entry.path(),
+                timestamp,
+            })
+        })
+        .collect();
+    backups.sort_by_key(|backup| Reverse(backup.timestamp));
+    backups
+}
+
+/// List kodegen-created backups older than `older_than` without deleting anything
+///
+/// Covers everything under the state directory's backups (including
+/// snapshot archives) and legacy `.backup` files left next to client configs
+/// by earlier versions.
+///
+/// # Errors
+///
+/// Returns an error if the settings or state directory cannot be resolved.
+pub fn stale_backups(older_than: Duration) -> Result<Vec<PathBuf>> {
+    let cutoff = SystemTime::now()
+        .checked_sub(older_than)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+
+    let mut candidates = Vec::new();
+    collect_files(&StateDirs::resolve()?.backups_dir(), &mut candidates);
+
+    let settings = Settings::load()?;
+    for client in crate::clients::registry() {
+        for config_path in settings.config_paths_for(client.as_ref()) {
+            let mut legacy_name = config_path.path.as_os_str().to_os_string();
+            legacy_name.push(".backup");
+            let legacy_path = PathBuf::from(legacy_name);
+            if legacy_path.is_file() {
+                candidates.push(legacy_path);
+            }
+        }
+    }
+
+    Ok(candidates
+        .into_iter()
+        .filter(|path| {
+            std::fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified < cutoff)
+        })
+        .collect())
+}
+
+/// Delete kodegen-created backups older than `older_than`
+///
+/// Returns the paths that were removed; use [`stale_backups`] for a dry run.
+///
+/// # Errors
+///
+/// Returns an error if the backups cannot be listed or a backup cannot be removed.
+pub fn cleanup_backups(older_than: Duration) -> Result<Vec<PathBuf>> {
+    let stale = stale_backups(older_than)?;
+
+    for path in &stale {
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove backup {}", path.display()))?;
+        debug!("Removed stale backup {}", path.display());
+    }
+
+    info!("🧹 Removed {} stale backups", stale.len());
+    Ok(stale)
+}
+
+/// Recursively collect all files below a directory
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
@@ -0,0 +1,404 @@
+//! `kodegen-autoconfig`: configure MCP clients for KODEGEN.ᴀɪ from the command line
+//!
+//! A thin front end over the library, for running autoconfig without the
+//! bundler. Logs go to stderr (`RUST_LOG` adjusts them); results go to stdout,
+//! as JSON with `--json`.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use clap::{Parser, Subcommand};
+use kodegen_bundler_autoconfig::watcher::AutoConfigWatcher;
+use kodegen_bundler_autoconfig::{
+    Daemon, InstallAction, InstallMode, InstallOptions, InstallReport, InstallResult,
+    InstallTarget, MessageCode, SshTarget, UninstallResult, clients, detect_clients,
+    install_all_clients_with, install_to, install_windows_host_clients, list_backups,
+    restore_backup, uninstall_all_clients, wsl,
+};
+use serde::Serialize;
+
+#[derive(Parser)]
+#[command(name = "kodegen-autoconfig", version, about)]
+struct Cli {
+    /// Only act on this client (repeatable, see `list-clients`)
+    #[arg(long = "client", short, global = true, value_name = "ID")]
+    clients: Vec<String>,
+
+    /// Show what would change without writing anything
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Print results as JSON
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Add kodegen to every detected client
+    Install {
+        /// Also rewrite kodegen entries that differ from the current server definition
+        #[arg(long)]
+        upgrade: bool,
+        /// Under WSL, configure the Windows host's clients instead
+        #[arg(long, conflicts_with_all = ["ssh", "devcontainer"])]
+        windows_host: bool,
+        /// Configure the clients on a machine reached with `ssh` instead
+        #[arg(long, value_name = "DESTINATION", conflicts_with = "devcontainer")]
+        ssh: Option<String>,
+        /// Add kodegen to a workspace's dev container definition instead
+        #[arg(long, value_name = "WORKSPACE")]
+        devcontainer: Option<PathBuf>,
+        /// Use this settings profile instead of the default one
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+    },
+    /// Remove kodegen from every client config it was added to
+    Uninstall,
+    /// Show which clients are installed and which configs have kodegen
+    Status,
+    /// Keep client configs configured as they change
+    Watch,
+    /// List the supported clients
+    ListClients,
+    /// Show the changes an install would make, as unified diffs
+    Diff {
+        /// Diff against an upgrade instead of an install
+        #[arg(long)]
+        upgrade: bool,
+        /// Use this settings profile instead of the default one
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+    },
+    /// Put back a client's configs from a backup (the newest unless given)
+    Restore {
+        /// Backup to restore, in milliseconds since the Unix epoch
+        timestamp: Option<u64>,
+    },
+}
+
+/// A client and what was found of it
+#[derive(Serialize)]
+struct ClientStatus {
+    client_id: String,
+    client_name: String,
+    installed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    configs: Vec<ConfigStatus>,
+}
+
+/// An existing config file of a client
+#[derive(Serialize)]
+struct ConfigStatus {
+    path: PathBuf,
+    configured: bool,
+}
+
+/// A supported client
+#[derive(Serialize)]
+struct ClientInfo {
+    client_id: String,
+    client_name: String,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    // Progress logs would only get in the way of JSON consumers
+    let level = if cli.json { "warn" } else { "info" };
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(level))
+        .format_target(false)
+        .format_timestamp(None)
+        .init();
+
+    match run(cli) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("Error: {e:#}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Run a command, returning whether everything it did succeeded
+fn run(cli: Cli) -> Result<bool> {
+    select_clients(&cli.clients)?;
+
+    match cli.command {
+        Command::Install {
+            upgrade,
+            windows_host,
+            ssh,
+            devcontainer,
+            profile,
+        } => {
+            let target = match (ssh, devcontainer) {
+                (Some(destination), _) => InstallTarget::Ssh(SshTarget::new(destination)),
+                (None, Some(workspace)) => InstallTarget::Devcontainer(workspace),
+                (None, None) => InstallTarget::Local,
+            };
+            let options = install_options(upgrade, profile).with_dry_run(cli.dry_run);
+            install(&target, options, windows_host, cli.json)
+        }
+        Command::Diff { upgrade, profile } => diff(install_options(upgrade, profile), cli.json),
+        Command::Uninstall if cli.dry_run => {
+            let configured: Vec<ClientStatus> = status()?
+                .into_iter()
+                .filter(|client| client.configs.iter().any(|config| config.configured))
+                .collect();
+            print_status(&configured, cli.json)?;
+            Ok(true)
+        }
+        Command::Uninstall => uninstall(cli.json),
+        Command::Status => {
+            print_status(&status()?, cli.json)?;
+            Ok(true)
+        }
+        Command::Watch => watch(),
+        Command::ListClients => list_clients(cli.json),
+        Command::Restore { timestamp } => restore(&cli.clients, timestamp, cli.dry_run, cli.json),
+    }
+}
+
+/// Limit the process-wide registry to the clients given with `--client`
+fn select_clients(ids: &[String]) -> Result<()> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    let registry = clients::registry();
+    if let Some(unknown) = ids.iter().find(|id| registry.get(id).is_none()) {
+        bail!("Unknown client `{unknown}` (see `kodegen-autoconfig list-clients`)");
+    }
+
+    let selected = registry.filter(|client| ids.iter().any(|id| id == client.client_id()));
+    clients::update_registry(|registry| *registry = selected);
+    Ok(())
+}
+
+/// Options for `install` and `diff`
+fn install_options(upgrade: bool, profile: Option<String>) -> InstallOptions<'static> {
+    let mode = if upgrade {
+        InstallMode::Upgrade
+    } else {
+        InstallMode::Install
+    };
+    let options = InstallOptions::default().with_mode(mode);
+    match profile {
+        Some(profile) => options.with_profile(profile),
+        None => options,
+    }
+}
+
+fn install(
+    target: &InstallTarget,
+    options: InstallOptions,
+    windows_host: bool,
+    json: bool,
+) -> Result<bool> {
+    let results = if windows_host {
+        if !wsl::is_wsl() {
+            bail!("`--windows-host` only works under WSL");
+        }
+        install_windows_host_clients(options)?
+    } else {
+        install_to(target, options)?
+    };
+    let success = no_failures(&results);
+
+    if json {
+        println!("{}", InstallReport::from(results).to_json()?);
+    } else {
+        for result in results
+            .iter()
+            .filter(|result| result.code != MessageCode::NotInstalled)
+        {
+            print_install_result(result);
+        }
+        if !windows_host && *target == InstallTarget::Local && wsl::is_wsl() {
+            println!("Clients on the Windows host can be configured with `install --windows-host`");
+        }
+    }
+    Ok(success)
+}
+
+fn diff(options: InstallOptions, json: bool) -> Result<bool> {
+    let results = install_all_clients_with(options.with_dry_run(true))?;
+    let success = no_failures(&results);
+
+    if json {
+        let changes: Vec<InstallResult> = results
+            .into_iter()
+            .filter(|result| result.diff.is_some())
+            .collect();
+        println!("{}", InstallReport::from(changes).to_json()?);
+    } else {
+        for diff in results.iter().filter_map(|result| result.diff.as_deref()) {
+            print!("{diff}");
+        }
+    }
+    Ok(success)
+}
+
+fn uninstall(json: bool) -> Result<bool> {
+    let results = uninstall_all_clients()?;
+    let success = results.iter().all(|result| result.success);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        for result in results
+            .iter()
+            .filter(|result| !result.config_paths.is_empty() || !result.success)
+        {
+            print_uninstall_result(result);
+        }
+    }
+    Ok(success)
+}
+
+/// Check every client for its configs and whether they have kodegen
+fn status() -> Result<Vec<ClientStatus>> {
+    Ok(detect_clients()?
+        .into_iter()
+        .map(|client| ClientStatus {
+            client_id: client.client_id,
+            client_name: client.client_name,
+            installed: client.installed,
+            version: client.version.map(|version| version.to_string()),
+            configs: client
+                .configs
+                .into_iter()
+                .filter(|config| config.exists)
+                .map(|config| ConfigStatus {
+                    path: config.path,
+                    configured: config.configured,
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+fn watch() -> Result<bool> {
+    let runtime = tokio::runtime::Runtime::new().context("Failed to start the tokio runtime")?;
+
+    runtime.block_on(async {
+        let watcher = AutoConfigWatcher::new(clients::registry().into_iter().collect())?;
+        let daemon = Daemon::start(watcher)?;
+        // The watcher stops by itself on Ctrl-C and termination signals
+        while daemon.is_running() {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        daemon.shutdown().await
+    })?;
+    Ok(true)
+}
+
+fn list_clients(json: bool) -> Result<bool> {
+    let clients: Vec<ClientInfo> = clients::registry()
+        .iter()
+        .map(|client| ClientInfo {
+            client_id: client.client_id().to_string(),
+            client_name: client.client_name().to_string(),
+        })
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&clients)?);
+    } else {
+        for client in &clients {
+            println!("{:<20} {}", client.client_id, client.client_name);
+        }
+    }
+    Ok(true)
+}
+
+fn restore(ids: &[String], timestamp: Option<u64>, dry_run: bool, json: bool) -> Result<bool> {
+    let [client_id] = ids else {
+        bail!("`restore` needs exactly one `--client`");
+    };
+
+    let backups = list_backups(client_id)?;
+    let timestamp = match timestamp {
+        Some(timestamp) => timestamp,
+        None => match backups.first() {
+            Some(newest) => newest.timestamp,
+            None => bail!("There are no backups of {client_id}"),
+        },
+    };
+
+    let restored = if dry_run {
+        backups
+            .into_iter()
+            .filter(|backup| backup.timestamp == timestamp)
+            .map(|backup| backup.config_path)
+            .collect()
+    } else {
+        restore_backup(client_id, timestamp)?
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&restored)?);
+    } else {
+        let verb = if dry_run { "Would restore" } else { "Restored" };
+        for path in &restored {
+            println!("{verb} {} from backup {timestamp}", path.display());
+        }
+    }
+    Ok(!restored.is_empty())
+}
+
+/// Whether no client failed; clients that aren't installed are fine
+fn no_failures(results: &[InstallResult]) -> bool {
+    results
+        .iter()
+        .all(|result| result.action != InstallAction::Failed)
+}
+
+fn print_install_result(result: &InstallResult) {
+    let mark = if result.success { "✓" } else { "✗" };
+    println!("{mark} {}: {}", result.client_name, result.message);
+    for warning in &result.warnings {
+        println!("    ⚠ {}", warning.message);
+    }
+    if let Some(remediation) = result.params.get("remediation") {
+        println!("    💡 {remediation}");
+    }
+    if let Some(diff) = &result.diff {
+        for line in diff.lines() {
+            println!("    {line}");
+        }
+    }
+}
+
+fn print_uninstall_result(result: &UninstallResult) {
+    let mark = if result.success { "✓" } else { "✗" };
+    println!("{mark} {}: {}", result.client_name, result.message);
+}
+
+fn print_status(clients: &[ClientStatus], json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(clients)?);
+        return Ok(());
+    }
+
+    for client in clients {
+        let installed = match (&client.version, client.installed) {
+            (Some(version), true) => format!("installed, {version}"),
+            (None, true) => "installed".to_string(),
+            (_, false) => "not installed".to_string(),
+        };
+        println!("{} ({installed})", client.client_name);
+        for config in &client.configs {
+            let mark = if config.configured { "✓" } else { "·" };
+            println!("    {mark} {}", config.path.display());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,193 @@
+//! Finding the kodegen binary
+//!
+//! Clients start the configured command themselves, often without the
+//! user's shell `PATH` (GUI apps on macOS get a minimal one), so a command
+//! that works in a terminal can still fail to start. Installs check the
+//! command before writing it; [`BinaryCheck`] decides whether a missing one
+//! is only reported or stops the install.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::ConfigMerger;
+use crate::{Platform, paths};
+
+/// What an install does when the configured command can't be run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BinaryCheck {
+    /// Write the config anyway and report a warning
+    #[default]
+    Warn,
+    /// Leave the config alone and report why
+    Refuse,
+}
+
+/// Why a configured command can't be run
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BinaryError {
+    /// Nothing exists at the command's path, or a bare name isn't on `PATH`
+    ///
+    /// `found` is where kodegen was found instead, if anywhere.
+    #[error("{command} was not found")]
+    NotFound {
+        command: String,
+        found: Option<PathBuf>,
+    },
+    /// The command exists but can't be executed (e.g. missing execute permission)
+    #[error("{} is not executable", path.display())]
+    NotExecutable { path: PathBuf },
+}
+
+impl BinaryError {
+    /// Get the command that can't be run
+    #[must_use]
+    pub fn command(&self) -> String {
+        match self {
+            Self::NotFound { command, .. } => command.clone(),
+            Self::NotExecutable { path } => path.display().to_string(),
+        }
+    }
+}
+
+/// Find the kodegen binary
+///
+/// Checks the `KODEGEN_BIN` environment variable, then `PATH`, then the
+/// usual install directories (see [`install_dirs`]).
+#[must_use]
+pub fn resolve_kodegen_binary() -> Option<PathBuf> {
+    if let Some(bin) = std::env::var_os("KODEGEN_BIN").filter(|bin| !bin.is_empty()) {
+        return Some(PathBuf::from(bin));
+    }
+
+    paths::find_on_path("kodegen").or_else(|| {
+        install_dirs()
+            .into_iter()
+            .find_map(|dir| executable_in(&dir, "kodegen"))
+    })
+}
+
+/// Get the standard install location of the kodegen binary
+///
+/// `%LOCALAPPDATA%\kodegen\bin\kodegen.exe` on Windows and
+/// `~/.local/bin/kodegen` elsewhere.
+#[must_use]
+pub fn default_install_path() -> Option<PathBuf> {
+    match Platform::native() {
+        Platform::Windows => {
+            paths::data_dir().map(|dir| dir.join("kodegen").join("bin").join("kodegen.exe"))
+        }
+        _ => paths::home_dir().map(|home| home.join(".local").join("bin").join("kodegen")),
+    }
+}
+
+/// Get the directories kodegen is commonly installed to, most specific first
+#[must_use]
+pub fn install_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = default_install_path()
+        .and_then(|path| path.parent().map(Path::to_path_buf))
+        .into_iter()
+        .collect();
+
+    if let Some(home) = paths::home_dir() {
+        dirs.push(home.join(".cargo").join("bin"));
+    }
+
+    match Platform::native() {
+        Platform::Windows => {
+            dirs.extend(
+                std::env::var_os("ProgramFiles")
+                    .map(|dir| PathBuf::from(dir).join("kodegen").join("bin")),
+            );
+        }
+        Platform::MacOS => {
+            dirs.push(PathBuf::from("/opt/homebrew/bin"));
+            dirs.push(PathBuf::from("/usr/local/bin"));
+            dirs.push(PathBuf::from("/opt/kodegen/bin"));
+        }
+        Platform::Linux | Platform::All => {
+            dirs.push(PathBuf::from("/usr/local/bin"));
+            dirs.push(PathBuf::from("/opt/kodegen/bin"));
+        }
+    }
+
+    dirs
+}
+
+/// Check that a configured command can be started
+///
+/// A bare name must be on `PATH`; a path (a leading `~` is expanded) must be
+/// an executable file. Returns the executable the command refers to.
+///
+/// # Errors
+///
+/// Returns [`BinaryError`] if the command can't be found or executed.
+pub fn verify_command(command: &str) -> Result<PathBuf, BinaryError> {
+    let path = Path::new(command);
+    if path.components().count() <= 1 {
+        return paths::find_on_path(command).ok_or_else(|| BinaryError::NotFound {
+            command: command.to_string(),
+            found: install_dirs()
+                .into_iter()
+                .find_map(|dir| executable_in(&dir, command)),
+        });
+    }
+
+    let path = paths::expand_home(path);
+    if !path.is_file() {
+        return Err(BinaryError::NotFound {
+            command: command.to_string(),
+            found: None,
+        });
+    }
+    if !is_executable(&path) {
+        return Err(BinaryError::NotExecutable { path });
+    }
+
+    Ok(path)
+}
+
+/// Check the commands of every stdio server a merger injects
+///
+/// Servers reached over HTTP have no command to check.
+///
+/// # Errors
+///
+/// Returns the first [`BinaryError`] found.
+pub fn verify_merger(merger: &ConfigMerger) -> Result<(), BinaryError> {
+    merger
+        .servers()
+        .filter(|server| !server.transport().is_http())
+        .try_for_each(|server| verify_command(&server.server_config().command).map(drop))
+}
+
+/// Find an executable named `name` (with the platform's extensions) in `dir`
+fn executable_in(dir: &Path, name: &str) -> Option<PathBuf> {
+    [
+        name.to_string(),
+        format!("{name}.cmd"),
+        format!("{name}.exe"),
+    ]
+    .into_iter()
+    .map(|candidate| dir.join(candidate))
+    .find(|candidate| candidate.is_file() && is_executable(candidate))
+}
+
+/// Check whether anyone may execute a file
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+/// Windows runs any file with an executable extension
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| {
+        ["exe", "cmd", "bat", "com"]
+            .iter()
+            .any(|known| extension.eq_ignore_ascii_case(known))
+    })
+}
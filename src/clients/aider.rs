@@ -0,0 +1,47 @@
+//! Aider support (enabled with the `aider` feature)
+//!
+//! Aider doesn't speak MCP, and `.aider.conf.yml` has nothing a server could
+//! be registered in, so this plugin only detects Aider: its installs are
+//! reported with [`MessageCode::Unsupported`](crate::MessageCode::Unsupported)
+//! instead of going unnoticed. Aider users can reach kodegen through an
+//! MCP-to-CLI bridge configured by hand.
+
+use std::path::{Path, PathBuf};
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, paths};
+
+pub struct AiderPlugin;
+
+impl ClientConfigPlugin for AiderPlugin {
+    fn client_id(&self) -> &'static str {
+        "aider"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Aider"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        // Aider keeps its caches and analytics settings in ~/.aider
+        paths::home_dir()
+            .map(|home| home.join(".aider"))
+            .into_iter()
+            .collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        Vec::new()
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        path.is_dir()
+            || path
+                .parent()
+                .is_some_and(|home| home.join(".aider.conf.yml").is_file())
+            || paths::find_on_path("aider").is_some()
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Yaml
+    }
+}
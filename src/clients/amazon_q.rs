@@ -0,0 +1,74 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+/// Amazon Q Developer CLI
+///
+/// Global servers live in `~/.aws/amazonq/mcp.json`, workspace servers in
+/// `.amazonq/mcp.json` (see [`workspace_config_path`]), both with the standard
+/// `mcpServers` map.
+pub struct AmazonQPlugin;
+
+impl ClientConfigPlugin for AmazonQPlugin {
+    fn client_id(&self) -> &'static str {
+        "amazon-q"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Amazon Q Developer CLI"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        paths::home_dir()
+            .map(|home| home.join(".aws").join("amazonq"))
+            .into_iter()
+            .collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        self.watch_paths()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: dir.join("mcp.json"),
+                format: ConfigFormat::Json,
+                platform: Platform::All,
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn workspace_config_paths(&self, workspace: &Path) -> Vec<ConfigPath> {
+        vec![ConfigPath {
+            path: workspace_config_path(workspace),
+            format: ConfigFormat::Json,
+            platform: Platform::All,
+            scope: Scope::Workspace(workspace.to_path_buf()),
+        }]
+    }
+
+    // No process names: each new `q chat` session loads the servers
+
+    fn is_installed(&self, path: &Path) -> bool {
+        // The directory only appears after the first login, the binary right away
+        path.is_dir() || paths::find_on_path("q").is_some()
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
+
+/// Get the workspace MCP config of a directory `q chat` is started in
+///
+/// [`install_for_workspace`](crate::install_for_workspace) writes it to have
+/// kodegen configured for everyone working on the project.
+#[must_use]
+pub fn workspace_config_path(workspace_dir: &Path) -> PathBuf {
+    workspace_dir.join(".amazonq").join("mcp.json")
+}
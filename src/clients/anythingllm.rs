@@ -0,0 +1,56 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+/// AnythingLLM Desktop
+///
+/// Servers live in `plugins/anythingllm_mcp_servers.json` in the app's
+/// storage directory, with the standard `mcpServers` map.
+pub struct AnythingLlmPlugin;
+
+impl ClientConfigPlugin for AnythingLlmPlugin {
+    fn client_id(&self) -> &'static str {
+        "anythingllm"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "AnythingLLM Desktop"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        paths::app_config_dir()
+            .map(|app_config| app_config.join("anythingllm-desktop").join("storage"))
+            .into_iter()
+            .collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        self.watch_paths()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: dir.join("plugins").join("anythingllm_mcp_servers.json"),
+                format: ConfigFormat::Json,
+                platform: Platform::All,
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn process_names(&self) -> &[&str] {
+        &["AnythingLLM", "anythingllm-desktop"]
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
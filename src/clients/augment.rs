@@ -0,0 +1,80 @@
+//! Augment Code support
+//!
+//! The VS Code extension reads its MCP servers from VS Code's settings, as
+//! an array of named entries at `augment.advanced.mcpServers`. The array is
+//! replaced as a whole in the settings text, so the user's comments and
+//! formatting elsewhere are kept.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use super::vscode;
+use crate::{
+    ClientConfigPlugin, ConfigFormat, ConfigPath, EntryShape, MergeTarget, Platform, Scope,
+    validate,
+};
+
+/// Augment's extension id, naming its global storage directory
+const EXTENSION_ID: &str = "augment.vscode-augment";
+
+/// Settings key of Augment's advanced settings object
+const ADVANCED_KEY: &str = "augment.advanced";
+
+pub struct AugmentPlugin;
+
+impl ClientConfigPlugin for AugmentPlugin {
+    fn client_id(&self) -> &'static str {
+        "augment"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Augment Code"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        // Augment is a VSCode extension, so we watch VSCode config directories
+        vscode::user_data_dirs()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        self.watch_paths()
+            .into_iter()
+            .filter(|dir| self.is_installed(dir))
+            .flat_map(|dir| vscode::profile_settings_paths(&dir.join("User")))
+            .map(|path| ConfigPath {
+                path,
+                format: ConfigFormat::Json,
+                platform: Platform::current(),
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn process_names(&self) -> &[&str] {
+        vscode::PROCESS_NAMES
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        // Only exists once the extension has been installed and run
+        path.join("User")
+            .join("globalStorage")
+            .join(EXTENSION_ID)
+            .is_dir()
+    }
+
+    fn merge_target(&self) -> MergeTarget {
+        MergeTarget::new([ADVANCED_KEY, "mcpServers"]).with_entry_shape(EntryShape::Named)
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::at(
+            &[ADVANCED_KEY, "mcpServers"],
+            validate::server_list(validate::named(validate::stdio_server())),
+        ))
+    }
+}
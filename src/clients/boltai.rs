@@ -0,0 +1,85 @@
+//! BoltAI support (macOS only)
+//!
+//! BoltAI keeps its MCP servers in its preferences domain, in an
+//! `mcpServers` dictionary shaped like the standard JSON map, so this is the
+//! one client configured through the plist merge. The app is restarted
+//! afterwards so `cfprefsd` doesn't hand it a stale cached copy.
+//!
+//! Only XML preference files can be merged; `cfprefsd` usually writes binary
+//! ones, which are reported as unreadable until converted with
+//! `plutil -convert xml1`.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+/// BoltAI's bundle identifier, naming its preferences domain
+const BUNDLE_ID: &str = "co.podzim.BoltGPT";
+
+pub struct BoltAiPlugin;
+
+impl ClientConfigPlugin for BoltAiPlugin {
+    fn client_id(&self) -> &'static str {
+        "boltai"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "BoltAI"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        // The support directory rather than ~/Library/Preferences, which every app writes to
+        match Platform::current() {
+            Platform::MacOS => paths::app_config_dir()
+                .map(|app_config| app_config.join(BUNDLE_ID))
+                .into_iter()
+                .collect(),
+            _ => {
+                // BoltAI is only available on macOS
+                Vec::new()
+            }
+        }
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        match Platform::current() {
+            Platform::MacOS => paths::home_dir()
+                .map(|home| {
+                    home.join("Library")
+                        .join("Preferences")
+                        .join(format!("{BUNDLE_ID}.plist"))
+                })
+                .into_iter()
+                .map(|path| ConfigPath {
+                    path,
+                    format: ConfigFormat::Plist,
+                    platform: Platform::MacOS,
+                    scope: Scope::User,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn process_names(&self) -> &[&str] {
+        &["BoltAI"]
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        // The support directory outlives an uninstalled app, so require the bundle too
+        path.is_dir()
+            && paths::app_bundle_paths("BoltAI.app")
+                .iter()
+                .any(|bundle| bundle.is_dir())
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Plist
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
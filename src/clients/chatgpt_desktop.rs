@@ -0,0 +1,115 @@
+//! ChatGPT Desktop support (enabled with the `chatgpt-desktop` feature)
+//!
+//! ChatGPT adds MCP servers as connectors, and only remote ones so far. The
+//! desktop app does not read local connector config yet; these are the
+//! locations its app data lives in, so support can be switched on as soon
+//! as it does. The connector entry is the standard `mcpServers` one.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+/// Prefix of the Microsoft Store package directory, followed by the publisher hash
+const WINDOWS_PACKAGE_PREFIX: &str = "OpenAI.ChatGPT-Desktop_";
+
+pub struct ChatGptDesktopPlugin;
+
+impl ClientConfigPlugin for ChatGptDesktopPlugin {
+    fn client_id(&self) -> &'static str {
+        "chatgpt-desktop"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "ChatGPT Desktop"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+
+        match Platform::current() {
+            Platform::MacOS => {
+                if let Some(app_config) = paths::app_config_dir() {
+                    paths.push(app_config.join("com.openai.chat"));
+                }
+            }
+            Platform::Windows => {
+                // The Store app's roaming data is redirected into its package
+                paths.extend(
+                    windows_packages()
+                        .into_iter()
+                        .map(|package| package.join("LocalCache").join("Roaming").join("ChatGPT")),
+                );
+                if let Some(app_config) = paths::app_config_dir() {
+                    paths.push(app_config.join("ChatGPT"));
+                }
+            }
+            _ => {
+                // ChatGPT Desktop is only available on macOS and Windows
+            }
+        }
+
+        paths
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        self.watch_paths()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: dir.join("mcp.json"),
+                format: ConfigFormat::Json,
+                platform: Platform::current(),
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn process_names(&self) -> &[&str] {
+        &["ChatGPT"]
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        if !path.is_dir() {
+            return false;
+        }
+
+        // App data outlives an uninstalled app, so require the app itself too
+        match Platform::current() {
+            Platform::MacOS => paths::app_bundle_paths("ChatGPT.app")
+                .iter()
+                .any(|bundle| bundle.is_dir()),
+            _ => true,
+        }
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
+
+/// Get the ChatGPT Store package directories under `%LOCALAPPDATA%\Packages`
+fn windows_packages() -> Vec<PathBuf> {
+    let Some(packages) = paths::data_dir().map(|data_dir| data_dir.join("Packages")) else {
+        return Vec::new();
+    };
+
+    std::fs::read_dir(packages)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| {
+                    entry
+                        .file_name()
+                        .to_string_lossy()
+                        .starts_with(WINDOWS_PACKAGE_PREFIX)
+                })
+                .map(|entry| entry.path())
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,100 @@
+//! Claude Code, Anthropic's terminal coding agent
+//!
+//! Claude Code reads MCP servers from three scopes:
+//! - user: the top-level `mcpServers` map in `~/.claude.json`, for every project
+//! - local: `projects.<dir>.mcpServers` in `~/.claude.json`, for one project only
+//! - project: `.mcp.json` in a project's root, checked in and shared with the team
+//!
+//! kodegen is added at user scope. Project `.mcp.json` files belong to the
+//! repository, so they're only managed when declared in the settings (see
+//! [`project_config_path`]); local scope is left to the user.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+pub struct ClaudeCodePlugin;
+
+impl ClientConfigPlugin for ClaudeCodePlugin {
+    fn client_id(&self) -> &'static str {
+        "claude-code"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Claude Code"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        config_dir().into_iter().collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        user_config_path()
+            .into_iter()
+            .map(|path| ConfigPath {
+                path,
+                format: ConfigFormat::Json,
+                platform: Platform::All,
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn workspace_config_paths(&self, workspace: &Path) -> Vec<ConfigPath> {
+        vec![ConfigPath {
+            path: project_config_path(workspace),
+            format: ConfigFormat::Json,
+            platform: Platform::All,
+            scope: Scope::Workspace(workspace.to_path_buf()),
+        }]
+    }
+
+    // No process names: restarting would end the user's terminal sessions, and
+    // each new `claude` session reads the config anyway
+
+    fn is_installed(&self, path: &Path) -> bool {
+        // `~/.claude` holds settings, history and credentials once Claude Code has run
+        path.is_dir() || user_config_path().is_some_and(|config| config.is_file())
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
+
+/// Get the directory set by `CLAUDE_CONFIG_DIR`, if any
+fn config_dir_override() -> Option<PathBuf> {
+    paths::session_env_path("CLAUDE_CONFIG_DIR")
+}
+
+/// Get Claude Code's data directory (`~/.claude`, or `CLAUDE_CONFIG_DIR`)
+fn config_dir() -> Option<PathBuf> {
+    config_dir_override().or_else(|| paths::home_dir().map(|home| home.join(".claude")))
+}
+
+/// Get the file holding user- and local-scope MCP servers
+///
+/// This is `~/.claude.json`, next to (not inside) `~/.claude`, unless
+/// `CLAUDE_CONFIG_DIR` moves both into one directory.
+#[must_use]
+pub fn user_config_path() -> Option<PathBuf> {
+    match config_dir_override() {
+        Some(config_dir) => Some(config_dir.join(".claude.json")),
+        None => paths::home_dir().map(|home| home.join(".claude.json")),
+    }
+}
+
+/// Get the project-scope config file of a project directory
+///
+/// [`install_for_workspace`](crate::install_for_workspace) writes it to have
+/// kodegen configured for everyone working on the project.
+#[must_use]
+pub fn project_config_path(project_dir: &Path) -> PathBuf {
+    project_dir.join(".mcp.json")
+}
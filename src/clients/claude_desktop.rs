@@ -1,10 +1,10 @@
 use std::path::{Path, PathBuf};
 
-use anyhow::Result;
 use log::debug;
 
-use crate::config::ConfigMerger;
-use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform};
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
 
 pub struct ClaudeDesktopPlugin;
 
@@ -21,14 +21,9 @@ impl ClientConfigPlugin for ClaudeDesktopPlugin {
         let mut paths = Vec::new();
 
         match Platform::current() {
-            Platform::Windows => {
-                if let Ok(appdata) = std::env::var("APPDATA") {
-                    paths.push(PathBuf::from(appdata).join("Claude"));
-                }
-            }
-            Platform::MacOS => {
-                if let Some(home) = directories::BaseDirs::new() {
-                    paths.push(home.home_dir().join("Library/Application Support/Claude"));
+            Platform::Windows | Platform::MacOS => {
+                if let Some(app_config) = paths::app_config_dir() {
+                    paths.push(app_config.join("Claude"));
                 }
             }
             _ => {
@@ -40,36 +35,23 @@ impl ClientConfigPlugin for ClaudeDesktopPlugin {
     }
 
     fn config_paths(&self) -> Vec<ConfigPath> {
-        let mut configs = Vec::new();
+        self.watch_paths()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: dir.join("claude_desktop_config.json"),
+                format: ConfigFormat::Json,
+                platform: Platform::current(),
+                scope: Scope::User,
+            })
+            .collect()
+    }
 
-        match Platform::current() {
-            Platform::Windows => {
-                if let Ok(appdata) = std::env::var("APPDATA") {
-                    configs.push(ConfigPath {
-                        path: PathBuf::from(appdata)
-                            .join("Claude")
-                            .join("claude_desktop_config.json"),
-                        format: ConfigFormat::Json,
-                        platform: Platform::Windows,
-                    });
-                }
-            }
-            Platform::MacOS => {
-                if let Some(home) = directories::BaseDirs::new() {
-                    configs.push(ConfigPath {
-                        path: home
-                            .home_dir()
-                            .join("Library/Application Support/Claude")
-                            .join("claude_desktop_config.json"),
-                        format: ConfigFormat::Json,
-                        platform: Platform::MacOS,
-                    });
-                }
-            }
-            _ => {}
-        }
+    fn process_names(&self) -> &[&str] {
+        &["Claude"]
+    }
 
-        configs
+    fn windows_app_names(&self) -> &[&str] {
+        &["Claude"]
     }
 
     fn is_installed(&self, path: &Path) -> bool {
@@ -77,12 +59,11 @@ impl ClientConfigPlugin for ClaudeDesktopPlugin {
         path.exists() && path.is_dir()
     }
 
-    fn inject_kodegen(&self, config_content: &str, format: ConfigFormat) -> Result<String> {
-        let merger = ConfigMerger::new();
-        merger.merge(config_content, format)
-    }
-
     fn config_format(&self) -> ConfigFormat {
         ConfigFormat::Json
     }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
 }
@@ -0,0 +1,69 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use super::vscode;
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, validate};
+
+/// Cline's extension id, naming its global storage directory
+const EXTENSION_ID: &str = "saoudrizwan.claude-dev";
+
+pub struct ClinePlugin;
+
+impl ClientConfigPlugin for ClinePlugin {
+    fn client_id(&self) -> &'static str {
+        "cline"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Cline"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        // Cline is a VSCode extension, so we watch VSCode config directories
+        let mut paths = vscode::user_data_dirs();
+        paths.extend(vscode::remote_server_data_dirs());
+        paths
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        // Cline keeps its MCP servers in the extension's global storage rather
+        // than in settings.json, and global storage is shared by all profiles
+        self.watch_paths()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: global_storage_dir(&dir)
+                    .join("settings")
+                    .join("cline_mcp_settings.json"),
+                format: ConfigFormat::Json,
+                platform: Platform::current(),
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn process_names(&self) -> &[&str] {
+        vscode::PROCESS_NAMES
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        // Only exists once the extension has been installed and run
+        global_storage_dir(path).is_dir()
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
+
+/// Get Cline's global storage directory within a VS Code data directory
+fn global_storage_dir(data_dir: &Path) -> PathBuf {
+    data_dir
+        .join("User")
+        .join("globalStorage")
+        .join(EXTENSION_ID)
+}
@@ -0,0 +1,66 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{
+    ClientConfigPlugin, ConfigFormat, ConfigPath, MergeTarget, Platform, Scope, paths, validate,
+};
+
+/// OpenAI's Codex CLI
+///
+/// Servers are `[mcp_servers.<name>]` tables in `config.toml` rather than the
+/// usual `mcpServers`, with the same `command`, `args` and `env` keys.
+pub struct CodexPlugin;
+
+impl ClientConfigPlugin for CodexPlugin {
+    fn client_id(&self) -> &'static str {
+        "codex"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Codex CLI"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        codex_home().into_iter().collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        codex_home()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: dir.join("config.toml"),
+                format: ConfigFormat::Toml,
+                platform: Platform::All,
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    // No process names: each new `codex` session reads the config
+
+    fn is_installed(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn merge_target(&self) -> MergeTarget {
+        MergeTarget::new(["mcp_servers"])
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Toml
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::at(
+            &["mcp_servers"],
+            validate::server_map(validate::any_server()),
+        ))
+    }
+}
+
+/// Get Codex's home directory (`~/.codex`, or `CODEX_HOME`)
+fn codex_home() -> Option<PathBuf> {
+    paths::session_env_path("CODEX_HOME")
+        .or_else(|| paths::home_dir().map(|home| home.join(".codex")))
+}
@@ -0,0 +1,265 @@
+//! Sourcegraph Cody support
+//!
+//! Cody reaches MCP servers through OpenCtx: the MCP provider is configured
+//! under `openctx.providers` in VS Code's settings, keyed by the provider's
+//! URL, so there is room for a single MCP server per profile.
+//!
+//! The provider starts a server as `<nodeCommand> <file from mcp.provider.uri>
+//! <args>`. To run the kodegen binary itself, `/usr/bin/env` stands in for
+//! Node, which is why Cody is only configured on macOS and Linux.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde_json::{Value as JsonValue, json};
+
+use super::vscode;
+use crate::config::{self, ConfigMerger};
+use crate::{
+    ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, jsonc, paths, validate,
+};
+
+/// Cody's extension id, naming its global storage directory
+const EXTENSION_ID: &str = "sourcegraph.cody-ai";
+
+/// Settings key holding the OpenCtx providers
+const PROVIDERS_KEY: &str = "openctx.providers";
+
+/// The OpenCtx MCP provider's URL, keying its settings
+const MCP_PROVIDER: &str = "https://openctx.org/npm/@openctx/provider-modelcontextprotocol";
+
+/// Launcher that runs the file it is given, standing in for `node`
+const LAUNCHER: &str = "/usr/bin/env";
+
+pub struct CodyPlugin;
+
+impl ClientConfigPlugin for CodyPlugin {
+    fn client_id(&self) -> &'static str {
+        "cody"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Sourcegraph Cody"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        // Cody is a VSCode extension, so we watch VSCode config directories
+        match Platform::current() {
+            Platform::Windows => Vec::new(),
+            _ => vscode::user_data_dirs(),
+        }
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        self.watch_paths()
+            .into_iter()
+            .filter(|dir| self.is_installed(dir))
+            .flat_map(|dir| vscode::profile_settings_paths(&dir.join("User")))
+            .map(|path| ConfigPath {
+                path,
+                format: ConfigFormat::Json,
+                platform: Platform::current(),
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn process_names(&self) -> &[&str] {
+        vscode::PROCESS_NAMES
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        // Only exists once the extension has been installed and run
+        path.join("User")
+            .join("globalStorage")
+            .join(EXTENSION_ID)
+            .is_dir()
+    }
+
+    fn inject_kodegen_with(
+        &self,
+        config_content: &str,
+        _format: ConfigFormat,
+        merger: &ConfigMerger,
+    ) -> Result<String> {
+        config::ensure_config_len(config_content)?;
+        let content = if config_content.trim().is_empty() {
+            "{}"
+        } else {
+            config_content
+        };
+
+        let document = jsonc::Document::new(content);
+        let mut config: JsonValue = serde_json::from_str(document.stripped())?;
+        config::ensure_json_servers_object(&config, PROVIDERS_KEY)?;
+
+        // The provider slot holds one server, so suffixed names don't help
+        let existing = config
+            .get(PROVIDERS_KEY)
+            .and_then(|providers| providers.get(MCP_PROVIDER))
+            .map(as_server_entry);
+        let lookup = |name: &str| existing.clone().filter(|_| name == merger.server_name());
+        match merger.resolve_server_name(lookup)? {
+            None => return Ok(config_content.to_string()),
+            Some(name) if name != merger.server_name() => {
+                bail!("Cody's MCP provider is already configured for another server");
+            }
+            Some(_) => {}
+        }
+
+        let server = merger.server_config();
+        let binary = if Path::new(&server.command).is_absolute() {
+            PathBuf::from(&server.command)
+        } else {
+            paths::find_on_path(&server.command)
+                .with_context(|| format!("`{}` not found on PATH", server.command))?
+        };
+        let mut entry = serde_json::json!({
+            "nodeCommand": LAUNCHER,
+            "mcp.provider.uri": file_uri(&binary),
+            "mcp.provider.args": server.args,
+        });
+        if let Some(env) = &server.env {
+            entry["mcp.provider.env"] = env.clone();
+        }
+
+        let edited = document
+            .upsert(&[PROVIDERS_KEY], MCP_PROVIDER, &entry)
+            .map(Some);
+        if let Some(obj) = config.as_object_mut() {
+            let providers = obj
+                .entry(PROVIDERS_KEY)
+                .or_insert_with(|| JsonValue::Object(serde_json::Map::new()));
+            if let Some(providers) = providers.as_object_mut() {
+                providers.insert(MCP_PROVIDER.to_string(), entry);
+            }
+        }
+
+        config::checked_edit(&document, edited, &config)
+    }
+
+    fn remove_kodegen(
+        &self,
+        config_content: &str,
+        _format: ConfigFormat,
+        merger: &ConfigMerger,
+    ) -> Result<String> {
+        config::ensure_config_len(config_content)?;
+        if config_content.trim().is_empty() {
+            return Ok(config_content.to_string());
+        }
+
+        let document = jsonc::Document::new(config_content);
+        let mut config: JsonValue = serde_json::from_str(document.stripped())?;
+        config::ensure_json_servers_object(&config, PROVIDERS_KEY)?;
+
+        let Some(providers) = config
+            .get_mut(PROVIDERS_KEY)
+            .and_then(JsonValue::as_object_mut)
+        else {
+            return Ok(config_content.to_string());
+        };
+        if !providers
+            .get(MCP_PROVIDER)
+            .is_some_and(|provider| merger.is_ours(&as_server_entry(provider)))
+        {
+            return Ok(config_content.to_string());
+        }
+
+        providers.remove(MCP_PROVIDER);
+        let content = config::checked_edit(
+            &document,
+            document.remove(&[PROVIDERS_KEY], MCP_PROVIDER),
+            &config,
+        )?;
+
+        // Drop the providers object we emptied, unless the user left comments in it
+        let document = jsonc::Document::new(&content);
+        if config[PROVIDERS_KEY]
+            .as_object()
+            .is_some_and(serde_json::Map::is_empty)
+            && document.is_blank_object(&[PROVIDERS_KEY])?
+        {
+            if let Some(obj) = config.as_object_mut() {
+                obj.remove(PROVIDERS_KEY);
+            }
+            return config::checked_edit(&document, document.remove(&[], PROVIDERS_KEY), &config);
+        }
+
+        Ok(content)
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::at(
+            &[PROVIDERS_KEY, MCP_PROVIDER],
+            validate::required(
+                &["mcp.provider.uri"],
+                json!({
+                    "nodeCommand": { "type": "string", "minLength": 1 },
+                    "mcp.provider.uri": { "type": "string", "minLength": 1 },
+                    "mcp.provider.args": validate::string_list(),
+                    "mcp.provider.env": validate::string_map(),
+                }),
+            ),
+        ))
+    }
+}
+
+/// View the MCP provider's settings as a standard server entry (`command`, `args`, `env`)
+fn as_server_entry(provider: &JsonValue) -> JsonValue {
+    let command = provider
+        .get("mcp.provider.uri")
+        .and_then(JsonValue::as_str)
+        .map(uri_path);
+
+    serde_json::json!({
+        "command": command,
+        "args": provider.get("mcp.provider.args"),
+        "env": provider.get("mcp.provider.env"),
+    })
+}
+
+/// Get the `file://` URI of an absolute path, percent-encoding all but unreserved characters and `/`
+fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            uri.push(char::from(byte));
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    uri
+}
+
+/// Get the path a `file://` URI points to, decoding percent-escapes
+fn uri_path(uri: &str) -> String {
+    let Some(path) = uri.strip_prefix("file://") else {
+        return uri.to_string();
+    };
+
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = (byte == b'%')
+            .then(|| tail.get(..2))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
@@ -0,0 +1,243 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde_json::{Value as JsonValue, json};
+
+use crate::config::{self, ConfigMerger};
+use crate::version::Version;
+use crate::{
+    ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, jsonc, paths, validate,
+};
+
+/// Continue, the open-source coding assistant for VS Code and JetBrains
+///
+/// `config.yaml` lists servers as `mcpServers: [{ name, command, ... }]`.
+/// Older installs use `config.json`, which lists them under
+/// `experimental.modelContextProtocolServers` as `{ "transport": { ... } }`
+/// without a name. Before Continue 1.0, a fresh install reads `config.json`,
+/// so that's the file created when the extension is that old.
+pub struct ContinuePlugin;
+
+/// Path of the server list in the legacy `config.json`
+const LEGACY_SERVERS: [&str; 2] = ["experimental", "modelContextProtocolServers"];
+
+/// First release creating `config.yaml` rather than `config.json`
+const YAML_CONFIG_VERSION: Version = Version::new(1, 0, 0);
+
+impl ClientConfigPlugin for ContinuePlugin {
+    fn client_id(&self) -> &'static str {
+        "continue"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Continue"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        continue_dir().into_iter().collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        let Some(dir) = continue_dir() else {
+            return Vec::new();
+        };
+
+        // Continue only falls back to config.json when there is no config.yaml
+        let yaml = dir.join("config.yaml");
+        let json = dir.join("config.json");
+        let legacy = if yaml.exists() {
+            false
+        } else {
+            json.is_file() || installed_version().is_some_and(|v| v < YAML_CONFIG_VERSION)
+        };
+        let (path, format) = if legacy {
+            (json, ConfigFormat::Json)
+        } else {
+            (yaml, ConfigFormat::Yaml)
+        };
+
+        vec![ConfigPath {
+            path,
+            format,
+            platform: Platform::All,
+            scope: Scope::User,
+        }]
+    }
+
+    // No process names: Continue reloads its config when the file changes
+
+    fn detect_version(&self, _config_path: &Path) -> Option<Version> {
+        installed_version()
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn inject_kodegen_with(
+        &self,
+        config_content: &str,
+        format: ConfigFormat,
+        merger: &ConfigMerger,
+    ) -> Result<String> {
+        match format {
+            ConfigFormat::Yaml => {
+                merger.merge_yaml_list(config_content, "mcpServers", &server_entry(merger))
+            }
+            ConfigFormat::Json => merge_legacy_json(config_content, merger),
+            _ => bail!("Continue configs are YAML or JSON"),
+        }
+    }
+
+    fn remove_kodegen(
+        &self,
+        config_content: &str,
+        format: ConfigFormat,
+        merger: &ConfigMerger,
+    ) -> Result<String> {
+        match format {
+            ConfigFormat::Yaml => merger.remove_yaml_list(config_content, "mcpServers"),
+            ConfigFormat::Json => remove_legacy_json(config_content, merger),
+            _ => bail!("Continue configs are YAML or JSON"),
+        }
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Yaml
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::object(json!({
+            "mcpServers": validate::server_list(validate::named(validate::stdio_server())),
+            "experimental": validate::object(json!({
+                "modelContextProtocolServers": validate::server_list(validate::required(
+                    &["transport"],
+                    json!({ "transport": validate::stdio_server() }),
+                )),
+            })),
+        })))
+    }
+}
+
+/// Get Continue's global directory (`~/.continue`, or `CONTINUE_GLOBAL_DIR`)
+fn continue_dir() -> Option<PathBuf> {
+    paths::session_env_path("CONTINUE_GLOBAL_DIR")
+        .or_else(|| paths::home_dir().map(|home| home.join(".continue")))
+}
+
+/// Get the newest Continue extension installed in a VS Code variant
+///
+/// Extensions live in `~/.vscode*/extensions/continue.continue-<version>[-<target>]`.
+fn installed_version() -> Option<Version> {
+    let home = paths::home_dir()?;
+    [".vscode", ".vscode-insiders", ".vscode-oss"]
+        .into_iter()
+        .filter_map(|dir| std::fs::read_dir(home.join(dir).join("extensions")).ok())
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("continue.continue-")?
+                .parse()
+                .ok()
+        })
+        .max()
+}
+
+/// Build the server fields shared by both config formats
+fn server_entry(merger: &ConfigMerger) -> JsonValue {
+    let server = merger.server_config();
+    serde_json::json!({
+        "command": server.command,
+        "args": server.args,
+        "env": server.env.clone().unwrap_or_else(|| serde_json::json!({})),
+    })
+}
+
+/// Find the index of our server in the legacy list
+fn legacy_position(servers: &[JsonValue], merger: &ConfigMerger) -> Option<usize> {
+    servers.iter().position(|server| {
+        server
+            .get("transport")
+            .is_some_and(|transport| merger.is_ours(transport))
+    })
+}
+
+/// Add kodegen to a legacy `config.json`
+///
+/// Like other JSON configs, the file is edited in place so comments and
+/// formatting elsewhere survive.
+fn merge_legacy_json(config_content: &str, merger: &ConfigMerger) -> Result<String> {
+    config::ensure_config_len(config_content)?;
+    let content = if config_content.trim().is_empty() {
+        "{}"
+    } else {
+        config_content
+    };
+    let document = jsonc::Document::new(content);
+    let mut config: JsonValue =
+        serde_json::from_str(document.stripped()).context("Failed to parse Continue config")?;
+
+    let mut servers = config::json_list_at(&config, &LEGACY_SERVERS)?;
+    let position = legacy_position(&servers, merger);
+
+    // Entries have no name, so only our own entry can ever be found
+    let found = position.and_then(|index| servers[index].get("transport").cloned());
+    let lookup = |name: &str| found.clone().filter(|_| name == merger.server_name());
+    if merger.resolve_server_name(lookup)?.is_none() {
+        return Ok(config_content.to_string());
+    }
+
+    let mut transport = server_entry(merger);
+    transport["type"] = serde_json::json!("stdio");
+    let server = serde_json::json!({ "transport": transport });
+
+    match position {
+        Some(index) => servers[index] = server,
+        None => servers.push(server),
+    }
+
+    config::set_json_list(&document, &mut config, &LEGACY_SERVERS, servers)
+}
+
+/// Remove kodegen from a legacy `config.json`
+fn remove_legacy_json(config_content: &str, merger: &ConfigMerger) -> Result<String> {
+    config::ensure_config_len(config_content)?;
+    if config_content.trim().is_empty() {
+        return Ok(config_content.to_string());
+    }
+
+    let document = jsonc::Document::new(config_content);
+    let mut config: JsonValue =
+        serde_json::from_str(document.stripped()).context("Failed to parse Continue config")?;
+
+    let Some(mut servers) = config::json_at(&config, &LEGACY_SERVERS)
+        .and_then(JsonValue::as_array)
+        .cloned()
+    else {
+        return Ok(config_content.to_string());
+    };
+
+    let before = servers.len();
+    while let Some(index) = legacy_position(&servers, merger) {
+        servers.remove(index);
+    }
+    if servers.len() == before {
+        return Ok(config_content.to_string());
+    }
+
+    if !servers.is_empty() {
+        return config::set_json_list(&document, &mut config, &LEGACY_SERVERS, servers);
+    }
+
+    if let Some(experimental) = config
+        .get_mut(LEGACY_SERVERS[0])
+        .and_then(JsonValue::as_object_mut)
+    {
+        experimental.remove(LEGACY_SERVERS[1]);
+    }
+    let removed = document.remove(&LEGACY_SERVERS[..1], LEGACY_SERVERS[1]);
+    config::checked_edit(&document, removed, &config)
+}
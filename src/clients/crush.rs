@@ -0,0 +1,87 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{
+    ClientConfigPlugin, ConfigFormat, ConfigPath, MergeTarget, Platform, Scope, paths, validate,
+};
+
+/// Crush, Charm's terminal coding agent
+///
+/// Servers are keyed by name under `mcp` rather than `mcpServers`, with an
+/// explicit `"type": "stdio"`. Projects can also have a `crush.json` of their
+/// own (see [`project_config_path`]).
+pub struct CrushPlugin;
+
+impl ClientConfigPlugin for CrushPlugin {
+    fn client_id(&self) -> &'static str {
+        "crush"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Crush"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        crush_dir().into_iter().collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        crush_dir()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: dir.join("crush.json"),
+                format: ConfigFormat::Json,
+                platform: Platform::All,
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn workspace_config_paths(&self, workspace: &Path) -> Vec<ConfigPath> {
+        vec![ConfigPath {
+            path: project_config_path(workspace),
+            format: ConfigFormat::Json,
+            platform: Platform::All,
+            scope: Scope::Workspace(workspace.to_path_buf()),
+        }]
+    }
+
+    // No process names: each new `crush` session reads the config
+
+    fn is_installed(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn merge_target(&self) -> MergeTarget {
+        MergeTarget::new(["mcp"]).with_entry_field("type", "stdio")
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::at(
+            &["mcp"],
+            validate::server_map(validate::any_server()),
+        ))
+    }
+}
+
+/// Get the config of a project Crush is run in
+///
+/// [`install_for_workspace`](crate::install_for_workspace) writes it to have
+/// kodegen configured for everyone working on the project.
+#[must_use]
+pub fn project_config_path(project_dir: &Path) -> PathBuf {
+    project_dir.join("crush.json")
+}
+
+/// Get Crush's global config directory
+fn crush_dir() -> Option<PathBuf> {
+    match Platform::current() {
+        Platform::Windows => paths::data_dir().map(|data_dir| data_dir.join("crush")),
+        _ => paths::config_home().map(|config_home| config_home.join("crush")),
+    }
+}
@@ -2,9 +2,17 @@ use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 
-use crate::config::ConfigMerger;
-use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform};
+use serde_json::Value as JsonValue;
 
+use crate::config::{ConfigMerger, TransportPreference};
+use crate::paths::Sandbox;
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+/// Cursor, configured through `~/.cursor/mcp.json`
+///
+/// Remote servers are entries with a `url` (and optional `headers`) but no
+/// `type`: Cursor tries streamable HTTP and falls back to SSE by itself. It
+/// expands `${env:NAME}` in headers, which is how an API key is referenced.
 pub struct CursorPlugin;
 
 impl ClientConfigPlugin for CursorPlugin {
@@ -19,38 +27,77 @@ impl ClientConfigPlugin for CursorPlugin {
     fn watch_paths(&self) -> Vec<PathBuf> {
         let mut paths = Vec::new();
 
-        if let Some(base_dirs) = directories::BaseDirs::new() {
+        if let Some(home) = paths::home_dir() {
             // Watch for global config
-            paths.push(base_dirs.home_dir().join(".cursor"));
+            paths.push(home.join(".cursor"));
 
             // Also watch common project locations
             // Common development directories
-            paths.push(base_dirs.home_dir().join("Projects"));
-            paths.push(base_dirs.home_dir().join("projects"));
-            paths.push(base_dirs.home_dir().join("Development"));
-            paths.push(base_dirs.home_dir().join("dev"));
-            paths.push(base_dirs.home_dir().join("code"));
-            paths.push(base_dirs.home_dir().join("workspace"));
+            paths.push(home.join("Projects"));
+            paths.push(home.join("projects"));
+            paths.push(home.join("Development"));
+            paths.push(home.join("dev"));
+            paths.push(home.join("code"));
+            paths.push(home.join("workspace"));
         }
 
+        paths.extend(snap_config_dir());
         paths
     }
 
     fn config_paths(&self) -> Vec<ConfigPath> {
         let mut configs = Vec::new();
 
-        if let Some(base_dirs) = directories::BaseDirs::new() {
+        if let Some(home) = paths::home_dir() {
             // Global config
             configs.push(ConfigPath {
-                path: base_dirs.home_dir().join(".cursor").join("mcp.json"),
+                path: home.join(".cursor").join("mcp.json"),
                 format: ConfigFormat::Json,
                 platform: Platform::All,
+                scope: Scope::User,
             });
         }
 
+        // A Snap install sees its own home; Flatpak shares the real one
+        configs.extend(snap_config_dir().map(|dir| ConfigPath {
+            path: dir.join("mcp.json"),
+            format: ConfigFormat::Json,
+            platform: Platform::Linux,
+            scope: Scope::User,
+        }));
+
         configs
     }
 
+    fn workspace_config_paths(&self, workspace: &Path) -> Vec<ConfigPath> {
+        vec![ConfigPath {
+            path: workspace.join(".cursor").join("mcp.json"),
+            format: ConfigFormat::Json,
+            platform: Platform::All,
+            scope: Scope::Workspace(workspace.to_path_buf()),
+        }]
+    }
+
+    fn process_names(&self) -> &[&str] {
+        &["Cursor", "cursor"]
+    }
+
+    fn windows_app_names(&self) -> &[&str] {
+        &["Cursor"]
+    }
+
+    fn workspace_variable(&self) -> Option<&str> {
+        Some("${workspaceFolder}")
+    }
+
+    fn supported_transports(&self) -> &[TransportPreference] {
+        &[
+            TransportPreference::Stdio,
+            TransportPreference::StreamableHttp,
+            TransportPreference::Sse,
+        ]
+    }
+
     fn is_installed(&self, path: &Path) -> bool {
         // For global config, check if .cursor directory exists
         if path.ends_with(".cursor") {
@@ -62,12 +109,38 @@ impl ClientConfigPlugin for CursorPlugin {
         cursor_dir.exists() && cursor_dir.is_dir()
     }
 
-    fn inject_kodegen(&self, config_content: &str, format: ConfigFormat) -> Result<String> {
-        let merger = ConfigMerger::new();
-        merger.merge(config_content, format)
+    fn inject_kodegen_with(
+        &self,
+        config_content: &str,
+        format: ConfigFormat,
+        merger: &ConfigMerger,
+    ) -> Result<String> {
+        if !merger.transport().is_http() {
+            return Ok(merger.merge(config_content, format)?);
+        }
+
+        let http = merger.http_config();
+        let mut entry = serde_json::json!({ "url": http.url });
+        let headers = http.headers_with(|var| format!("${{env:{var}}}"));
+        if !headers.is_empty() {
+            entry["headers"] = serde_json::json!(headers);
+        }
+
+        merger.merge_json_servers(config_content, "mcpServers", &entry)
     }
 
     fn config_format(&self) -> ConfigFormat {
         ConfigFormat::Json
     }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
+
+/// Get the `.cursor` directory of a Snap install, if there is one
+fn snap_config_dir() -> Option<PathBuf> {
+    Sandbox::Snap("cursor")
+        .home()
+        .map(|home| home.join(".cursor"))
 }
@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, MergeTarget, Platform, Scope, validate};
+
+/// Path of the servers object in `devcontainer.json`
+const SERVERS: [&str; 4] = ["customizations", "vscode", "mcp", "servers"];
+
+/// Dev Containers (VS Code and Codespaces), configured through a workspace's `devcontainer.json`
+///
+/// Servers go under `customizations.vscode.mcp.servers` in VS Code's shape
+/// and are set up in every container built from the definition. Their
+/// command runs inside the container, so kodegen has to be installed in the
+/// image. There are only workspace configs; see [`crate::install_for_workspace`].
+pub struct DevcontainerPlugin;
+
+impl ClientConfigPlugin for DevcontainerPlugin {
+    fn client_id(&self) -> &'static str {
+        "devcontainer"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Dev Containers"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        Vec::new()
+    }
+
+    fn workspace_config_paths(&self, workspace: &Path) -> Vec<ConfigPath> {
+        vec![ConfigPath {
+            path: devcontainer_path(workspace),
+            format: ConfigFormat::Json,
+            platform: Platform::All,
+            scope: Scope::Workspace(workspace.to_path_buf()),
+        }]
+    }
+
+    fn workspace_variable(&self) -> Option<&str> {
+        Some("${containerWorkspaceFolder}")
+    }
+
+    fn is_installed(&self, _path: &Path) -> bool {
+        false
+    }
+
+    fn merge_target(&self) -> MergeTarget {
+        MergeTarget::new(SERVERS).with_entry_field("type", "stdio")
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::at(
+            &SERVERS,
+            validate::server_map(validate::any_server()),
+        ))
+    }
+}
+
+/// Get the container definition of a workspace
+///
+/// `.devcontainer.json` at the root if there is one, otherwise
+/// `.devcontainer/devcontainer.json`.
+#[must_use]
+pub fn devcontainer_path(workspace: &Path) -> PathBuf {
+    let root_config = workspace.join(".devcontainer.json");
+    if root_config.is_file() {
+        return root_config;
+    }
+
+    workspace.join(".devcontainer").join("devcontainer.json")
+}
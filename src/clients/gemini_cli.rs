@@ -0,0 +1,91 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+/// Google's Gemini CLI
+///
+/// User settings live in `~/.gemini/settings.json` and project settings in
+/// `.gemini/settings.json` at a project's root (see [`project_config_path`]),
+/// both with the standard `mcpServers` map.
+pub struct GeminiCliPlugin;
+
+impl ClientConfigPlugin for GeminiCliPlugin {
+    fn client_id(&self) -> &'static str {
+        "gemini-cli"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Gemini CLI"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        paths::home_dir()
+            .map(|home| home.join(".gemini"))
+            .into_iter()
+            .collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        self.watch_paths()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: dir.join("settings.json"),
+                format: ConfigFormat::Json,
+                platform: Platform::All,
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn system_config_paths(&self) -> Vec<ConfigPath> {
+        let dir_name = match Platform::current() {
+            Platform::MacOS => "GeminiCli",
+            _ => "gemini-cli",
+        };
+
+        paths::system_config_dir()
+            .map(|dir| dir.join(dir_name))
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: dir.join("settings.json"),
+                format: ConfigFormat::Json,
+                platform: Platform::current(),
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn workspace_config_paths(&self, workspace: &Path) -> Vec<ConfigPath> {
+        vec![ConfigPath {
+            path: project_config_path(workspace),
+            format: ConfigFormat::Json,
+            platform: Platform::All,
+            scope: Scope::Workspace(workspace.to_path_buf()),
+        }]
+    }
+
+    // No process names: each new `gemini` session reads the settings
+
+    fn is_installed(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
+
+/// Get the project settings file of a project directory
+///
+/// [`install_for_workspace`](crate::install_for_workspace) writes it to have
+/// kodegen configured for everyone working on the project.
+#[must_use]
+pub fn project_config_path(project_dir: &Path) -> PathBuf {
+    project_dir.join(".gemini").join("settings.json")
+}
@@ -0,0 +1,202 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde_json::{Value as JsonValue, json};
+use serde_yaml::Value as YamlValue;
+
+use crate::config::{self, ConfigMerger};
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate, yaml};
+
+/// Seconds Goose waits for a tool call, matching its own default
+const TIMEOUT_SECS: u64 = 300;
+
+/// Goose, Block's open-source agent (CLI and desktop)
+///
+/// MCP servers are Goose "extensions", keyed by name under `extensions` with
+/// their own field names: `cmd`, `args`, `envs`, `type: stdio`.
+pub struct GoosePlugin;
+
+impl ClientConfigPlugin for GoosePlugin {
+    fn client_id(&self) -> &'static str {
+        "goose"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Goose"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        goose_dir().into_iter().collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        goose_dir()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: dir.join("config.yaml"),
+                format: ConfigFormat::Yaml,
+                platform: Platform::All,
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn process_names(&self) -> &[&str] {
+        // Only the desktop app: CLI sessions belong to the user's terminals
+        &["Goose"]
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn inject_kodegen_with(
+        &self,
+        config_content: &str,
+        _format: ConfigFormat,
+        merger: &ConfigMerger,
+    ) -> Result<String> {
+        config::ensure_config_len(config_content)?;
+        let mut config = config::parse_yaml_mapping(config_content)?;
+        if config
+            .get("extensions")
+            .is_some_and(|extensions| !extensions.is_mapping())
+        {
+            bail!("`extensions` in existing Goose config is not a mapping");
+        }
+
+        // Fast path: already configured?
+        let Some(name) = merger.resolve_server_name(|name| {
+            config
+                .get("extensions")
+                .and_then(|extensions| extensions.get(name))
+                .map(as_server_entry)
+        })?
+        else {
+            return Ok(config_content.to_string());
+        };
+
+        let Some(map) = config.as_mapping_mut() else {
+            bail!("Existing YAML config is not a mapping");
+        };
+        let extensions = map
+            .entry("extensions".into())
+            .or_insert_with(|| YamlValue::Mapping(serde_yaml::Mapping::new()))
+            .as_mapping_mut()
+            .context("`extensions` in existing Goose config is not a mapping")?;
+
+        // Keep an extension the user disabled disabled when updating it
+        let enabled = extensions
+            .get(name.as_str())
+            .and_then(|extension| extension.get("enabled"))
+            .and_then(YamlValue::as_bool)
+            .unwrap_or(true);
+
+        let server = merger.server_config();
+        let env = server.env.clone().unwrap_or_else(|| serde_json::json!({}));
+        let mut extension = serde_yaml::Mapping::new();
+        extension.insert("name".into(), YamlValue::String(name.clone()));
+        extension.insert("type".into(), "stdio".into());
+        extension.insert("cmd".into(), YamlValue::String(server.command.clone()));
+        extension.insert("args".into(), serde_yaml::to_value(&server.args)?);
+        extension.insert("envs".into(), serde_yaml::to_value(&env)?);
+        extension.insert("enabled".into(), YamlValue::Bool(enabled));
+        extension.insert("timeout".into(), TIMEOUT_SECS.into());
+        let extension = YamlValue::Mapping(extension);
+        let edited = yaml::upsert_entry(config_content, "extensions", &name, &extension);
+        extensions.insert(YamlValue::String(name), extension);
+
+        config::checked_yaml_edit(edited, &config)
+    }
+
+    fn remove_kodegen(
+        &self,
+        config_content: &str,
+        _format: ConfigFormat,
+        merger: &ConfigMerger,
+    ) -> Result<String> {
+        config::ensure_config_len(config_content)?;
+        if config_content.trim().is_empty() {
+            return Ok(config_content.to_string());
+        }
+
+        let mut config: YamlValue =
+            serde_yaml::from_str(config_content).context("Failed to parse existing YAML")?;
+
+        let Some(map) = config.as_mapping_mut() else {
+            return Ok(config_content.to_string());
+        };
+        let Some(extensions) = map
+            .get_mut("extensions")
+            .and_then(YamlValue::as_mapping_mut)
+        else {
+            return Ok(config_content.to_string());
+        };
+
+        let own: Vec<String> = extensions
+            .iter()
+            .filter_map(|(name, extension)| {
+                name.as_str()
+                    .filter(|name| merger.is_own_entry(name, &as_server_entry(extension)))
+            })
+            .map(str::to_string)
+            .collect();
+        if own.is_empty() {
+            return Ok(config_content.to_string());
+        }
+
+        extensions.retain(|name, _| {
+            !name
+                .as_str()
+                .is_some_and(|name| own.iter().any(|own| own == name))
+        });
+        if extensions.is_empty() {
+            map.remove("extensions");
+        }
+
+        config::checked_yaml_edit(
+            yaml::remove_entries(config_content, "extensions", &own),
+            &config,
+        )
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Yaml
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::at(
+            &["extensions"],
+            validate::server_map(validate::required(
+                &["type"],
+                json!({
+                    "type": { "type": "string", "minLength": 1 },
+                    "cmd": { "type": "string", "minLength": 1 },
+                    "args": validate::string_list(),
+                    "envs": { "type": "object" },
+                    "enabled": { "type": "boolean" },
+                    "timeout": { "type": "integer" },
+                }),
+            )),
+        ))
+    }
+}
+
+/// Get Goose's config directory
+fn goose_dir() -> Option<PathBuf> {
+    match Platform::current() {
+        Platform::Windows => paths::app_config_dir()
+            .map(|app_config| app_config.join("Block").join("goose").join("config")),
+        _ => paths::config_home().map(|config_home| config_home.join("goose")),
+    }
+}
+
+/// View a Goose extension as a standard server entry (`command`, `args`, `env`)
+fn as_server_entry(extension: &YamlValue) -> JsonValue {
+    let extension = config::to_json(extension);
+    serde_json::json!({
+        "command": extension.get("cmd"),
+        "args": extension.get("args"),
+        "env": extension.get("envs"),
+    })
+}
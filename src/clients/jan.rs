@@ -0,0 +1,104 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde_json::Value as JsonValue;
+
+use crate::config::ConfigMerger;
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+/// Jan, the local AI desktop app
+///
+/// Servers live in `mcp_config.json` in Jan's data folder, in the standard
+/// `mcpServers` map plus an `active` flag Jan toggles from its settings page.
+pub struct JanPlugin;
+
+impl ClientConfigPlugin for JanPlugin {
+    fn client_id(&self) -> &'static str {
+        "jan"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Jan"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+
+        if let Some(app_config) = app_data_dir() {
+            paths.push(app_config.join("Jan").join("data"));
+        }
+        if let Some(home) = paths::home_dir() {
+            paths.push(home.join(".jan"));
+        }
+
+        paths
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        self.watch_paths()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: dir.join("mcp_config.json"),
+                format: ConfigFormat::Json,
+                platform: Platform::All,
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn process_names(&self) -> &[&str] {
+        &["Jan", "jan"]
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn inject_kodegen_with(
+        &self,
+        config_content: &str,
+        _format: ConfigFormat,
+        merger: &ConfigMerger,
+    ) -> Result<String> {
+        let server = merger.server_config();
+        let entry = serde_json::json!({
+            "command": server.command,
+            "args": server.args,
+            "env": server.env.clone().unwrap_or_else(|| serde_json::json!({})),
+            "active": is_active(config_content, merger.server_name()),
+        });
+
+        merger.merge_json_servers(config_content, "mcpServers", &entry)
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
+
+/// Get the root of Jan's data folder: the roaming app data on Windows
+fn app_data_dir() -> Option<PathBuf> {
+    match Platform::current() {
+        Platform::Windows => paths::app_config_dir(),
+        _ => paths::data_dir(),
+    }
+}
+
+/// Check whether an existing server entry is active, keeping one the user
+/// switched off switched off when it is updated
+fn is_active(config_content: &str, name: &str) -> bool {
+    serde_json::from_str::<JsonValue>(config_content)
+        .ok()
+        .and_then(|config| {
+            config
+                .get("mcpServers")?
+                .get(name)?
+                .get("active")?
+                .as_bool()
+        })
+        .unwrap_or(true)
+}
@@ -0,0 +1,166 @@
+//! JetBrains IDEs (IntelliJ IDEA, PyCharm, WebStorm, GoLand)
+//!
+//! Installs are detected through the versioned per-IDE config directories
+//! (e.g. `~/.config/JetBrains/PyCharm2024.3`). kodegen is added to the global
+//! MCP config of Junie, JetBrains' coding agent, which every IDE shares, and
+//! to AI Assistant's per-IDE XML store (`options/llm.mcpServers.xml`) in the
+//! newest config directory of each installed IDE. Junie's config is the
+//! primary one; an IDE's store is only updated once it exists.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+/// Product codes (config directory prefixes) of the supported IDEs
+const PRODUCTS: &[&str] = &[
+    "IntelliJIdea",
+    "IdeaIC",
+    "PyCharm",
+    "PyCharmCE",
+    "WebStorm",
+    "GoLand",
+];
+
+pub struct JetBrainsPlugin;
+
+impl ClientConfigPlugin for JetBrainsPlugin {
+    fn client_id(&self) -> &'static str {
+        "jetbrains"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "JetBrains IDEs"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        config_root().into_iter().chain(junie_dir()).collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        let junie = junie_dir().map(|dir| ConfigPath {
+            path: dir.join("mcp").join("mcp.json"),
+            format: ConfigFormat::Json,
+            platform: Platform::All,
+            scope: Scope::User,
+        });
+        let ai_assistant = installed_products().into_iter().map(|dir| ConfigPath {
+            path: dir.path.join("options").join("llm.mcpServers.xml"),
+            format: ConfigFormat::Xml,
+            platform: Platform::All,
+            scope: Scope::User,
+        });
+
+        junie.into_iter().chain(ai_assistant).collect()
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        if config_root().is_some_and(|root| root == path) {
+            return !installed_products().is_empty();
+        }
+        path.is_dir()
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
+
+/// Get Junie's global directory
+fn junie_dir() -> Option<PathBuf> {
+    paths::home_dir().map(|home| home.join(".junie"))
+}
+
+/// Get the newest config directory of each supported IDE that is installed
+#[must_use]
+pub fn installed_products() -> Vec<JetBrainsConfigDir> {
+    newest_config_dirs()
+        .into_iter()
+        .filter(|dir| PRODUCTS.contains(&dir.product.as_str()))
+        .collect()
+}
+
+/// A versioned JetBrains product config directory (e.g. `PyCharm2024.3`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JetBrainsConfigDir {
+    /// Product code as it appears in the directory name (e.g. "IntelliJIdea")
+    pub product: String,
+    /// Release components parsed from the directory name (e.g. `[2024, 3]`)
+    pub version: Vec<u32>,
+    pub path: PathBuf,
+}
+
+/// Get the root directory holding all JetBrains product config directories
+#[must_use]
+pub fn config_root() -> Option<PathBuf> {
+    paths::app_config_dir().map(|app_config| app_config.join("JetBrains"))
+}
+
+/// Enumerate every product config directory across all installed versions
+#[must_use]
+pub fn config_dirs() -> Vec<JetBrainsConfigDir> {
+    let Some(root) = config_root() else {
+        return Vec::new();
+    };
+
+    let Ok(entries) = std::fs::read_dir(&root) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let (product, version) = parse_dir_name(&name)?;
+            Some(JetBrainsConfigDir {
+                product,
+                version,
+                path: entry.path(),
+            })
+        })
+        .collect()
+}
+
+/// Enumerate the newest config directory of each installed product
+#[must_use]
+pub fn newest_config_dirs() -> Vec<JetBrainsConfigDir> {
+    let mut newest: HashMap<String, JetBrainsConfigDir> = HashMap::new();
+
+    for dir in config_dirs() {
+        match newest.get(&dir.product) {
+            Some(existing) if existing.version >= dir.version => {}
+            _ => {
+                newest.insert(dir.product.clone(), dir);
+            }
+        }
+    }
+
+    let mut dirs: Vec<JetBrainsConfigDir> = newest.into_values().collect();
+    dirs.sort_by(|a, b| a.product.cmp(&b.product));
+    dirs
+}
+
+/// Split a directory name like `GoLand2024.3` into its product and version
+fn parse_dir_name(name: &str) -> Option<(String, Vec<u32>)> {
+    let split = name.find(|c: char| c.is_ascii_digit())?;
+    let (product, version) = name.split_at(split);
+
+    if product.is_empty() {
+        return None;
+    }
+
+    let version = version
+        .split('.')
+        .map(str::parse)
+        .collect::<Result<Vec<u32>, _>>()
+        .ok()?;
+
+    Some((product.to_string(), version))
+}
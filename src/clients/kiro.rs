@@ -0,0 +1,78 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+/// Kiro, the AWS agentic IDE
+///
+/// User servers live in `~/.kiro/settings/mcp.json` and workspace servers in
+/// `.kiro/settings/mcp.json` (see [`workspace_config_path`]), both with the
+/// standard `mcpServers` map. Workspace servers take precedence.
+pub struct KiroPlugin;
+
+impl ClientConfigPlugin for KiroPlugin {
+    fn client_id(&self) -> &'static str {
+        "kiro"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Kiro"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        paths::home_dir()
+            .map(|home| home.join(".kiro"))
+            .into_iter()
+            .collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        self.watch_paths()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: settings_path(&dir),
+                format: ConfigFormat::Json,
+                platform: Platform::All,
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn workspace_config_paths(&self, workspace: &Path) -> Vec<ConfigPath> {
+        vec![ConfigPath {
+            path: workspace_config_path(workspace),
+            format: ConfigFormat::Json,
+            platform: Platform::All,
+            scope: Scope::Workspace(workspace.to_path_buf()),
+        }]
+    }
+
+    // No process names: Kiro reconnects MCP servers when mcp.json changes
+
+    fn is_installed(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
+
+/// Get the workspace MCP config of a folder opened in Kiro
+///
+/// [`install_for_workspace`](crate::install_for_workspace) writes it to have
+/// kodegen configured for everyone working on the project.
+#[must_use]
+pub fn workspace_config_path(workspace_dir: &Path) -> PathBuf {
+    settings_path(&workspace_dir.join(".kiro"))
+}
+
+/// Get `settings/mcp.json` within a `.kiro` directory
+fn settings_path(kiro_dir: &Path) -> PathBuf {
+    kiro_dir.join("settings").join("mcp.json")
+}
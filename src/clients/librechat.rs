@@ -0,0 +1,105 @@
+//! LibreChat support
+//!
+//! LibreChat is self-hosted, so it is found by its checkout: a directory
+//! holding `librechat.yaml` (or the `librechat.example.yaml` it is copied
+//! from). Servers go in the standard `mcpServers` mapping of `librechat.yaml`,
+//! where remote servers take a `type` of `streamable-http` or `sse`, a `url`
+//! and optional `headers`. LibreChat expands `${NAME}` from its environment
+//! (`.env`), which is how an API key is referenced.
+//!
+//! The Docker setup bind-mounts the checkout's `librechat.yaml` into the
+//! container, so the same file is edited there, but the `kodegen` command
+//! then has to be available inside the container too.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::config::TransportPreference;
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+/// Checkout directory names, as cloned from GitHub and as commonly lowercased
+const CHECKOUT_NAMES: &[&str] = &["LibreChat", "librechat"];
+
+pub struct LibreChatPlugin;
+
+impl ClientConfigPlugin for LibreChatPlugin {
+    fn client_id(&self) -> &'static str {
+        "librechat"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "LibreChat"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        paths::home_dir()
+            .into_iter()
+            .flat_map(|home| CHECKOUT_NAMES.iter().map(move |name| home.join(name)))
+            .collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        paths::home_dir()
+            .into_iter()
+            .filter_map(|home| config_path_in(&home, Platform::All))
+            .collect()
+    }
+
+    fn system_config_paths(&self) -> Vec<ConfigPath> {
+        // Server installs in the usual places for self-hosted software
+        match Platform::current() {
+            Platform::Linux => ["/opt", "/srv"]
+                .into_iter()
+                .filter_map(|root| config_path_in(Path::new(root), Platform::Linux))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    // No process names: the server (or its container) is restarted by whoever runs it
+
+    fn supported_transports(&self) -> &[TransportPreference] {
+        &[
+            TransportPreference::Stdio,
+            TransportPreference::StreamableHttp,
+            TransportPreference::Sse,
+        ]
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        is_checkout(path)
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Yaml
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
+
+/// Check whether a directory is a LibreChat checkout
+fn is_checkout(dir: &Path) -> bool {
+    ["librechat.yaml", "librechat.example.yaml"]
+        .iter()
+        .any(|file_name| dir.join(file_name).is_file())
+}
+
+/// Get `librechat.yaml` of the LibreChat checkout in `root`, if there is one
+///
+/// Only the first checkout name found counts: on case-insensitive file
+/// systems both names are the same directory.
+fn config_path_in(root: &Path, platform: Platform) -> Option<ConfigPath> {
+    CHECKOUT_NAMES
+        .iter()
+        .map(|name| root.join(name))
+        .find(|dir| is_checkout(dir))
+        .map(|dir| ConfigPath {
+            path: dir.join("librechat.yaml"),
+            format: ConfigFormat::Yaml,
+            platform,
+            scope: Scope::User,
+        })
+}
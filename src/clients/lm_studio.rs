@@ -0,0 +1,59 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+/// LM Studio (0.3.17+), configured through `mcp.json` with the standard
+/// `mcpServers` map
+pub struct LmStudioPlugin;
+
+impl ClientConfigPlugin for LmStudioPlugin {
+    fn client_id(&self) -> &'static str {
+        "lm-studio"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "LM Studio"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        // `~/.lmstudio` on every platform (`%USERPROFILE%\.lmstudio` on Windows)
+        paths::home_dir()
+            .map(|home| home.join(".lmstudio"))
+            .into_iter()
+            .collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        self.watch_paths()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: dir.join("mcp.json"),
+                format: ConfigFormat::Json,
+                platform: Platform::All,
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn process_names(&self) -> &[&str] {
+        &["LM Studio", "lm-studio"]
+    }
+
+    fn windows_app_names(&self) -> &[&str] {
+        &["LM Studio"]
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
@@ -0,0 +1,83 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+/// mcphub.nvim, the MCP hub for Neovim (also used by avante.nvim and CodeCompanion)
+pub struct McpHubPlugin;
+
+impl ClientConfigPlugin for McpHubPlugin {
+    fn client_id(&self) -> &'static str {
+        "mcphub"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "mcphub.nvim"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        mcphub_dir().into_iter().chain(nvim_data_dir()).collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        mcphub_dir()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: dir.join("servers.json"),
+                format: ConfigFormat::Json,
+                platform: Platform::All,
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    // No process names: mcphub watches servers.json and restarts its servers itself
+
+    fn is_installed(&self, path: &Path) -> bool {
+        if nvim_data_dir().is_some_and(|data_dir| data_dir == path) {
+            return plugin_checkouts(path).any(|checkout| checkout.is_dir());
+        }
+        path.is_dir()
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
+
+/// Get mcphub's config directory (`~/.config/mcphub` on every platform)
+fn mcphub_dir() -> Option<PathBuf> {
+    paths::config_home().map(|config_home| config_home.join("mcphub"))
+}
+
+/// Get Neovim's data directory, where plugin managers keep their checkouts
+fn nvim_data_dir() -> Option<PathBuf> {
+    match Platform::current() {
+        Platform::Windows => paths::data_dir().map(|data_dir| data_dir.join("nvim-data")),
+        _ => paths::data_home().map(|data_home| data_home.join("nvim")),
+    }
+}
+
+/// Where lazy.nvim, packer and native packages put an mcphub.nvim checkout
+fn plugin_checkouts(data_dir: &Path) -> impl Iterator<Item = PathBuf> {
+    let pack = data_dir.join("site").join("pack");
+    let packages: Vec<PathBuf> = std::fs::read_dir(&pack)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    std::iter::once(data_dir.join("lazy").join("mcphub.nvim")).chain(
+        packages.into_iter().flat_map(|package| {
+            ["start", "opt"].map(|kind| package.join(kind).join("mcphub.nvim"))
+        }),
+    )
+}
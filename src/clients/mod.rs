@@ -1,21 +1,224 @@
+#[cfg(feature = "aider")]
+pub mod aider;
+pub mod amazon_q;
+pub mod anythingllm;
+pub mod augment;
+pub mod boltai;
+#[cfg(feature = "chatgpt-desktop")]
+pub mod chatgpt_desktop;
+pub mod claude_code;
 pub mod claude_desktop;
+pub mod cline;
+pub mod codex;
+pub mod cody;
+pub mod continue_dev;
+pub mod crush;
 pub mod cursor;
+pub mod devcontainer;
+pub mod gemini_cli;
+pub mod goose;
+pub mod jan;
+pub mod jetbrains;
+pub mod kiro;
+pub mod librechat;
+pub mod lm_studio;
+pub mod mcphub;
+pub mod obsidian;
+pub mod opencode;
+pub mod qodo;
+pub mod raycast;
 pub mod roo_code;
+pub mod sublime_text;
+pub mod tabby;
+pub mod vscode;
+pub mod vscode_forks;
+pub mod vscode_mcp;
+pub mod warp;
 pub mod windsurf;
+pub mod witsy;
+pub mod xcode;
 pub mod zed;
 
-use std::sync::Arc;
+use std::fmt;
+use std::sync::{Arc, LazyLock};
+
+use parking_lot::RwLock;
 
 use crate::ClientConfigPlugin;
 
-/// Get all available client plugins
+/// The registry used by installs, uninstalls, restarts and backup cleanup
+static REGISTRY: LazyLock<RwLock<ClientRegistry>> =
+    LazyLock::new(|| RwLock::new(ClientRegistry::builtin()));
+
+/// Get a snapshot of the process-wide client registry
+///
+/// Starts out as [`ClientRegistry::builtin`]; change it with [`update_registry`].
 #[must_use]
-pub fn all_clients() -> Vec<Arc<dyn ClientConfigPlugin>> {
-    vec![
-        Arc::new(claude_desktop::ClaudeDesktopPlugin),
-        Arc::new(windsurf::WindsurfPlugin),
-        Arc::new(cursor::CursorPlugin),
-        Arc::new(zed::ZedPlugin),
-        Arc::new(roo_code::RooCodePlugin),
-    ]
+pub fn registry() -> ClientRegistry {
+    REGISTRY.read().clone()
+}
+
+/// Change the process-wide client registry, e.g. to add a proprietary client
+pub fn update_registry(f: impl FnOnce(&mut ClientRegistry)) {
+    f(&mut REGISTRY.write());
+}
+
+/// An ordered set of client plugins, unique by client id
+#[derive(Clone, Default)]
+pub struct ClientRegistry {
+    clients: Vec<Arc<dyn ClientConfigPlugin>>,
+}
+
+impl ClientRegistry {
+    /// Create an empty registry
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry holding every built-in client plugin
+    #[must_use]
+    pub fn builtin() -> Self {
+        #[allow(unused_mut)]
+        let mut clients: Vec<Arc<dyn ClientConfigPlugin>> = vec![
+            Arc::new(claude_desktop::ClaudeDesktopPlugin),
+            Arc::new(claude_code::ClaudeCodePlugin),
+            Arc::new(gemini_cli::GeminiCliPlugin),
+            Arc::new(codex::CodexPlugin),
+            Arc::new(crush::CrushPlugin),
+            Arc::new(opencode::OpenCodePlugin),
+            Arc::new(amazon_q::AmazonQPlugin),
+            Arc::new(windsurf::WindsurfPlugin),
+            Arc::new(cursor::CursorPlugin),
+            Arc::new(vscode_forks::PearAiPlugin),
+            Arc::new(vscode_forks::VoidPlugin),
+            Arc::new(kiro::KiroPlugin),
+            Arc::new(zed::ZedPlugin),
+            Arc::new(roo_code::RooCodePlugin),
+            Arc::new(cline::ClinePlugin),
+            Arc::new(vscode_mcp::VsCodeMcpPlugin),
+            Arc::new(devcontainer::DevcontainerPlugin),
+            Arc::new(cody::CodyPlugin),
+            Arc::new(qodo::QodoGenPlugin),
+            Arc::new(augment::AugmentPlugin),
+            Arc::new(tabby::TabbyPlugin),
+            Arc::new(continue_dev::ContinuePlugin),
+            Arc::new(jetbrains::JetBrainsPlugin),
+            Arc::new(mcphub::McpHubPlugin),
+            Arc::new(goose::GoosePlugin),
+            Arc::new(warp::WarpPlugin),
+            Arc::new(librechat::LibreChatPlugin),
+            Arc::new(lm_studio::LmStudioPlugin),
+            Arc::new(jan::JanPlugin),
+            Arc::new(anythingllm::AnythingLlmPlugin),
+            Arc::new(witsy::WitsyPlugin),
+            Arc::new(sublime_text::SublimeTextPlugin),
+            Arc::new(obsidian::ObsidianPlugin),
+            Arc::new(raycast::RaycastPlugin),
+            Arc::new(boltai::BoltAiPlugin),
+            Arc::new(xcode::XcodePlugin),
+        ];
+
+        #[cfg(feature = "chatgpt-desktop")]
+        clients.push(Arc::new(chatgpt_desktop::ChatGptDesktopPlugin));
+
+        #[cfg(feature = "aider")]
+        clients.push(Arc::new(aider::AiderPlugin));
+
+        Self { clients }
+    }
+
+    /// Add a client plugin
+    ///
+    /// A plugin with the same client id is replaced in place and returned.
+    pub fn register(
+        &mut self,
+        client: Box<dyn ClientConfigPlugin>,
+    ) -> Option<Arc<dyn ClientConfigPlugin>> {
+        let client: Arc<dyn ClientConfigPlugin> = Arc::from(client);
+
+        match self.position(client.client_id()) {
+            Some(index) => Some(std::mem::replace(&mut self.clients[index], client)),
+            None => {
+                self.clients.push(client);
+                None
+            }
+        }
+    }
+
+    /// Remove the client plugin with the given id, returning it
+    pub fn unregister(&mut self, client_id: &str) -> Option<Arc<dyn ClientConfigPlugin>> {
+        let index = self.position(client_id)?;
+        Some(self.clients.remove(index))
+    }
+
+    /// Get the client plugin with the given id
+    #[must_use]
+    pub fn get(&self, client_id: &str) -> Option<&Arc<dyn ClientConfigPlugin>> {
+        self.clients
+            .iter()
+            .find(|client| client.client_id() == client_id)
+    }
+
+    /// Get a registry with only the plugins matching `predicate`
+    #[must_use]
+    pub fn filter(&self, predicate: impl Fn(&dyn ClientConfigPlugin) -> bool) -> Self {
+        Self {
+            clients: self
+                .clients
+                .iter()
+                .filter(|client| predicate(client.as_ref()))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Iterate over the plugins in registration order
+    pub fn iter(&self) -> std::slice::Iter<'_, Arc<dyn ClientConfigPlugin>> {
+        self.clients.iter()
+    }
+
+    /// Number of registered plugins
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Whether no plugins are registered
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    fn position(&self, client_id: &str) -> Option<usize> {
+        self.clients
+            .iter()
+            .position(|client| client.client_id() == client_id)
+    }
+}
+
+impl fmt::Debug for ClientRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.clients.iter().map(|client| client.client_id()))
+            .finish()
+    }
+}
+
+impl IntoIterator for ClientRegistry {
+    type Item = Arc<dyn ClientConfigPlugin>;
+    type IntoIter = std::vec::IntoIter<Arc<dyn ClientConfigPlugin>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.clients.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ClientRegistry {
+    type Item = &'a Arc<dyn ClientConfigPlugin>;
+    type IntoIter = std::slice::Iter<'a, Arc<dyn ClientConfigPlugin>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.clients.iter()
+    }
 }
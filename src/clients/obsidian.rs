@@ -0,0 +1,109 @@
+use std::path::{Path, PathBuf};
+
+use log::debug;
+
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+/// Community plugins that act as MCP clients and keep their servers in `data.json`
+const MCP_PLUGIN_IDS: &[&str] = &["smart-composer", "copilot", "obsidian-mcp-client"];
+
+pub struct ObsidianPlugin;
+
+impl ClientConfigPlugin for ObsidianPlugin {
+    fn client_id(&self) -> &'static str {
+        "obsidian"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Obsidian"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = app_dir().into_iter().collect();
+
+        // Watch each vault's plugin directory so newly installed plugins are noticed
+        paths.extend(
+            vault_paths()
+                .into_iter()
+                .map(|vault| vault.join(".obsidian").join("plugins")),
+        );
+
+        paths
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        vault_paths()
+            .into_iter()
+            .flat_map(|vault| {
+                MCP_PLUGIN_IDS
+                    .iter()
+                    .map(move |plugin_id| vault.join(".obsidian").join("plugins").join(plugin_id))
+            })
+            .filter(|plugin_dir| plugin_dir.is_dir())
+            .map(|plugin_dir| ConfigPath {
+                path: plugin_dir.join("data.json"),
+                format: ConfigFormat::Json,
+                platform: Platform::All,
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn process_names(&self) -> &[&str] {
+        &["Obsidian", "obsidian"]
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        // Only vaults with an MCP-capable plugin count as installed
+        if path.ends_with("plugins") {
+            return MCP_PLUGIN_IDS
+                .iter()
+                .any(|plugin_id| path.join(plugin_id).is_dir());
+        }
+
+        false
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
+
+/// Get Obsidian's app directory, which holds the vault registry
+fn app_dir() -> Option<PathBuf> {
+    paths::app_config_dir().map(|app_config| app_config.join("obsidian"))
+}
+
+/// Read the vault locations from Obsidian's `obsidian.json` registry
+fn vault_paths() -> Vec<PathBuf> {
+    let Some(registry_path) = app_dir().map(|dir| dir.join("obsidian.json")) else {
+        return Vec::new();
+    };
+
+    let Ok(content) = std::fs::read_to_string(&registry_path) else {
+        return Vec::new();
+    };
+
+    let registry: serde_json::Value = match serde_json::from_str(&content) {
+        Ok(registry) => registry,
+        Err(e) => {
+            debug!("Failed to parse {}: {e}", registry_path.display());
+            return Vec::new();
+        }
+    };
+
+    registry
+        .get("vaults")
+        .and_then(|v| v.as_object())
+        .into_iter()
+        .flat_map(|vaults| vaults.values())
+        .filter_map(|vault| vault.get("path").and_then(|v| v.as_str()))
+        .map(PathBuf::from)
+        .collect()
+}
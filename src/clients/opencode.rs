@@ -0,0 +1,165 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde_json::{Value as JsonValue, json};
+
+use crate::config::ConfigMerger;
+use crate::{
+    ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, jsonc, paths, validate,
+};
+
+/// OpenCode, the terminal coding agent
+///
+/// Servers are keyed by name under `mcp` as `"type": "local"` entries, whose
+/// `command` is the whole command line and whose variables are in
+/// `environment`.
+pub struct OpenCodePlugin;
+
+impl ClientConfigPlugin for OpenCodePlugin {
+    fn client_id(&self) -> &'static str {
+        "opencode"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "OpenCode"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        // `~/.config/opencode` on every platform
+        paths::config_home()
+            .map(|config_home| config_home.join("opencode"))
+            .into_iter()
+            .collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        self.watch_paths()
+            .into_iter()
+            .map(|dir| {
+                // OpenCode reads either name; keep using the one the user has
+                let jsonc = dir.join("opencode.jsonc");
+                ConfigPath {
+                    path: if jsonc.is_file() {
+                        jsonc
+                    } else {
+                        dir.join("opencode.json")
+                    },
+                    format: ConfigFormat::Json,
+                    platform: Platform::All,
+                    scope: Scope::User,
+                }
+            })
+            .collect()
+    }
+
+    fn workspace_config_paths(&self, workspace: &Path) -> Vec<ConfigPath> {
+        vec![ConfigPath {
+            path: project_config_path(workspace),
+            format: ConfigFormat::Json,
+            platform: Platform::All,
+            scope: Scope::Workspace(workspace.to_path_buf()),
+        }]
+    }
+
+    // No process names: each new `opencode` session reads the config
+
+    fn is_installed(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn inject_kodegen_with(
+        &self,
+        config_content: &str,
+        _format: ConfigFormat,
+        merger: &ConfigMerger,
+    ) -> Result<String> {
+        let server = merger.server_config();
+        let command: Vec<&str> = std::iter::once(server.command.as_str())
+            .chain(server.args.iter().map(String::as_str))
+            .collect();
+        let entry = serde_json::json!({
+            "type": "local",
+            "command": command,
+            "environment": server.env.clone().unwrap_or_else(|| serde_json::json!({})),
+            "enabled": is_enabled(config_content, merger.server_name()),
+        });
+
+        merger.merge_json_servers_as(config_content, "mcp", &entry, as_server_entry)
+    }
+
+    fn remove_kodegen(
+        &self,
+        config_content: &str,
+        _format: ConfigFormat,
+        merger: &ConfigMerger,
+    ) -> Result<String> {
+        merger.remove_json_servers_as(config_content, "mcp", as_server_entry)
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::at(
+            &["mcp"],
+            validate::server_map(server_schema()),
+        ))
+    }
+}
+
+/// Get the config of a project OpenCode is run in
+///
+/// [`install_for_workspace`](crate::install_for_workspace) writes it to have
+/// kodegen configured for everyone working on the project.
+#[must_use]
+pub fn project_config_path(project_dir: &Path) -> PathBuf {
+    project_dir.join("opencode.json")
+}
+
+/// View an OpenCode server as a standard server entry (`command`, `args`, `env`)
+fn as_server_entry(server: &JsonValue) -> JsonValue {
+    let command_line = server
+        .get("command")
+        .and_then(JsonValue::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    serde_json::json!({
+        "command": command_line.first(),
+        "args": command_line.get(1..),
+        "env": server.get("environment"),
+    })
+}
+
+/// Check whether an existing server entry is enabled, keeping one the user
+/// disabled disabled when it is updated
+fn is_enabled(config_content: &str, name: &str) -> bool {
+    serde_json::from_str::<JsonValue>(jsonc::Document::new(config_content).stripped())
+        .ok()
+        .and_then(|config| config.get("mcp")?.get(name)?.get("enabled")?.as_bool())
+        .unwrap_or(true)
+}
+
+/// Schema of a server: a local command line or a remote URL
+fn server_schema() -> JsonValue {
+    let local = validate::required(
+        &["type", "command"],
+        json!({
+            "type": { "enum": ["local"] },
+            "command": { "type": "array", "items": { "type": "string" }, "minItems": 1 },
+            "environment": validate::string_map(),
+            "enabled": { "type": "boolean" },
+        }),
+    );
+    let remote = validate::required(
+        &["type", "url"],
+        json!({
+            "type": { "enum": ["remote"] },
+            "url": { "type": "string", "minLength": 1 },
+            "headers": validate::string_map(),
+            "enabled": { "type": "boolean" },
+        }),
+    );
+    json!({ "anyOf": [local, remote] })
+}
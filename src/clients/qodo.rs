@@ -0,0 +1,74 @@
+//! Qodo Gen (formerly Codiumate)
+//!
+//! The VS Code extension keeps the MCP servers of its agentic mode in
+//! `mcp.json` in its global storage, with the standard `mcpServers` map. The
+//! JetBrains plugin keeps them in the IDE's own settings store, which isn't
+//! written yet.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use super::vscode;
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, validate};
+
+/// Qodo Gen's extension id, naming its global storage directory
+const EXTENSION_ID: &str = "codium.codium";
+
+pub struct QodoGenPlugin;
+
+impl ClientConfigPlugin for QodoGenPlugin {
+    fn client_id(&self) -> &'static str {
+        "qodo-gen"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Qodo Gen"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        // Qodo Gen is a VSCode extension, so we watch VSCode config directories
+        let mut paths = vscode::user_data_dirs();
+        paths.extend(vscode::remote_server_data_dirs());
+        paths
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        // Global storage is shared by all profiles
+        self.watch_paths()
+            .into_iter()
+            .filter(|dir| self.is_installed(dir))
+            .map(|dir| ConfigPath {
+                path: global_storage_dir(&dir).join("mcp.json"),
+                format: ConfigFormat::Json,
+                platform: Platform::current(),
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn process_names(&self) -> &[&str] {
+        vscode::PROCESS_NAMES
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        // Only exists once the extension has been installed and run
+        global_storage_dir(path).is_dir()
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
+
+/// Get Qodo Gen's global storage directory within a VS Code data directory
+fn global_storage_dir(data_dir: &Path) -> PathBuf {
+    data_dir
+        .join("User")
+        .join("globalStorage")
+        .join(EXTENSION_ID)
+}
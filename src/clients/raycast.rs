@@ -0,0 +1,72 @@
+//! Raycast support (macOS only)
+//!
+//! Raycast's own server list lives in its encrypted database, which can't be
+//! written from outside the app. Raycast imports servers from an
+//! `mcp-config.json` with the standard `mcpServers` map in its support
+//! directory, so that is the file kodegen maintains.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+/// Raycast's bundle identifier, naming its support directory
+const BUNDLE_ID: &str = "com.raycast.macos";
+
+pub struct RaycastPlugin;
+
+impl ClientConfigPlugin for RaycastPlugin {
+    fn client_id(&self) -> &'static str {
+        "raycast"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Raycast"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        match Platform::current() {
+            Platform::MacOS => paths::app_config_dir()
+                .map(|app_config| app_config.join(BUNDLE_ID))
+                .into_iter()
+                .collect(),
+            _ => {
+                // Raycast is only available on macOS
+                Vec::new()
+            }
+        }
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        self.watch_paths()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: dir.join("mcp-config.json"),
+                format: ConfigFormat::Json,
+                platform: Platform::MacOS,
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn process_names(&self) -> &[&str] {
+        &["Raycast"]
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        // The support directory outlives an uninstalled app, so require the bundle too
+        path.is_dir()
+            && paths::app_bundle_paths("Raycast.app")
+                .iter()
+                .any(|bundle| bundle.is_dir())
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
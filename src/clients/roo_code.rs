@@ -1,9 +1,9 @@
 use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use serde_json::Value as JsonValue;
 
-use crate::config::ConfigMerger;
-use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform};
+use super::vscode;
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, validate};
 
 pub struct RooCodePlugin;
 
@@ -17,82 +17,35 @@ impl ClientConfigPlugin for RooCodePlugin {
     }
 
     fn watch_paths(&self) -> Vec<PathBuf> {
-        let mut paths = Vec::new();
-
         // Roo Code is a VSCode extension, so we watch VSCode config directories
-        match Platform::current() {
-            Platform::Windows => {
-                if let Ok(appdata) = std::env::var("APPDATA") {
-                    paths.push(PathBuf::from(appdata).join("Code"));
-                }
-            }
-            Platform::MacOS => {
-                if let Some(base_dirs) = directories::BaseDirs::new() {
-                    paths.push(
-                        base_dirs
-                            .home_dir()
-                            .join("Library/Application Support/Code"),
-                    );
-                }
-            }
-            Platform::Linux => {
-                if let Some(base_dirs) = directories::BaseDirs::new() {
-                    paths.push(base_dirs.config_dir().join("Code"));
-                }
-            }
-            Platform::All => {}
-        }
-
+        let mut paths = vscode::user_data_dirs();
+        paths.extend(vscode::remote_server_data_dirs());
         paths
     }
 
     fn config_paths(&self) -> Vec<ConfigPath> {
-        let mut configs = Vec::new();
-
-        // Roo Code stores its MCP config in VSCode's settings
-        match Platform::current() {
-            Platform::Windows => {
-                if let Ok(appdata) = std::env::var("APPDATA") {
-                    configs.push(ConfigPath {
-                        path: PathBuf::from(appdata)
-                            .join("Code")
-                            .join("User")
-                            .join("settings.json"),
-                        format: ConfigFormat::Json,
-                        platform: Platform::Windows,
-                    });
-                }
-            }
-            Platform::MacOS => {
-                if let Some(base_dirs) = directories::BaseDirs::new() {
-                    configs.push(ConfigPath {
-                        path: base_dirs
-                            .home_dir()
-                            .join("Library/Application Support/Code")
-                            .join("User")
-                            .join("settings.json"),
-                        format: ConfigFormat::Json,
-                        platform: Platform::MacOS,
-                    });
-                }
-            }
-            Platform::Linux => {
-                if let Some(base_dirs) = directories::BaseDirs::new() {
-                    configs.push(ConfigPath {
-                        path: base_dirs
-                            .config_dir()
-                            .join("Code")
-                            .join("User")
-                            .join("settings.json"),
-                        format: ConfigFormat::Json,
-                        platform: Platform::Linux,
-                    });
-                }
-            }
-            Platform::All => {}
-        }
+        // Roo Code stores its MCP config in VSCode's settings, and every
+        // VS Code profile has its own settings.json
+        vscode::user_data_dirs()
+            .into_iter()
+            .flat_map(|dir| vscode::profile_settings_paths(&dir.join("User")))
+            // Remote windows read the VS Code Server's machine settings
+            .chain(
+                vscode::remote_server_data_dirs()
+                    .iter()
+                    .map(|dir| vscode::machine_settings_path(dir)),
+            )
+            .map(|path| ConfigPath {
+                path,
+                format: ConfigFormat::Json,
+                platform: Platform::current(),
+                scope: Scope::User,
+            })
+            .collect()
+    }
 
-        configs
+    fn process_names(&self) -> &[&str] {
+        vscode::PROCESS_NAMES
     }
 
     fn is_installed(&self, path: &Path) -> bool {
@@ -111,12 +64,11 @@ impl ClientConfigPlugin for RooCodePlugin {
         global_storage.exists() && global_storage.is_dir()
     }
 
-    fn inject_kodegen(&self, config_content: &str, format: ConfigFormat) -> Result<String> {
-        let merger = ConfigMerger::new();
-        merger.merge(config_content, format)
-    }
-
     fn config_format(&self) -> ConfigFormat {
         ConfigFormat::Json
     }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
 }
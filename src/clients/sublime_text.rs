@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+pub struct SublimeTextPlugin;
+
+impl ClientConfigPlugin for SublimeTextPlugin {
+    fn client_id(&self) -> &'static str {
+        "sublime-text"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Sublime Text"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        data_dirs()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        // The MCP package reads its server list from the user package settings
+        data_dirs()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: dir
+                    .join("Packages")
+                    .join("User")
+                    .join("MCP.sublime-settings"),
+                format: ConfigFormat::Json,
+                platform: Platform::current(),
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn process_names(&self) -> &[&str] {
+        &["sublime_text", "Sublime Text"]
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        // Sublime creates its Packages directory on first launch
+        let packages_dir = path.join("Packages");
+        packages_dir.exists() && packages_dir.is_dir()
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
+
+/// Get Sublime Text's data directories (Sublime Text 4 and 3)
+fn data_dirs() -> Vec<PathBuf> {
+    let Some(app_config) = paths::app_config_dir() else {
+        return Vec::new();
+    };
+
+    let names: [&str; 2] = match Platform::current() {
+        Platform::Linux => ["sublime-text", "sublime-text-3"],
+        _ => ["Sublime Text", "Sublime Text 3"],
+    };
+
+    names
+        .into_iter()
+        .map(|name| app_config.join(name))
+        .collect()
+}
@@ -0,0 +1,56 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+/// Tabby Agent, the client side of self-hosted TabbyML shared by its editor
+/// extensions
+///
+/// kodegen is added to the agent's `config.toml` as an `[mcpServers.kodegen]`
+/// table next to the `[server]` endpoint settings.
+pub struct TabbyPlugin;
+
+impl ClientConfigPlugin for TabbyPlugin {
+    fn client_id(&self) -> &'static str {
+        "tabby"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Tabby"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        // `~/.tabby-client/agent` on every platform
+        paths::home_dir()
+            .map(|home| home.join(".tabby-client").join("agent"))
+            .into_iter()
+            .collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        self.watch_paths()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: dir.join("config.toml"),
+                format: ConfigFormat::Toml,
+                platform: Platform::All,
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    // No process names: the agent runs inside each editor and reloads its config
+
+    fn is_installed(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Toml
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
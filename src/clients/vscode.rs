@@ -0,0 +1,323 @@
+use std::path::{Path, PathBuf};
+
+use log::debug;
+
+use crate::paths::Sandbox;
+use crate::version::Version;
+use crate::{Platform, paths, registry};
+
+/// Process names of every VS Code variant, for extensions that need a restart
+pub const PROCESS_NAMES: &[&str] = &[
+    "Code",
+    "code",
+    "Code - Insiders",
+    "code-insiders",
+    "VSCodium",
+    "codium",
+    "Code - OSS",
+    "code-oss",
+];
+
+/// A VS Code build sharing the VS Code settings layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Variant {
+    /// Human-readable name (e.g., "VSCodium")
+    pub name: &'static str,
+    /// Name of its user data directory in the platform's app config root
+    pub data_dir_name: &'static str,
+    /// Name of its install directory on Windows
+    pub install_dir_name: &'static str,
+    /// Name it's listed under in Windows' installed apps (see [`registry`])
+    pub app_name: &'static str,
+    /// Name of its app bundle on macOS
+    pub bundle_name: &'static str,
+    /// Name of its command-line launcher
+    pub executable: &'static str,
+    /// Flatpak and Snap packages it's published as on Linux
+    pub sandboxes: &'static [Sandbox],
+}
+
+/// Get the VS Code variants extensions can be installed in
+#[must_use]
+pub fn vscode_variants() -> &'static [Variant] {
+    &[
+        Variant {
+            name: "VS Code",
+            data_dir_name: "Code",
+            install_dir_name: "Microsoft VS Code",
+            app_name: "Microsoft Visual Studio Code",
+            bundle_name: "Visual Studio Code.app",
+            executable: "code",
+            sandboxes: &[
+                Sandbox::Flatpak("com.visualstudio.code"),
+                Sandbox::Snap("code"),
+            ],
+        },
+        Variant {
+            name: "VS Code Insiders",
+            data_dir_name: "Code - Insiders",
+            install_dir_name: "Microsoft VS Code Insiders",
+            app_name: "Microsoft Visual Studio Code Insiders",
+            bundle_name: "Visual Studio Code - Insiders.app",
+            executable: "code-insiders",
+            sandboxes: &[Sandbox::Snap("code-insiders")],
+        },
+        Variant {
+            name: "VSCodium",
+            data_dir_name: "VSCodium",
+            install_dir_name: "VSCodium",
+            app_name: "VSCodium",
+            bundle_name: "VSCodium.app",
+            executable: "codium",
+            sandboxes: &[
+                Sandbox::Flatpak("com.vscodium.codium"),
+                Sandbox::Snap("codium"),
+            ],
+        },
+        Variant {
+            name: "Code - OSS",
+            data_dir_name: "Code - OSS",
+            install_dir_name: "Code - OSS",
+            app_name: "Code - OSS",
+            bundle_name: "Code - OSS.app",
+            executable: "code-oss",
+            sandboxes: &[Sandbox::Flatpak("com.visualstudio.code-oss")],
+        },
+    ]
+}
+
+/// Get the user data directories of every VS Code variant, plus Flatpak, Snap
+/// and portable installs
+#[must_use]
+pub fn user_data_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = paths::app_config_dir()
+        .into_iter()
+        .flat_map(|app_config| {
+            vscode_variants()
+                .iter()
+                .map(move |variant| app_config.join(variant.data_dir_name))
+        })
+        .collect();
+
+    // Sandboxed builds keep theirs in the sandbox's config home
+    for variant in vscode_variants() {
+        dirs.extend(
+            variant
+                .sandboxes
+                .iter()
+                .filter_map(|sandbox| sandbox.config_home())
+                .map(|config_home| config_home.join(variant.data_dir_name)),
+        );
+    }
+
+    dirs.extend(portable_user_data_dirs());
+    dirs
+}
+
+/// Get the `settings.json` of every VS Code profile under a `User` directory
+///
+/// The default profile's settings come first, followed by each profile listed
+/// in `globalStorage/storage.json` (stored under `User/profiles/<id>/`).
+#[must_use]
+pub fn profile_settings_paths(user_dir: &Path) -> Vec<PathBuf> {
+    profile_file_paths(user_dir, "settings.json")
+}
+
+/// Get a per-profile file (e.g. `mcp.json`) of every VS Code profile under a `User` directory
+#[must_use]
+pub fn profile_file_paths(user_dir: &Path, file_name: &str) -> Vec<PathBuf> {
+    let mut paths = vec![user_dir.join(file_name)];
+
+    for id in profile_ids(user_dir) {
+        paths.push(user_dir.join("profiles").join(id).join(file_name));
+    }
+
+    paths
+}
+
+/// Read the ids of all non-default profiles from VS Code's global storage
+fn profile_ids(user_dir: &Path) -> Vec<String> {
+    let storage_path = user_dir.join("globalStorage").join("storage.json");
+
+    let Ok(content) = std::fs::read_to_string(&storage_path) else {
+        return Vec::new();
+    };
+
+    let storage: serde_json::Value = match serde_json::from_str(&content) {
+        Ok(storage) => storage,
+        Err(e) => {
+            debug!("Failed to parse {}: {e}", storage_path.display());
+            return Vec::new();
+        }
+    };
+
+    storage
+        .get("userDataProfiles")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|profile| profile.get("location").and_then(|v| v.as_str()))
+        // Profiles stored outside the user data directory are referenced by URI
+        .filter(|location| !location.contains(['/', '\\', ':']))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Get the `user-data` directories of portable-mode VS Code installations
+///
+/// Portable installs keep everything in a `data` directory next to the
+/// executable (`code-portable-data` next to the app bundle on macOS), and
+/// export its location as `VSCODE_PORTABLE` to child processes.
+#[must_use]
+pub fn portable_user_data_dirs() -> Vec<PathBuf> {
+    let mut data_dirs = Vec::new();
+
+    data_dirs.extend(paths::session_env_path("VSCODE_PORTABLE"));
+
+    for executable in ["code", "codium", "code-oss"] {
+        let Some(executable) = paths::find_on_path(executable) else {
+            continue;
+        };
+
+        // Resolve launcher symlinks (e.g. /usr/local/bin/code) to the install dir
+        let executable = executable.canonicalize().unwrap_or(executable);
+
+        for ancestor in executable.ancestors().skip(1).take(4) {
+            if ancestor.extension().is_some_and(|ext| ext == "app") {
+                if let Some(parent) = ancestor.parent() {
+                    data_dirs.push(parent.join("code-portable-data"));
+                }
+                break;
+            }
+            data_dirs.push(ancestor.join("data"));
+        }
+    }
+
+    let mut user_data_dirs: Vec<PathBuf> = Vec::new();
+    for data_dir in data_dirs {
+        let user_data_dir = data_dir.join("user-data");
+        if user_data_dir.is_dir() && !user_data_dirs.contains(&user_data_dir) {
+            debug!("Found portable VS Code data at {}", data_dir.display());
+            user_data_dirs.push(user_data_dir);
+        }
+    }
+
+    user_data_dirs
+}
+
+/// Get the data directories of VS Code Server installations in the home directory
+///
+/// These exist on machines used as Remote-SSH, WSL or dev container targets;
+/// settings that apply to remote windows live in `Machine/settings.json`.
+#[must_use]
+pub fn remote_server_data_dirs() -> Vec<PathBuf> {
+    let Some(home) = paths::home_dir() else {
+        return Vec::new();
+    };
+
+    [
+        ".vscode-server",
+        ".vscode-server-insiders",
+        ".vscodium-server",
+        ".vscode-remote",
+    ]
+    .into_iter()
+    .map(|server_dir| home.join(server_dir).join("data"))
+    .filter(|data_dir| data_dir.is_dir())
+    .collect()
+}
+
+/// Get the machine-wide settings file of a VS Code Server data directory
+#[must_use]
+pub fn machine_settings_path(server_data_dir: &Path) -> PathBuf {
+    server_data_dir.join("Machine").join("settings.json")
+}
+
+/// Get the version of the VS Code build a config file belongs to
+///
+/// The build is told by the user data directory the file is in (e.g.
+/// `Code - Insiders`); portable installs are found next to their `data`
+/// directory. The version is read from the build's `resources/app/package.json`.
+#[must_use]
+pub fn version_for_config(config_path: &Path) -> Option<Version> {
+    // `<install>/data/user-data/...` for portable installs
+    if let Some(install_dir) = config_path
+        .ancestors()
+        .find(|dir| dir.ends_with("data/user-data"))
+        .and_then(|dir| dir.parent()?.parent())
+    {
+        return read_package_version(&install_dir.join("resources"));
+    }
+
+    let variant = config_path.ancestors().find_map(|dir| {
+        let name = dir.file_name()?;
+        vscode_variants()
+            .iter()
+            .find(|variant| name == variant.data_dir_name)
+    })?;
+    resource_dirs(variant)
+        .iter()
+        .find_map(|resources| read_package_version(resources))
+}
+
+/// Read the version from `app/package.json` in a build's `resources` directory
+fn read_package_version(resources: &Path) -> Option<Version> {
+    let package = resources.join("app").join("package.json");
+    let content = std::fs::read_to_string(&package).ok()?;
+    let package: serde_json::Value = serde_json::from_str(&content).ok()?;
+    package.get("version")?.as_str()?.parse().ok()
+}
+
+/// Get the `resources` directories a VS Code variant is commonly installed with
+fn resource_dirs(variant: &Variant) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    match Platform::current() {
+        Platform::Windows => {
+            // Installs in a custom location are only known to the registry
+            dirs.extend(
+                registry::find_app(&[variant.app_name])
+                    .and_then(|app| app.install_location)
+                    .map(|dir| dir.join("resources")),
+            );
+            let roots = paths::data_dir()
+                .map(|dir| dir.join("Programs"))
+                .into_iter()
+                .chain(std::env::var_os("ProgramFiles").map(PathBuf::from));
+            for root in roots {
+                dirs.push(root.join(variant.install_dir_name).join("resources"));
+            }
+        }
+        Platform::MacOS => {
+            dirs.extend(
+                paths::app_bundle_paths(variant.bundle_name)
+                    .into_iter()
+                    .map(|app| app.join("Contents").join("Resources")),
+            );
+        }
+        Platform::Linux | Platform::All => {
+            let name = variant.executable;
+            dirs.push(PathBuf::from("/usr/share").join(name).join("resources"));
+            dirs.push(
+                PathBuf::from("/snap")
+                    .join(name)
+                    .join("current/usr/share")
+                    .join(name)
+                    .join("resources"),
+            );
+        }
+    }
+
+    // The launcher lives in `bin/` of the install (symlinked from e.g. /usr/bin)
+    if let Some(launcher) = paths::find_on_path(variant.executable) {
+        let launcher = launcher.canonicalize().unwrap_or(launcher);
+        dirs.extend(
+            launcher
+                .parent()
+                .and_then(Path::parent)
+                .map(|dir| dir.join("resources")),
+        );
+    }
+
+    dirs
+}
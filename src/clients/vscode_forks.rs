@@ -0,0 +1,108 @@
+//! VS Code forks that keep the VS Code settings layout: a user data
+//! directory of their own, with per-profile `settings.json` files holding the
+//! standard `mcpServers` map
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use super::vscode;
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+/// PearAI, the open-source AI code editor
+pub struct PearAiPlugin;
+
+impl ClientConfigPlugin for PearAiPlugin {
+    fn client_id(&self) -> &'static str {
+        "pearai"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "PearAI"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        user_data_dir("PearAI").into_iter().collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        settings_paths(self.watch_paths())
+    }
+
+    fn process_names(&self) -> &[&str] {
+        &["PearAI", "pearai"]
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        // The AI features ship as an extension whose storage appears on first run
+        path.join("User")
+            .join("globalStorage")
+            .join("pearai.pearai")
+            .is_dir()
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
+
+/// Void, the open-source Cursor alternative
+pub struct VoidPlugin;
+
+impl ClientConfigPlugin for VoidPlugin {
+    fn client_id(&self) -> &'static str {
+        "void"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Void"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        user_data_dir("Void").into_iter().collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        settings_paths(self.watch_paths())
+    }
+
+    fn process_names(&self) -> &[&str] {
+        &["Void", "void"]
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        // Void's AI features are built in rather than an extension
+        path.join("User").is_dir()
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
+
+/// Get a fork's user data directory, named like VS Code's `Code`
+fn user_data_dir(dir_name: &str) -> Option<PathBuf> {
+    paths::app_config_dir().map(|app_config| app_config.join(dir_name))
+}
+
+/// Get the `settings.json` of every profile in the given user data directories
+fn settings_paths(user_data_dirs: Vec<PathBuf>) -> Vec<ConfigPath> {
+    user_data_dirs
+        .into_iter()
+        .flat_map(|dir| vscode::profile_settings_paths(&dir.join("User")))
+        .map(|path| ConfigPath {
+            path,
+            format: ConfigFormat::Json,
+            platform: Platform::current(),
+            scope: Scope::User,
+        })
+        .collect()
+}
@@ -0,0 +1,310 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+use serde_json::{Value as JsonValue, json};
+
+use super::vscode;
+use crate::config::{self, ConfigMerger, TransportPreference};
+use crate::version::Version;
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, jsonc, validate};
+
+/// Id of the `inputs` entry VS Code prompts for the API key with
+const API_KEY_INPUT: &str = "kodegen-api-key";
+
+/// First release with MCP support, reading user servers from `mcp` in `settings.json`
+const FIRST_MCP_VERSION: Version = Version::new(1, 99, 0);
+
+/// First release reading user servers from `mcp.json`
+const MCP_JSON_VERSION: Version = Version::new(1, 102, 0);
+
+/// VS Code's built-in MCP support (1.99+), configured through `mcp.json`
+///
+/// Unlike the `mcpServers` clients, servers live under `servers` and carry an
+/// explicit `"type"` (`stdio`, or `http`/`sse` for a URL). An API key is read
+/// through a password `inputs` entry VS Code prompts for once and keeps in its
+/// secret storage, rather than from the environment. Workspace configs (`.vscode/mcp.json`, see
+/// [`workspace_config_path`]) are only managed when declared in the settings.
+///
+/// Before 1.102, user servers were kept in `settings.json` under
+/// `mcp.servers` (with `mcp.inputs`); builds that old are configured there.
+pub struct VsCodeMcpPlugin;
+
+impl ClientConfigPlugin for VsCodeMcpPlugin {
+    fn client_id(&self) -> &'static str {
+        "vscode"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "VS Code"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        vscode::user_data_dirs()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        // Every VS Code profile has its own mcp.json (or settings.json before 1.102)
+        vscode::user_data_dirs()
+            .into_iter()
+            .flat_map(|dir| {
+                let user_dir = dir.join("User");
+                match vscode::version_for_config(&dir) {
+                    Some(version) if version < FIRST_MCP_VERSION => Vec::new(),
+                    Some(version) if version < MCP_JSON_VERSION => {
+                        vscode::profile_settings_paths(&user_dir)
+                    }
+                    _ => vscode::profile_file_paths(&user_dir, "mcp.json"),
+                }
+            })
+            .map(|path| ConfigPath {
+                path,
+                format: ConfigFormat::Json,
+                platform: Platform::current(),
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn workspace_config_paths(&self, workspace: &Path) -> Vec<ConfigPath> {
+        vec![ConfigPath {
+            path: workspace_config_path(workspace),
+            format: ConfigFormat::Json,
+            platform: Platform::All,
+            scope: Scope::Workspace(workspace.to_path_buf()),
+        }]
+    }
+
+    // No process names: VS Code picks up mcp.json changes without a restart
+
+    fn windows_app_names(&self) -> &[&str] {
+        // Installers list `... (User)` for per-user installs; shortcuts drop the `Microsoft`
+        &[
+            "Microsoft Visual Studio Code",
+            "Microsoft Visual Studio Code Insiders",
+            "Visual Studio Code",
+            "Visual Studio Code - Insiders",
+            "VSCodium",
+        ]
+    }
+
+    fn workspace_variable(&self) -> Option<&str> {
+        Some("${workspaceFolder}")
+    }
+
+    fn detect_version(&self, config_path: &Path) -> Option<Version> {
+        vscode::version_for_config(config_path)
+    }
+
+    fn supported_transports(&self) -> &[TransportPreference] {
+        &[
+            TransportPreference::Stdio,
+            TransportPreference::StreamableHttp,
+            TransportPreference::Sse,
+        ]
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        path.join("User").is_dir()
+    }
+
+    fn inject_kodegen_with(
+        &self,
+        config_content: &str,
+        _format: ConfigFormat,
+        merger: &ConfigMerger,
+    ) -> Result<String> {
+        // VS Code format: https://code.visualstudio.com/docs/copilot/chat/mcp-servers
+        let entry = match merger.transport() {
+            TransportPreference::Stdio => {
+                let server = merger.server_config();
+                serde_json::json!({
+                    "type": "stdio",
+                    "command": server.command,
+                    "args": server.args,
+                    "env": server.env.clone().unwrap_or_else(|| serde_json::json!({})),
+                })
+            }
+            transport => {
+                let http = merger.http_config();
+                let mut entry = serde_json::json!({
+                    "type": if transport == TransportPreference::Sse { "sse" } else { "http" },
+                    "url": http.url,
+                });
+                let headers = http.headers_with(|_| format!("${{input:{API_KEY_INPUT}}}"));
+                if !headers.is_empty() {
+                    entry["headers"] = serde_json::json!(headers);
+                }
+                entry
+            }
+        };
+
+        let root = mcp_root(merger);
+        let merged = merger.merge_json_servers_at(
+            config_content,
+            &[root, &["servers"]].concat(),
+            &entry,
+            |entry| entry.clone(),
+        )?;
+        if references_api_key_input(&entry) {
+            let description = match &merger.http_config().api_key_env {
+                Some(var) => format!("KODEGEN.ᴀɪ API key ({var})"),
+                None => "KODEGEN.ᴀɪ API key".to_string(),
+            };
+            return add_api_key_input(&merged, root, &description);
+        }
+
+        Ok(merged)
+    }
+
+    fn remove_kodegen(
+        &self,
+        config_content: &str,
+        _format: ConfigFormat,
+        merger: &ConfigMerger,
+    ) -> Result<String> {
+        let root = mcp_root(merger);
+        let removed = merger.remove_json_servers_at(
+            config_content,
+            &[root, &["servers"]].concat(),
+            |entry| entry.clone(),
+        )?;
+        if removed == config_content {
+            return Ok(removed);
+        }
+
+        remove_api_key_input(&removed, root)
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        let servers = validate::server_map(validate::any_server());
+        Some(validate::object(json!({
+            "servers": servers,
+            "mcp": validate::object(json!({ "servers": servers })),
+            "inputs": { "type": "array", "items": { "type": "object" } },
+        })))
+    }
+}
+
+/// Get the workspace MCP config of a folder opened in VS Code
+#[must_use]
+pub fn workspace_config_path(workspace_dir: &Path) -> PathBuf {
+    workspace_dir.join(".vscode").join("mcp.json")
+}
+
+/// Get the object holding `servers` and `inputs` in the config being edited
+///
+/// That's `mcp` in the `settings.json` of builds before 1.102, and the whole
+/// file otherwise (workspace configs have no detected version).
+fn mcp_root(merger: &ConfigMerger) -> &'static [&'static str] {
+    match merger.client_version() {
+        Some(version) if version < MCP_JSON_VERSION => &["mcp"],
+        _ => &[],
+    }
+}
+
+/// Check whether a server entry reads the API key from our input
+fn references_api_key_input(entry: &JsonValue) -> bool {
+    let reference = format!("${{input:{API_KEY_INPUT}}}");
+    entry
+        .get("headers")
+        .and_then(JsonValue::as_object)
+        .is_some_and(|headers| {
+            headers.values().any(|value| {
+                value
+                    .as_str()
+                    .is_some_and(|value| value.contains(&reference))
+            })
+        })
+}
+
+/// Add the password input the API key is prompted with to the `inputs` under `root`,
+/// unless it's already there
+fn add_api_key_input(content: &str, root: &[&str], description: &str) -> Result<String> {
+    let document = jsonc::Document::new(content);
+    let mut config: JsonValue = serde_json::from_str(document.stripped())?;
+    let mut inputs = match config::json_at(&config, root).and_then(|root| root.get("inputs")) {
+        None => Vec::new(),
+        Some(JsonValue::Array(inputs)) => inputs.clone(),
+        Some(_) => bail!("`inputs` in existing config is not a list"),
+    };
+    if inputs.iter().any(is_api_key_input) {
+        return Ok(content.to_string());
+    }
+
+    inputs.push(serde_json::json!({
+        "type": "promptString",
+        "id": API_KEY_INPUT,
+        "description": description,
+        "password": true,
+    }));
+    let inputs = JsonValue::Array(inputs);
+    let parent = root.iter().fold(&mut config, |value, key| &mut value[*key]);
+    parent["inputs"] = inputs.clone();
+
+    config::checked_edit(
+        &document,
+        document.upsert(root, "inputs", &inputs).map(Some),
+        &config,
+    )
+}
+
+/// Remove our API key input under `root` once no server reads it, dropping
+/// `inputs` (and a `root` object left empty) if that empties it
+fn remove_api_key_input(content: &str, root: &[&str]) -> Result<String> {
+    let document = jsonc::Document::new(content);
+    let mut config: JsonValue = serde_json::from_str(document.stripped())?;
+    let Some(parent) = config::json_at(&config, root) else {
+        return Ok(content.to_string());
+    };
+
+    let still_used = parent
+        .get("servers")
+        .and_then(JsonValue::as_object)
+        .is_some_and(|servers| servers.values().any(references_api_key_input));
+    let Some(inputs) = parent.get("inputs").and_then(JsonValue::as_array) else {
+        return Ok(content.to_string());
+    };
+    if still_used || !inputs.iter().any(is_api_key_input) {
+        return Ok(content.to_string());
+    }
+
+    let inputs: Vec<JsonValue> = inputs
+        .iter()
+        .filter(|input| !is_api_key_input(input))
+        .cloned()
+        .collect();
+    let Some(obj) = root
+        .iter()
+        .try_fold(&mut config, |value, key| value.get_mut(key))
+        .and_then(JsonValue::as_object_mut)
+    else {
+        return Ok(content.to_string());
+    };
+
+    if !inputs.is_empty() {
+        let inputs = JsonValue::Array(inputs);
+        obj.insert("inputs".to_string(), inputs.clone());
+        return config::checked_edit(
+            &document,
+            document.upsert(root, "inputs", &inputs).map(Some),
+            &config,
+        );
+    }
+
+    obj.remove("inputs");
+    let emptied = !root.is_empty() && obj.is_empty();
+    let removed = config::checked_edit(&document, document.remove(root, "inputs"), &config)?;
+    if !emptied {
+        return Ok(removed);
+    }
+    config::remove_blank_objects(removed, root)
+}
+
+/// Check whether an `inputs` entry is the one we added
+fn is_api_key_input(input: &JsonValue) -> bool {
+    input.get("id").and_then(JsonValue::as_str) == Some(API_KEY_INPUT)
+}
@@ -0,0 +1,63 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+/// Warp, the agentic terminal
+///
+/// Warp reads file-based MCP servers from `mcp.json` in its data directory,
+/// using the standard `mcpServers` map.
+pub struct WarpPlugin;
+
+impl ClientConfigPlugin for WarpPlugin {
+    fn client_id(&self) -> &'static str {
+        "warp"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Warp"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        warp_dir().into_iter().collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        self.watch_paths()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: dir.join("mcp.json"),
+                format: ConfigFormat::Json,
+                platform: Platform::current(),
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    // No process names: restarting a terminal would kill the user's shells.
+    // Warp picks the server up from its MCP servers page instead.
+
+    fn is_installed(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
+
+/// Get Warp's data directory (where it also keeps themes and launch configurations)
+fn warp_dir() -> Option<PathBuf> {
+    match Platform::current() {
+        Platform::Windows => paths::app_config_dir()
+            .map(|app_config| app_config.join("warp").join("Warp").join("data")),
+        Platform::MacOS => paths::home_dir().map(|home| home.join(".warp")),
+        Platform::Linux => paths::data_home().map(|data_home| data_home.join("warp-terminal")),
+        Platform::All => None,
+    }
+}
@@ -1,9 +1,8 @@
 use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use serde_json::Value as JsonValue;
 
-use crate::config::ConfigMerger;
-use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform};
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
 
 pub struct WindsurfPlugin;
 
@@ -19,9 +18,9 @@ impl ClientConfigPlugin for WindsurfPlugin {
     fn watch_paths(&self) -> Vec<PathBuf> {
         let mut paths = Vec::new();
 
-        if let Some(base_dirs) = directories::BaseDirs::new() {
+        if let Some(home) = paths::home_dir() {
             // Windsurf uses ~/.codeium/windsurf on all platforms
-            paths.push(base_dirs.home_dir().join(".codeium").join("windsurf"));
+            paths.push(home.join(".codeium").join("windsurf"));
         }
 
         paths
@@ -30,32 +29,39 @@ impl ClientConfigPlugin for WindsurfPlugin {
     fn config_paths(&self) -> Vec<ConfigPath> {
         let mut configs = Vec::new();
 
-        if let Some(base_dirs) = directories::BaseDirs::new() {
+        if let Some(home) = paths::home_dir() {
             configs.push(ConfigPath {
-                path: base_dirs
-                    .home_dir()
+                path: home
                     .join(".codeium")
                     .join("windsurf")
                     .join("mcp_config.json"),
                 format: ConfigFormat::Json,
                 platform: Platform::All,
+                scope: Scope::User,
             });
         }
 
         configs
     }
 
+    fn process_names(&self) -> &[&str] {
+        &["Windsurf", "windsurf"]
+    }
+
+    fn windows_app_names(&self) -> &[&str] {
+        &["Windsurf"]
+    }
+
     fn is_installed(&self, path: &Path) -> bool {
         // Windsurf is installed if the windsurf directory exists
         path.exists() && path.is_dir()
     }
 
-    fn inject_kodegen(&self, config_content: &str, format: ConfigFormat) -> Result<String> {
-        let merger = ConfigMerger::new();
-        merger.merge(config_content, format)
-    }
-
     fn config_format(&self) -> ConfigFormat {
         ConfigFormat::Json
     }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
 }
@@ -0,0 +1,198 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde_json::{Value as JsonValue, json};
+
+use crate::config::{self, ConfigMerger};
+use crate::{
+    ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, jsonc, paths, validate,
+};
+
+/// Witsy, the desktop AI assistant
+///
+/// Servers are a list in `settings.json` under `mcp.servers`, identified by
+/// `uuid`, with the arguments joined into one `url` string and a `state` the
+/// user toggles.
+pub struct WitsyPlugin;
+
+impl ClientConfigPlugin for WitsyPlugin {
+    fn client_id(&self) -> &'static str {
+        "witsy"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Witsy"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        paths::app_config_dir()
+            .map(|app_config| app_config.join("Witsy"))
+            .into_iter()
+            .collect()
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        self.watch_paths()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: dir.join("settings.json"),
+                format: ConfigFormat::Json,
+                platform: Platform::All,
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    fn process_names(&self) -> &[&str] {
+        &["Witsy", "witsy"]
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn inject_kodegen_with(
+        &self,
+        config_content: &str,
+        _format: ConfigFormat,
+        merger: &ConfigMerger,
+    ) -> Result<String> {
+        config::ensure_config_len(config_content)?;
+        let mut config: JsonValue = if config_content.trim().is_empty() {
+            serde_json::json!({})
+        } else {
+            serde_json::from_str(config_content).context("Failed to parse Witsy settings")?
+        };
+
+        let servers = match config.pointer("/mcp/servers") {
+            Some(servers) => servers
+                .as_array()
+                .context("`mcp.servers` in Witsy settings is not an array")?
+                .clone(),
+            None => Vec::new(),
+        };
+        let by_uuid = |uuid: &str| {
+            servers
+                .iter()
+                .position(|server| server.get("uuid").and_then(JsonValue::as_str) == Some(uuid))
+        };
+
+        let Some(name) = merger.resolve_server_name(|name| {
+            by_uuid(name).map(|index| as_server_entry(&servers[index]))
+        })?
+        else {
+            return Ok(config_content.to_string());
+        };
+        let position = by_uuid(&name);
+
+        // Keep a server the user disabled disabled when updating it
+        let state = position
+            .and_then(|index| servers[index].get("state").cloned())
+            .unwrap_or_else(|| serde_json::json!("enabled"));
+
+        let server = merger.server_config();
+        let entry = serde_json::json!({
+            "uuid": name,
+            "registryId": name,
+            "state": state,
+            "type": "stdio",
+            "command": server.command,
+            "url": server.args.join(" "),
+            "env": server.env.clone().unwrap_or_else(|| serde_json::json!({})),
+        });
+
+        let Some(root) = config.as_object_mut() else {
+            bail!("Existing Witsy settings are not an object");
+        };
+        let servers = root
+            .entry("mcp")
+            .or_insert_with(|| serde_json::json!({}))
+            .as_object_mut()
+            .context("`mcp` in Witsy settings is not an object")?
+            .entry("servers")
+            .or_insert_with(|| serde_json::json!([]))
+            .as_array_mut()
+            .context("`mcp.servers` in Witsy settings is not an array")?;
+
+        match position {
+            Some(index) => servers[index] = entry,
+            None => servers.push(entry),
+        }
+
+        jsonc::Document::new(config_content)
+            .rewrite(&config)
+            .context("Failed to serialize Witsy settings")
+    }
+
+    fn remove_kodegen(
+        &self,
+        config_content: &str,
+        _format: ConfigFormat,
+        merger: &ConfigMerger,
+    ) -> Result<String> {
+        config::ensure_config_len(config_content)?;
+        if config_content.trim().is_empty() {
+            return Ok(config_content.to_string());
+        }
+
+        let mut config: JsonValue =
+            serde_json::from_str(config_content).context("Failed to parse Witsy settings")?;
+        let Some(servers) = config
+            .pointer_mut("/mcp/servers")
+            .and_then(JsonValue::as_array_mut)
+        else {
+            return Ok(config_content.to_string());
+        };
+
+        let before = servers.len();
+        servers.retain(|server| {
+            !server
+                .get("uuid")
+                .and_then(JsonValue::as_str)
+                .is_some_and(|uuid| merger.is_own_entry(uuid, &as_server_entry(server)))
+        });
+        if servers.len() == before {
+            return Ok(config_content.to_string());
+        }
+
+        jsonc::Document::new(config_content)
+            .rewrite(&config)
+            .context("Failed to serialize Witsy settings")
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Json
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::at(
+            &["mcp", "servers"],
+            validate::server_list(validate::required(
+                &["uuid", "type"],
+                json!({
+                    "uuid": { "type": "string", "minLength": 1 },
+                    "type": { "type": "string" },
+                    "state": { "enum": ["enabled", "disabled"] },
+                    "command": { "type": "string" },
+                    "url": { "type": "string" },
+                    "env": { "type": "object" },
+                }),
+            )),
+        ))
+    }
+}
+
+/// View a Witsy server as a standard server entry (`command`, `args`, `env`)
+fn as_server_entry(server: &JsonValue) -> JsonValue {
+    let args: Vec<&str> = server
+        .get("url")
+        .and_then(JsonValue::as_str)
+        .map(|url| url.split_whitespace().collect())
+        .unwrap_or_default();
+
+    serde_json::json!({
+        "command": server.get("command"),
+        "args": args,
+        "env": server.get("env"),
+    })
+}
@@ -0,0 +1,82 @@
+//! Xcode support (macOS only)
+//!
+//! Xcode 26's coding intelligence reads MCP servers from an `mcpServers`
+//! dictionary in Xcode's preferences domain, merged like BoltAI's. As there,
+//! only XML preference files can be merged for now.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform, Scope, paths, validate};
+
+/// Xcode's bundle identifier, naming its preferences domain
+const BUNDLE_ID: &str = "com.apple.dt.Xcode";
+
+pub struct XcodePlugin;
+
+impl ClientConfigPlugin for XcodePlugin {
+    fn client_id(&self) -> &'static str {
+        "xcode"
+    }
+
+    fn client_name(&self) -> &'static str {
+        "Xcode"
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        // UserData rather than all of ~/Library/Developer/Xcode, where builds churn DerivedData
+        match Platform::current() {
+            Platform::MacOS => paths::home_dir()
+                .map(|home| home.join("Library/Developer/Xcode/UserData"))
+                .into_iter()
+                .collect(),
+            _ => {
+                // Xcode is only available on macOS
+                Vec::new()
+            }
+        }
+    }
+
+    fn config_paths(&self) -> Vec<ConfigPath> {
+        match Platform::current() {
+            Platform::MacOS => paths::home_dir()
+                .map(|home| {
+                    home.join("Library")
+                        .join("Preferences")
+                        .join(format!("{BUNDLE_ID}.plist"))
+                })
+                .into_iter()
+                .map(|path| ConfigPath {
+                    path,
+                    format: ConfigFormat::Plist,
+                    platform: Platform::MacOS,
+                    scope: Scope::User,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn process_names(&self) -> &[&str] {
+        &["Xcode"]
+    }
+
+    fn is_installed(&self, path: &Path) -> bool {
+        // UserData outlives an uninstalled Xcode, so require the app too
+        path.is_dir()
+            && ["Xcode.app", "Xcode-beta.app"].iter().any(|bundle_name| {
+                paths::app_bundle_paths(bundle_name)
+                    .iter()
+                    .any(|bundle| bundle.is_dir())
+            })
+    }
+
+    fn config_format(&self) -> ConfigFormat {
+        ConfigFormat::Plist
+    }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::mcp_servers_schema())
+    }
+}
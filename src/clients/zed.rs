@@ -1,8 +1,11 @@
 use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use serde_json::{Value as JsonValue, json};
 
-use crate::{ClientConfigPlugin, ConfigFormat, ConfigPath, Platform};
+use crate::paths::Sandbox;
+use crate::{
+    ClientConfigPlugin, ConfigFormat, ConfigPath, MergeTarget, Platform, Scope, paths, validate,
+};
 
 pub struct ZedPlugin;
 
@@ -20,16 +23,23 @@ impl ClientConfigPlugin for ZedPlugin {
 
         match Platform::current() {
             Platform::MacOS => {
-                if let Some(base_dirs) = directories::BaseDirs::new() {
-                    paths.push(base_dirs.home_dir().join(".config").join("zed"));
-                    // Also check macOS-specific location
-                    paths.push(base_dirs.home_dir().join("Library/Application Support/Zed"));
+                if let Some(config_home) = paths::config_home() {
+                    paths.push(config_home.join("zed"));
+                }
+                // Also check macOS-specific location
+                if let Some(app_config) = paths::app_config_dir() {
+                    paths.push(app_config.join("Zed"));
                 }
             }
             Platform::Linux => {
-                if let Some(base_dirs) = directories::BaseDirs::new() {
-                    paths.push(base_dirs.config_dir().join("zed"));
+                if let Some(config_home) = paths::config_home() {
+                    paths.push(config_home.join("zed"));
                 }
+                paths.extend(
+                    Sandbox::Flatpak("dev.zed.Zed")
+                        .config_home()
+                        .map(|config_home| config_home.join("zed")),
+                );
             }
             _ => {
                 // Zed doesn't support Windows yet
@@ -40,93 +50,52 @@ impl ClientConfigPlugin for ZedPlugin {
     }
 
     fn config_paths(&self) -> Vec<ConfigPath> {
-        let mut configs = Vec::new();
-
-        match Platform::current() {
-            Platform::MacOS => {
-                if let Some(base_dirs) = directories::BaseDirs::new() {
-                    configs.push(ConfigPath {
-                        path: base_dirs
-                            .home_dir()
-                            .join(".config")
-                            .join("zed")
-                            .join("settings.json"),
-                        format: ConfigFormat::Json,
-                        platform: Platform::MacOS,
-                    });
-
-                    configs.push(ConfigPath {
-                        path: base_dirs
-                            .home_dir()
-                            .join("Library/Application Support/Zed")
-                            .join("settings.json"),
-                        format: ConfigFormat::Json,
-                        platform: Platform::MacOS,
-                    });
-                }
-            }
-            Platform::Linux => {
-                if let Some(base_dirs) = directories::BaseDirs::new() {
-                    configs.push(ConfigPath {
-                        path: base_dirs.config_dir().join("zed").join("settings.json"),
-                        format: ConfigFormat::Json,
-                        platform: Platform::Linux,
-                    });
-                }
-            }
-            _ => {}
-        }
+        self.watch_paths()
+            .into_iter()
+            .map(|dir| ConfigPath {
+                path: dir.join("settings.json"),
+                format: ConfigFormat::Json,
+                platform: Platform::current(),
+                scope: Scope::User,
+            })
+            .collect()
+    }
 
-        configs
+    fn process_names(&self) -> &[&str] {
+        &["zed", "Zed", "zed-editor"]
     }
 
     fn is_installed(&self, path: &Path) -> bool {
         path.exists() && path.is_dir()
     }
 
-    fn inject_kodegen(&self, config_content: &str, _format: ConfigFormat) -> Result<String> {
-        use anyhow::Context;
-
-        let mut config: serde_json::Value = if config_content.trim().is_empty() {
-            serde_json::json!({})
-        } else {
-            serde_json::from_str(config_content).context("Failed to parse Zed config")?
-        };
-
-        // Fast path: already configured?
-        if let Some(servers) = config.get("context_servers")
-            && servers.get("kodegen").is_some()
-        {
-            return Ok(config_content.to_string());
-        }
-
-        // Inject Zed format: uses context_servers with source, command, args, env
+    fn merge_target(&self) -> MergeTarget {
+        // Zed format: context_servers with source, command, args, env
         // According to official Zed docs at https://zed.dev/docs/ai/mcp
-        if let Some(obj) = config.as_object_mut() {
-            if !obj.contains_key("context_servers") {
-                obj.insert("context_servers".to_string(), serde_json::json!({}));
-            }
-
-            if let Some(servers) = obj
-                .get_mut("context_servers")
-                .and_then(|v| v.as_object_mut())
-            {
-                servers.insert(
-                    "kodegen".to_string(),
-                    serde_json::json!({
-                        "source": "custom",
-                        "command": "kodegen",
-                        "args": ["--stdio"],
-                        "env": {}
-                    }),
-                );
-            }
-        }
-
-        serde_json::to_string_pretty(&config).context("Failed to serialize Zed config")
+        MergeTarget::new(["context_servers"]).with_entry_field("source", "custom")
     }
 
     fn config_format(&self) -> ConfigFormat {
         ConfigFormat::Json
     }
+
+    fn schema(&self) -> Option<JsonValue> {
+        Some(validate::at(
+            &["context_servers"],
+            validate::server_map(server_schema()),
+        ))
+    }
+}
+
+/// Schema of a context server, whose command may also be a `{ path, args, env }` object
+fn server_schema() -> JsonValue {
+    let mut server = validate::stdio_server();
+    server["properties"]["command"] = json!({
+        "anyOf": [
+            { "type": "string", "minLength": 1 },
+            validate::required(&["path"], json!({ "path": { "type": "string", "minLength": 1 } })),
+        ],
+    });
+    server["properties"]["source"] = json!({ "type": "string" });
+    json!({ "anyOf": [server, validate::remote_server()] })
 }
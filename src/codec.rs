@@ -0,0 +1,584 @@
+//! Config formats as pluggable codecs
+//!
+//! [`ConfigMerger::merge`] and [`ConfigMerger::remove`] look a config's format
+//! up in a process-wide table of [`FormatCodec`]s and leave everything
+//! format-specific to the codec: reading the config, finding, adding and
+//! removing server entries, and writing it out. The built-in formats are in
+//! the table from the start. A crate with clients in another format (JSON5,
+//! HCL, ...) [`register`]s a codec for it, and has those clients return
+//! [`ConfigFormat::Custom`] with the name it registered.
+//!
+//! [`ConfigMerger::merge`]: crate::ConfigMerger::merge
+//! [`ConfigMerger::remove`]: crate::ConfigMerger::remove
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+
+use anyhow::{Context, Result, bail};
+use parking_lot::RwLock;
+use serde_json::{Map, Value as JsonValue};
+use serde_yaml::Value as YamlValue;
+use toml::Value as TomlValue;
+
+use crate::{ConfigFormat, config, ini, jsonc, xml, yaml};
+
+/// Deepest `<array>`/`<dict>` nesting accepted in a plist
+///
+/// The plist parser recurses per level, so unbounded nesting could overflow
+/// the stack. Matches the recursion limits of the JSON, TOML and YAML parsers.
+#[cfg(target_os = "macos")]
+const MAX_PLIST_DEPTH: usize = 128;
+
+/// Reads and edits the configs of one format
+///
+/// Configs are seen as JSON: [`parse`](Self::parse) reads one, and servers are
+/// the entries of the object under a top-level key (`mcpServers`). Only
+/// `parse` and `serialize` are required. The other methods default to
+/// changing the parsed config and serializing it whole, which loses comments
+/// and formatting; codecs that can edit the text in place override them.
+pub trait FormatCodec: Send + Sync {
+    /// Read a config as JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config isn't valid in the format.
+    fn parse(&self, content: &str) -> Result<JsonValue>;
+
+    /// Write a whole config
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config can't be written in the format.
+    fn serialize(&self, config: &JsonValue) -> Result<String>;
+
+    /// Get server `name` under `key` of a config read with [`parse`](Self::parse)
+    fn find_server(&self, config: &JsonValue, key: &str, name: &str) -> Option<JsonValue> {
+        config.get(key)?.get(name).cloned()
+    }
+
+    /// Add server `name` under `key`, or replace it
+    ///
+    /// `content` is blank for a config that doesn't exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config can't be parsed or has an unexpected shape.
+    fn insert_server(
+        &self,
+        content: &str,
+        key: &str,
+        name: &str,
+        entry: &JsonValue,
+    ) -> Result<String> {
+        let mut config = if content.trim().is_empty() {
+            JsonValue::Object(Map::new())
+        } else {
+            self.parse(content)?
+        };
+
+        let Some(root) = config.as_object_mut() else {
+            bail!("Existing config is not an object");
+        };
+        let servers = root
+            .entry(key)
+            .or_insert_with(|| JsonValue::Object(Map::new()));
+        let Some(servers) = servers.as_object_mut() else {
+            bail!("`{key}` in existing config is not an object");
+        };
+        servers.insert(name.to_string(), entry.clone());
+
+        self.serialize(&config)
+    }
+
+    /// Remove server `name` under `key`, and `key` too if that leaves it empty
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config can't be parsed or serialized.
+    fn remove_server(&self, content: &str, key: &str, name: &str) -> Result<String> {
+        let mut config = self.parse(content)?;
+        let Some(root) = config.as_object_mut() else {
+            return Ok(content.to_string());
+        };
+        let Some(servers) = root.get_mut(key).and_then(JsonValue::as_object_mut) else {
+            return Ok(content.to_string());
+        };
+        if servers.remove(name).is_none() {
+            return Ok(content.to_string());
+        }
+        if servers.is_empty() {
+            root.remove(key);
+        }
+
+        self.serialize(&config)
+    }
+}
+
+/// The codec table, by name
+///
+/// Names are kept for the life of the process, so formats parsed from text
+/// (see [`custom_format`]) can refer to them.
+static CODECS: LazyLock<RwLock<HashMap<&'static str, Arc<dyn FormatCodec>>>> =
+    LazyLock::new(|| RwLock::new(builtin()));
+
+/// Get the codec of a format, if there is one
+#[must_use]
+pub fn get(format: ConfigFormat) -> Option<Arc<dyn FormatCodec>> {
+    CODECS.read().get(name_of(format)).cloned()
+}
+
+/// Register a codec under `name`, for clients returning `ConfigFormat::Custom(name)`
+///
+/// A codec already registered under the name is replaced, which also works
+/// for the built-in ones (`json`, `toml`, `yaml`, `plist`, `ini`, `xml`).
+pub fn register(name: impl Into<String>, codec: impl FormatCodec + 'static) {
+    let name = name.into();
+    let mut codecs = CODECS.write();
+    let name = match codecs.get_key_value(name.as_str()) {
+        Some((&registered, _)) => registered,
+        None => Box::leak(name.into_boxed_str()),
+    };
+    codecs.insert(name, Arc::new(codec));
+}
+
+/// Get the custom format of the codec registered under `name`, if there is one
+pub(crate) fn custom_format(name: &str) -> Option<ConfigFormat> {
+    CODECS
+        .read()
+        .get_key_value(name)
+        .map(|(&name, _)| ConfigFormat::Custom(name))
+}
+
+/// Name of a format's codec in the table
+fn name_of(format: ConfigFormat) -> &'static str {
+    match format {
+        ConfigFormat::Json | ConfigFormat::Sqlite => "json",
+        ConfigFormat::Toml => "toml",
+        ConfigFormat::Yaml => "yaml",
+        ConfigFormat::Plist => "plist",
+        ConfigFormat::Ini => "ini",
+        ConfigFormat::Xml => "xml",
+        ConfigFormat::Custom(name) => name,
+    }
+}
+
+/// The codecs of the built-in formats
+fn builtin() -> HashMap<&'static str, Arc<dyn FormatCodec>> {
+    let codecs: [(&'static str, Arc<dyn FormatCodec>); 6] = [
+        ("json", Arc::new(JsonCodec)),
+        ("toml", Arc::new(TomlCodec)),
+        ("yaml", Arc::new(YamlCodec)),
+        ("plist", Arc::new(PlistCodec)),
+        ("ini", Arc::new(IniCodec)),
+        ("xml", Arc::new(XmlCodec)),
+    ];
+    HashMap::from(codecs)
+}
+
+/// JSON, with comments and trailing commas (JSONC); entries are edited as text
+struct JsonCodec;
+
+impl FormatCodec for JsonCodec {
+    fn parse(&self, content: &str) -> Result<JsonValue> {
+        Ok(serde_json::from_str(
+            jsonc::Document::new(content).stripped(),
+        )?)
+    }
+
+    fn serialize(&self, config: &JsonValue) -> Result<String> {
+        jsonc::Document::new("").rewrite(config)
+    }
+
+    fn insert_server(
+        &self,
+        content: &str,
+        key: &str,
+        name: &str,
+        entry: &JsonValue,
+    ) -> Result<String> {
+        config::insert_json_server(content, &[key], name, entry)
+    }
+
+    fn remove_server(&self, content: &str, key: &str, name: &str) -> Result<String> {
+        config::remove_json_server(content, &[key], name)
+    }
+}
+
+/// TOML; entries are edited into the document with `toml_edit`
+struct TomlCodec;
+
+impl FormatCodec for TomlCodec {
+    fn parse(&self, content: &str) -> Result<JsonValue> {
+        let config: TomlValue = toml::from_str(content)?;
+        Ok(config::to_json(&config))
+    }
+
+    fn serialize(&self, config: &JsonValue) -> Result<String> {
+        toml::to_string(config).context("Failed to serialize TOML")
+    }
+
+    fn insert_server(
+        &self,
+        content: &str,
+        key: &str,
+        name: &str,
+        entry: &JsonValue,
+    ) -> Result<String> {
+        config::insert_toml_server(content, key, name, &config::toml_entry(entry))
+    }
+
+    fn remove_server(&self, content: &str, key: &str, name: &str) -> Result<String> {
+        config::remove_toml_entries(content, key, &[name])
+    }
+}
+
+/// YAML; entries of block-style mappings are edited as lines
+struct YamlCodec;
+
+impl FormatCodec for YamlCodec {
+    fn parse(&self, content: &str) -> Result<JsonValue> {
+        Ok(config::to_json(&config::parse_yaml_mapping(content)?))
+    }
+
+    fn serialize(&self, config: &JsonValue) -> Result<String> {
+        serde_yaml::to_string(config).context("Failed to serialize YAML")
+    }
+
+    fn insert_server(
+        &self,
+        content: &str,
+        key: &str,
+        name: &str,
+        entry: &JsonValue,
+    ) -> Result<String> {
+        let mut config = config::parse_yaml_mapping(content)?;
+        if config.get(key).is_some_and(|servers| !servers.is_mapping()) {
+            bail!("`{key}` in existing config is not a mapping");
+        }
+
+        let entry = serde_yaml::to_value(entry)?;
+        let edited = yaml::upsert_entry(content, key, name, &entry);
+        if let Some(map) = config.as_mapping_mut()
+            && let Some(servers) = map
+                .entry(key.into())
+                .or_insert_with(|| YamlValue::Mapping(serde_yaml::Mapping::new()))
+                .as_mapping_mut()
+        {
+            servers.insert(name.into(), entry);
+        }
+
+        config::checked_yaml_edit(edited, &config)
+    }
+
+    fn remove_server(&self, content: &str, key: &str, name: &str) -> Result<String> {
+        let mut config: YamlValue =
+            serde_yaml::from_str(content).context("Failed to parse existing YAML")?;
+        let Some(map) = config.as_mapping_mut() else {
+            return Ok(content.to_string());
+        };
+        let Some(servers) = map.get_mut(key).and_then(YamlValue::as_mapping_mut) else {
+            return Ok(content.to_string());
+        };
+        if servers.remove(name).is_none() {
+            return Ok(content.to_string());
+        }
+        if servers.is_empty() {
+            map.remove(key);
+        }
+
+        config::checked_yaml_edit(
+            yaml::remove_entries(content, key, &[name.to_string()]),
+            &config,
+        )
+    }
+}
+
+/// XML property lists (macOS only); the whole plist is rewritten
+///
+/// Entries are changed in the parsed plist rather than through JSON, so
+/// values JSON can't hold (dates, data) survive.
+struct PlistCodec;
+
+#[cfg(target_os = "macos")]
+impl FormatCodec for PlistCodec {
+    fn parse(&self, content: &str) -> Result<JsonValue> {
+        Ok(config::to_json(&parse_plist(content)?))
+    }
+
+    fn serialize(&self, config: &JsonValue) -> Result<String> {
+        write_plist(&json_to_plist(config))
+    }
+
+    fn insert_server(
+        &self,
+        content: &str,
+        key: &str,
+        name: &str,
+        entry: &JsonValue,
+    ) -> Result<String> {
+        use plist::Value;
+
+        let mut config = if content.trim().is_empty() {
+            Value::Dictionary(plist::Dictionary::new())
+        } else {
+            parse_plist(content)?
+        };
+
+        let Some(dict) = config.as_dictionary_mut() else {
+            bail!("Existing plist is not a dictionary");
+        };
+        if !dict.contains_key(key) {
+            dict.insert(key.to_string(), Value::Dictionary(plist::Dictionary::new()));
+        }
+        let Some(servers) = dict.get_mut(key).and_then(Value::as_dictionary_mut) else {
+            bail!("`{key}` in existing plist is not a dictionary");
+        };
+        servers.insert(name.to_string(), json_to_plist(entry));
+
+        write_plist(&config)
+    }
+
+    fn remove_server(&self, content: &str, key: &str, name: &str) -> Result<String> {
+        let mut config = parse_plist(content)?;
+        let Some(dict) = config.as_dictionary_mut() else {
+            return Ok(content.to_string());
+        };
+        let Some(servers) = dict.get_mut(key).and_then(plist::Value::as_dictionary_mut) else {
+            return Ok(content.to_string());
+        };
+        if servers.remove(name).is_none() {
+            return Ok(content.to_string());
+        }
+        if servers.is_empty() {
+            dict.remove(key);
+        }
+
+        write_plist(&config)
+    }
+}
+
+/// Plists can't be read or written off macOS
+#[cfg(not(target_os = "macos"))]
+impl FormatCodec for PlistCodec {
+    fn parse(&self, _content: &str) -> Result<JsonValue> {
+        Err(crate::error::ConfigError::UnsupportedFormat(ConfigFormat::Plist).into())
+    }
+
+    fn serialize(&self, _config: &JsonValue) -> Result<String> {
+        Err(crate::error::ConfigError::UnsupportedFormat(ConfigFormat::Plist).into())
+    }
+
+    fn insert_server(
+        &self,
+        _content: &str,
+        _key: &str,
+        _name: &str,
+        _entry: &JsonValue,
+    ) -> Result<String> {
+        Err(crate::error::ConfigError::UnsupportedFormat(ConfigFormat::Plist).into())
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn parse_plist(content: &str) -> Result<plist::Value> {
+    ensure_plist_depth(content)?;
+    plist::from_reader(std::io::Cursor::new(content.as_bytes()))
+        .context("Failed to parse existing plist")
+}
+
+#[cfg(target_os = "macos")]
+fn write_plist(config: &plist::Value) -> Result<String> {
+    let mut output = Vec::new();
+    plist::to_writer_xml(&mut output, config).context("Failed to serialize plist")?;
+    String::from_utf8(output).context("Failed to convert plist to UTF-8")
+}
+
+/// Reject plists nested deeper than [`MAX_PLIST_DEPTH`] before parsing them
+#[cfg(target_os = "macos")]
+fn ensure_plist_depth(existing: &str) -> Result<()> {
+    let mut depth = 0usize;
+    for (index, _) in existing.match_indices('<') {
+        let tag = &existing[index + 1..];
+        if tag.starts_with("array>") || tag.starts_with("dict>") {
+            depth += 1;
+            if depth > MAX_PLIST_DEPTH {
+                bail!("Existing plist is nested too deeply");
+            }
+        } else if tag.starts_with("/array>") || tag.starts_with("/dict>") {
+            depth = depth.saturating_sub(1);
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert a JSON value to its plist equivalent (plist has no null, so it becomes an empty string)
+#[cfg(target_os = "macos")]
+fn json_to_plist(value: &JsonValue) -> plist::Value {
+    use plist::Value;
+
+    match value {
+        JsonValue::Null => Value::String(String::new()),
+        JsonValue::Bool(b) => Value::Boolean(*b),
+        JsonValue::Number(n) => n.as_i64().map_or_else(
+            || Value::Real(n.as_f64().unwrap_or_default()),
+            |i| Value::Integer(i.into()),
+        ),
+        JsonValue::String(s) => Value::String(s.clone()),
+        JsonValue::Array(items) => Value::Array(items.iter().map(json_to_plist).collect()),
+        JsonValue::Object(map) => Value::Dictionary(
+            map.iter()
+                .map(|(key, value)| (key.clone(), json_to_plist(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// INI; each server's sections are edited as lines
+struct IniCodec;
+
+impl FormatCodec for IniCodec {
+    fn parse(&self, content: &str) -> Result<JsonValue> {
+        Ok(ini::parse(content)?)
+    }
+
+    fn serialize(&self, config: &JsonValue) -> Result<String> {
+        ini::render_document(config).context("INI config is not an object")
+    }
+
+    fn insert_server(
+        &self,
+        content: &str,
+        key: &str,
+        name: &str,
+        entry: &JsonValue,
+    ) -> Result<String> {
+        let mut config = ini::parse(content)?;
+        if config.get(key).is_some_and(|servers| !servers.is_object()) {
+            bail!("`{key}` in existing config is not a section");
+        }
+
+        let edited = ini::upsert_section(content, key, name, entry);
+        config[key][name] = ini::written_form(entry);
+
+        checked_text_edit(edited, &config, "INI", |text| ini::parse(text).ok())
+    }
+
+    fn remove_server(&self, content: &str, key: &str, name: &str) -> Result<String> {
+        let mut config = ini::parse(content)?;
+        if !remove_entry(&mut config, key, name) {
+            return Ok(content.to_string());
+        }
+
+        checked_text_edit(
+            ini::remove_sections(content, key, &[name.to_string()]),
+            &config,
+            "INI",
+            |text| ini::parse(text).ok(),
+        )
+    }
+}
+
+/// XML; server elements are edited as text
+struct XmlCodec;
+
+impl FormatCodec for XmlCodec {
+    fn parse(&self, content: &str) -> Result<JsonValue> {
+        Ok(xml::parse(content)?)
+    }
+
+    fn serialize(&self, config: &JsonValue) -> Result<String> {
+        Ok(xml::render_document(config))
+    }
+
+    fn insert_server(
+        &self,
+        content: &str,
+        key: &str,
+        name: &str,
+        entry: &JsonValue,
+    ) -> Result<String> {
+        let mut config = if content.trim().is_empty() {
+            JsonValue::Object(Map::new())
+        } else {
+            parse_xml_object(content, key)?
+        };
+        if !config.is_object() {
+            bail!("Root element of existing config holds text");
+        }
+        if config.get(key).is_some_and(|servers| !servers.is_object()) {
+            bail!("`<{key}>` in existing config doesn't hold servers");
+        }
+
+        let edited = xml::upsert_server(content, key, name, entry);
+        config[key][name] = xml::written_form(entry);
+
+        checked_text_edit(edited, &config, "XML", |text| {
+            parse_xml_object(text, key).ok()
+        })
+    }
+
+    fn remove_server(&self, content: &str, key: &str, name: &str) -> Result<String> {
+        let mut config = parse_xml_object(content, key)?;
+        if !remove_entry(&mut config, key, name) {
+            return Ok(content.to_string());
+        }
+
+        checked_text_edit(
+            xml::remove_servers(content, key, &[name.to_string()]),
+            &config,
+            "XML",
+            |text| parse_xml_object(text, key).ok(),
+        )
+    }
+}
+
+/// Remove server `name` under `key` of a parsed config, and `key` too if that
+/// empties it; `false` if there was no such server
+fn remove_entry(config: &mut JsonValue, key: &str, name: &str) -> bool {
+    let Some(root) = config.as_object_mut() else {
+        return false;
+    };
+    let Some(servers) = root.get_mut(key).and_then(JsonValue::as_object_mut) else {
+        return false;
+    };
+    if servers.remove(name).is_none() {
+        return false;
+    }
+    if servers.is_empty() {
+        root.remove(key);
+    }
+    true
+}
+
+/// Accept an in-place edit of a config that can't be rewritten (INI, XML)
+/// only if it reads back as the structural merge
+fn checked_text_edit(
+    edited: Option<String>,
+    expected: &JsonValue,
+    format: &str,
+    read: impl Fn(&str) -> Option<JsonValue>,
+) -> Result<String> {
+    match edited {
+        Some(edited) if read(&edited).as_ref() == Some(expected) => Ok(edited),
+        Some(_) => bail!("In-place {format} edit didn't match the merged config"),
+        None => bail!("Couldn't edit {format} config in place"),
+    }
+}
+
+/// Read an XML config as an object, with a root element or `key` element
+/// that's left empty read as an empty object
+fn parse_xml_object(existing: &str, key: &str) -> Result<JsonValue> {
+    let empty = |value: &mut JsonValue| {
+        if value.as_str() == Some("") {
+            *value = JsonValue::Object(Map::new());
+        }
+    };
+
+    let mut config = xml::parse(existing)?;
+    empty(&mut config);
+    if let Some(servers) = config.get_mut(key) {
+        empty(servers);
+    }
+    Ok(config)
+}
@@ -1,28 +1,204 @@
-use anyhow::{Result, anyhow};
-#[cfg(target_os = "macos")]
-use anyhow::Context;
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow, bail};
 use serde_json::Value as JsonValue;
 use serde_yaml::Value as YamlValue;
 use toml::Value as TomlValue;
+use toml_edit::DocumentMut;
+
+use serde::{Deserialize, Serialize};
+
+use crate::codec::{self, FormatCodec};
+use crate::error::ConfigError;
+use crate::template::TemplateVars;
+use crate::version::Version;
+use crate::{ClientConfigPlugin, ConfigFormat, KodegenConfig, KodegenHttpConfig, jsonc, yaml};
+
+/// Default name of the server entry injected into client configs
+const SERVER_NAME: &str = "kodegen";
 
-#[cfg(target_os = "macos")]
-use plist::Value as PlistValue;
+/// Largest existing config the mergers will parse, in bytes
+///
+/// Real client configs are a few kilobytes; this only keeps corrupt or
+/// hostile files from using unbounded memory.
+pub const MAX_CONFIG_LEN: usize = 16 * 1024 * 1024;
 
-use crate::ConfigFormat;
+/// How to handle an existing server entry named `kodegen` that isn't ours
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictResolution {
+    /// Leave the config untouched and report a conflict
+    #[default]
+    Skip,
+    /// Inject under a suffixed name (`kodegen-2`, `kodegen-3`, ...)
+    Suffix,
+    /// Overwrite the existing entry
+    Replace,
+}
+
+/// How clients reach the kodegen server
+///
+/// Clients that can't use the preferred transport (see
+/// [`ClientConfigPlugin::supported_transports`]) are configured with stdio.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransportPreference {
+    /// Launch the server's command and talk to it over stdin/stdout
+    #[default]
+    Stdio,
+    /// Connect to the server's URL with MCP's streamable HTTP transport
+    StreamableHttp,
+    /// Connect to the server's URL with the older HTTP+SSE transport
+    Sse,
+}
+
+impl TransportPreference {
+    /// Get the transport's name, as used in `type` fields of server entries
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Stdio => "stdio",
+            Self::StreamableHttp => "streamable-http",
+            Self::Sse => "sse",
+        }
+    }
+
+    /// Check whether this transport connects to a URL rather than launching a command
+    #[must_use]
+    pub fn is_http(self) -> bool {
+        self != Self::Stdio
+    }
+}
+
+/// Error returned when an unrelated server already uses kodegen's name
+#[derive(Debug, thiserror::Error)]
+#[error("an unrelated MCP server named `{name}` already exists")]
+pub struct ServerNameConflict {
+    pub name: String,
+}
+
+/// How a client lays out its server entries
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EntryShape {
+    /// An object of entries keyed by server name (`{"kodegen": {...}}`)
+    #[default]
+    Keyed,
+    /// A list of entries, each naming itself in a `name` field (`[{"name": "kodegen", ...}]`)
+    Named,
+}
+
+/// Where in its config a client keeps its servers, and how
+///
+/// The default is the standard `mcpServers` object. Targets other than a
+/// [`EntryShape::Keyed`] object under a single top-level key need a JSON
+/// config, except for [`EntryShape::Named`] lists under a single YAML key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeTarget {
+    /// Keys leading from the top of the config to the servers (e.g. `["augment.advanced", "mcpServers"]`)
+    pub key_path: Vec<String>,
+    pub entry_shape: EntryShape,
+    /// Fields every entry gets on top of the server's (e.g. Zed's `"source": "custom"`)
+    pub entry_fields: serde_json::Map<String, JsonValue>,
+}
+
+impl MergeTarget {
+    /// Target the entries keyed by name at `key_path`
+    pub fn new(key_path: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            key_path: key_path.into_iter().map(Into::into).collect(),
+            entry_shape: EntryShape::Keyed,
+            entry_fields: serde_json::Map::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_entry_shape(mut self, entry_shape: EntryShape) -> Self {
+        self.entry_shape = entry_shape;
+        self
+    }
+
+    /// Add a field to every entry injected
+    #[must_use]
+    pub fn with_entry_field(mut self, name: &str, value: impl Into<JsonValue>) -> Self {
+        self.entry_fields.insert(name.to_string(), value.into());
+        self
+    }
+
+    /// Get the key path as the JSON helpers take it
+    fn path(&self) -> Vec<&str> {
+        self.key_path.iter().map(String::as_str).collect()
+    }
+}
+
+impl Default for MergeTarget {
+    fn default() -> Self {
+        Self::new(["mcpServers"])
+    }
+}
 
 /// Zero-allocation config merger for different formats
+#[derive(Clone)]
 pub struct ConfigMerger {
+    /// Server definition the stdio templates are built from
+    server_config: KodegenConfig,
+    /// Endpoint the HTTP templates are built from
+    http_config: KodegenHttpConfig,
+    /// Transport of the injected entry
+    transport: TransportPreference,
     /// Pre-allocated KODEGEN.ᴀɪ config template
-    kodegen_config: KodegenConfig,
+    kodegen_config: KodegenTemplates,
+    /// Key the server entry is injected under
+    server_name: String,
+    /// Further kodegen-provided servers injected next to the main one, by entry name
+    additional_servers: Vec<(String, KodegenConfig)>,
+    conflict_resolution: ConflictResolution,
+    update_existing: bool,
+    /// Release of the client reading the config, if it was detected
+    client_version: Option<Version>,
+    /// Format of the config file, where the client lists it with its own
+    config_format: Option<ConfigFormat>,
 }
 
+/// The kodegen server entry, as every codec takes it
 #[derive(Clone)]
-struct KodegenConfig {
+struct KodegenTemplates {
     json: JsonValue,
-    toml: TomlValue,
-    yaml: YamlValue,
-    #[cfg(target_os = "macos")]
-    plist: PlistValue,
+}
+
+impl KodegenTemplates {
+    fn build(
+        config: &KodegenConfig,
+        http_config: &KodegenHttpConfig,
+        transport: TransportPreference,
+    ) -> Self {
+        if transport.is_http() {
+            return Self::build_http(http_config, transport);
+        }
+
+        let env = config.env.clone().unwrap_or_else(|| serde_json::json!({}));
+
+        let json = serde_json::json!({
+            "command": config.command,
+            "args": config.args,
+            "env": env,
+        });
+
+        Self { json }
+    }
+
+    /// Build the `type`/`url`/`headers` entry of an HTTP transport
+    fn build_http(config: &KodegenHttpConfig, transport: TransportPreference) -> Self {
+        let mut json = serde_json::json!({
+            "type": transport.as_str(),
+            "url": config.url,
+        });
+        let headers = config.headers_with(|var| format!("${{{var}}}"));
+        if !headers.is_empty() {
+            json["headers"] = serde_json::json!(headers);
+        }
+
+        Self { json }
+    }
 }
 
 impl ConfigMerger {
@@ -30,264 +206,1282 @@ impl ConfigMerger {
     #[inline]
     #[must_use]
     pub fn new() -> Self {
-        let kodegen_config = KodegenConfig {
-            json: serde_json::json!({
-                "mcpServers": {
-                    "kodegen": {
-                        "command": "kodegen",
-                        "args": ["--stdio"],
-                        "env": {}
+        let server_config = KodegenConfig::default();
+        let http_config = KodegenHttpConfig::default();
+        let transport = TransportPreference::default();
+
+        Self {
+            kodegen_config: KodegenTemplates::build(&server_config, &http_config, transport),
+            server_config,
+            http_config,
+            transport,
+            server_name: SERVER_NAME.to_string(),
+            additional_servers: Vec::new(),
+            conflict_resolution: ConflictResolution::default(),
+            update_existing: false,
+            client_version: None,
+            config_format: None,
+        }
+    }
+
+    /// Inject a custom server definition instead of the default `kodegen --stdio`
+    #[must_use]
+    pub fn with_config(mut self, server_config: KodegenConfig) -> Self {
+        self.server_config = server_config;
+        self.rebuild_templates();
+        self
+    }
+
+    /// Replace the placeholders (`${HOME}`, ...) in the server definitions
+    ///
+    /// Applies to the definitions set so far, including the additional servers.
+    #[must_use]
+    pub fn with_template_vars(mut self, vars: &TemplateVars) -> Self {
+        self.server_config = vars.expand_config(&self.server_config);
+        for (_, config) in &mut self.additional_servers {
+            *config = vars.expand_config(config);
+        }
+        self.rebuild_templates();
+        self
+    }
+
+    /// Connect to a custom endpoint when injecting an HTTP transport
+    #[must_use]
+    pub fn with_http_config(mut self, http_config: KodegenHttpConfig) -> Self {
+        self.http_config = http_config;
+        self.rebuild_templates();
+        self
+    }
+
+    /// Inject an entry for `transport` instead of launching the stdio command
+    ///
+    /// The `type` of HTTP entries is taken from the transport rather than
+    /// [`KodegenHttpConfig::transport_type`]. See [`Self::for_client`] for
+    /// clients that only support some transports.
+    #[must_use]
+    pub fn with_transport(mut self, transport: TransportPreference) -> Self {
+        self.transport = transport;
+        self.rebuild_templates();
+        self
+    }
+
+    /// Fall back to stdio if the client doesn't support the selected transport
+    #[must_use]
+    pub fn for_client(self, client: &dyn ClientConfigPlugin) -> Self {
+        if client.supported_transports().contains(&self.transport) {
+            return self;
+        }
+
+        log::debug!(
+            "{} doesn't support the {} transport, using stdio",
+            client.client_name(),
+            self.transport.as_str()
+        );
+        self.with_transport(TransportPreference::Stdio)
+    }
+
+    /// Prepare the merger for one of a client's config files
+    ///
+    /// Records the client release reading it (see
+    /// [`ClientConfigPlugin::detect_version`]) for plugins to pick a schema by,
+    /// and the file's format where it differs between a client's configs. Files
+    /// the client doesn't list (e.g. a Windows host's, from WSL) are taken to
+    /// be in the format of their extension.
+    #[must_use]
+    pub fn for_config(mut self, client: &dyn ClientConfigPlugin, config_path: &Path) -> Self {
+        self.config_format = client
+            .config_paths()
+            .into_iter()
+            .find(|config| config.path == config_path)
+            .map(|config| config.format)
+            .or_else(|| ConfigFormat::from_path(config_path));
+        self.with_client_version(client.detect_version(config_path))
+    }
+
+    /// Set the release of the client the config is written for
+    #[must_use]
+    pub fn with_client_version(mut self, client_version: Option<Version>) -> Self {
+        self.client_version = client_version;
+        self
+    }
+
+    /// Get the release of the client the config is written for, if known
+    #[must_use]
+    pub fn client_version(&self) -> Option<Version> {
+        self.client_version
+    }
+
+    /// Get the format of the config file the merger was prepared for
+    ///
+    /// Falls back to the client's default format for files it doesn't list
+    /// and whose extension doesn't tell.
+    #[must_use]
+    pub fn config_format(&self, default: ConfigFormat) -> ConfigFormat {
+        self.config_format.unwrap_or(default)
+    }
+
+    /// Re-render the templates after the server definition or transport changed
+    fn rebuild_templates(&mut self) {
+        self.kodegen_config =
+            KodegenTemplates::build(&self.server_config, &self.http_config, self.transport);
+    }
+
+    /// Inject the server entry under `server_name` instead of `kodegen`
+    ///
+    /// Suffixed names from [`ConflictResolution::Suffix`] build on this name
+    /// (e.g. `kodegen-work-2`).
+    #[must_use]
+    pub fn with_server_name(mut self, server_name: &str) -> Self {
+        self.server_name = server_name.to_string();
+        self
+    }
+
+    /// Get the key the server entry is injected under
+    #[must_use]
+    pub fn server_name(&self) -> &str {
+        &self.server_name
+    }
+
+    /// Also inject each of `servers` under its name (e.g. `kodegen-github`)
+    ///
+    /// The additional servers always use stdio. Each is checked for on its own,
+    /// so adding one to a config that already has the others only adds that one.
+    #[must_use]
+    pub fn with_additional_servers(
+        mut self,
+        servers: impl IntoIterator<Item = (String, KodegenConfig)>,
+    ) -> Self {
+        self.additional_servers = servers.into_iter().collect();
+        self
+    }
+
+    /// Rewrite every server definition, the additional servers' included
+    #[must_use]
+    pub fn map_server_configs(mut self, f: impl Fn(&KodegenConfig) -> KodegenConfig) -> Self {
+        self.server_config = f(&self.server_config);
+        for (_, config) in &mut self.additional_servers {
+            *config = f(config);
+        }
+        self.rebuild_templates();
+        self
+    }
+
+    /// Get a merger for each server to inject, the main one first
+    ///
+    /// Each returned merger handles a single server, with this merger's
+    /// conflict resolution and update mode.
+    pub fn servers(&self) -> impl Iterator<Item = Self> + '_ {
+        let main = Self {
+            additional_servers: Vec::new(),
+            ..self.clone()
+        };
+
+        std::iter::once(main).chain(self.additional_servers.iter().map(|(name, config)| {
+            Self {
+                additional_servers: Vec::new(),
+                ..self.clone()
+            }
+            .with_server_name(name)
+            .with_transport(TransportPreference::Stdio)
+            .with_config(config.clone())
+        }))
+    }
+
+    /// Check whether this merger injects more than one server
+    fn has_additional_servers(&self) -> bool {
+        !self.additional_servers.is_empty()
+    }
+
+    /// Rewrite existing kodegen entries that differ from the current server definition
+    ///
+    /// Without this, any entry of ours counts as already configured.
+    #[must_use]
+    pub fn with_update_existing(mut self, update_existing: bool) -> Self {
+        self.update_existing = update_existing;
+        self
+    }
+
+    /// Get the server definition being injected
+    #[must_use]
+    pub fn server_config(&self) -> &KodegenConfig {
+        &self.server_config
+    }
+
+    /// Get the endpoint injected for HTTP transports
+    #[must_use]
+    pub fn http_config(&self) -> &KodegenHttpConfig {
+        &self.http_config
+    }
+
+    /// Get the transport of the injected entry
+    #[must_use]
+    pub fn transport(&self) -> TransportPreference {
+        self.transport
+    }
+
+    /// Set how an unrelated existing `kodegen` server entry is handled
+    #[must_use]
+    pub fn with_conflict_resolution(mut self, conflict_resolution: ConflictResolution) -> Self {
+        self.conflict_resolution = conflict_resolution;
+        self
+    }
+
+    /// Get how an unrelated existing `kodegen` server entry is handled
+    #[must_use]
+    pub fn conflict_resolution(&self) -> ConflictResolution {
+        self.conflict_resolution
+    }
+
+    /// Decide which server name to inject under, given a lookup of existing entries
+    ///
+    /// Returns `None` when an entry of ours is already present.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ServerNameConflict`] if an unrelated entry holds the name and
+    /// the resolution is [`ConflictResolution::Skip`].
+    pub fn resolve_server_name<F>(&self, lookup: F) -> Result<Option<String>>
+    where
+        F: Fn(&str) -> Option<JsonValue>,
+    {
+        self.resolve_server_name_against(lookup, &self.kodegen_config.json)
+    }
+
+    /// Like [`Self::resolve_server_name`], for clients whose entry differs from
+    /// the standard template: entries of ours are compared against `expected`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ServerNameConflict`] if an unrelated entry holds the name and
+    /// the resolution is [`ConflictResolution::Skip`].
+    pub(crate) fn resolve_server_name_against<F>(
+        &self,
+        lookup: F,
+        expected: &JsonValue,
+    ) -> Result<Option<String>>
+    where
+        F: Fn(&str) -> Option<JsonValue>,
+    {
+        let server_name = &self.server_name;
+        let entry = match lookup(server_name) {
+            None => return Ok(Some(server_name.clone())),
+            Some(entry) if self.is_ours(&entry) => {
+                return Ok(self
+                    .needs_update(&entry, expected)
+                    .then(|| server_name.clone()));
+            }
+            Some(entry) => entry,
+        };
+
+        match self.conflict_resolution {
+            ConflictResolution::Skip => Err(ServerNameConflict {
+                name: server_name.clone(),
+            }
+            .into()),
+            ConflictResolution::Replace => {
+                log::warn!("Replacing unrelated `{server_name}` server entry: {entry}");
+                Ok(Some(server_name.clone()))
+            }
+            ConflictResolution::Suffix => {
+                for n in 2.. {
+                    let candidate = format!("{server_name}-{n}");
+                    match lookup(&candidate) {
+                        None => return Ok(Some(candidate)),
+                        Some(entry) if self.is_ours(&entry) => {
+                            return Ok(self.needs_update(&entry, expected).then_some(candidate));
+                        }
+                        Some(_) => {}
                     }
                 }
-            }),
-            toml: TomlValue::Table({
-                let mut map = toml::map::Map::new();
-                let mut mcp_servers = toml::map::Map::new();
-                let mut kodegen = toml::map::Map::new();
-                kodegen.insert(
-                    "command".to_string(),
-                    TomlValue::String("kodegen".to_string()),
-                );
-                kodegen.insert(
-                    "args".to_string(),
-                    TomlValue::Array(vec![
-                        TomlValue::String("--stdio".to_string()),
-                    ]),
-                );
-                mcp_servers.insert("kodegen".to_string(), TomlValue::Table(kodegen));
-                map.insert("mcpServers".to_string(), TomlValue::Table(mcp_servers));
-                map
-            }),
-            yaml: {
-                let yaml_str = r"
-mcpServers:
-  kodegen:
-    command: kodegen
-    args:
-      - --stdio
-    env: {}
-";
-                serde_yaml::from_str(yaml_str)
-                    .ok()
-                    .unwrap_or(YamlValue::Null)
-            },
-            #[cfg(target_os = "macos")]
-            plist: {
-                use plist::Value;
-
-                let mut kodegen = plist::Dictionary::new();
-                kodegen.insert("command".to_string(), Value::String("kodegen".to_string()));
-                kodegen.insert(
-                    "args".to_string(),
-                    Value::Array(vec![
-                        Value::String("--stdio".to_string()),
-                    ]),
-                );
-                kodegen.insert(
-                    "env".to_string(),
-                    Value::Dictionary(plist::Dictionary::new()),
-                );
-
-                let mut servers = plist::Dictionary::new();
-                servers.insert("kodegen".to_string(), Value::Dictionary(kodegen));
-
-                let mut root = plist::Dictionary::new();
-                root.insert("mcpServers".to_string(), Value::Dictionary(servers));
-
-                Value::Dictionary(root)
-            },
+                unreachable!("suffix search is unbounded")
+            }
+        }
+    }
+
+    /// Check whether an existing entry of ours should be rewritten to `expected`
+    fn needs_update(&self, entry: &JsonValue, expected: &JsonValue) -> bool {
+        if !self.update_existing {
+            return false;
+        }
+
+        let empty = serde_json::json!({});
+        let map = |value: &JsonValue, key: &str| -> JsonValue {
+            match value.get(key) {
+                None | Some(JsonValue::Null) => empty.clone(),
+                Some(map) => map.clone(),
+            }
         };
 
-        Self { kodegen_config }
+        // Clients spell the transport `type` differently, so the URL stands for it
+        entry.get("command") != expected.get("command")
+            || entry.get("args") != expected.get("args")
+            || map(entry, "env") != map(expected, "env")
+            || entry.get("url") != expected.get("url")
+            || map(entry, "headers") != map(expected, "headers")
     }
 
     /// Merge KODEGEN.ᴀɪ config into existing config with zero allocation where possible
     ///
+    /// Every server from [`Self::with_additional_servers`] is merged as well.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the config parsing or serialization fails for the given format.
+    /// Returns [`ConfigError::ParseFailed`] if the config cannot be parsed for
+    /// the given format, [`ConfigError::Conflict`] if an unrelated server
+    /// already uses kodegen's name, or another [`ConfigError`] if serialization fails.
     #[inline]
-    pub fn merge(&self, existing: &str, format: ConfigFormat) -> Result<String> {
-        match format {
-            ConfigFormat::Json => self.merge_json(existing),
-            ConfigFormat::Toml => self.merge_toml(existing),
-            ConfigFormat::Yaml => self.merge_yaml(existing),
-            ConfigFormat::Plist => self.merge_plist(existing),
+    pub fn merge(&self, existing: &str, format: ConfigFormat) -> Result<String, ConfigError> {
+        self.merge_into(existing, format, &MergeTarget::default())
+    }
+
+    /// Like [`Self::merge`], for a client keeping its servers elsewhere or in
+    /// another shape (see [`ClientConfigPlugin::merge_target`])
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Self::merge`], or [`ConfigError::Other`] if
+    /// configs of the format can't hold servers as `target` asks.
+    pub fn merge_into(
+        &self,
+        existing: &str,
+        format: ConfigFormat,
+        target: &MergeTarget,
+    ) -> Result<String, ConfigError> {
+        if self.has_additional_servers() {
+            return self
+                .servers()
+                .try_fold(existing.to_string(), |merged, server| {
+                    server.merge_into(&merged, format, target)
+                });
         }
+
+        self.merge_format(existing, format, target)
+            .map_err(|e| ConfigError::classify(e, format))
     }
 
-    /// Merge JSON config with optimal performance
-    #[inline]
-    fn merge_json(&self, existing: &str) -> Result<String> {
-        let mut config: JsonValue = if existing.trim().is_empty() {
-            serde_json::json!({})
+    /// Merge into a config of the given format (see [`Self::merge_into`])
+    fn merge_format(
+        &self,
+        existing: &str,
+        format: ConfigFormat,
+        target: &MergeTarget,
+    ) -> Result<String> {
+        ensure_config_len(existing)?;
+        let mut entry = self.kodegen_config.json.clone();
+        if let Some(fields) = entry.as_object_mut() {
+            fields.extend(target.entry_fields.clone());
+        }
+
+        let path = target.path();
+        match (target.entry_shape, path.as_slice(), format) {
+            (EntryShape::Keyed, [key], _) => {
+                let codec = codec::get(format).ok_or(ConfigError::UnsupportedFormat(format))?;
+                self.merge_with(codec.as_ref(), existing, key, &entry)
+            }
+            (EntryShape::Keyed, _, ConfigFormat::Json | ConfigFormat::Sqlite) => {
+                self.merge_json_servers_at(existing, &path, &entry, JsonValue::clone)
+            }
+            (EntryShape::Named, _, ConfigFormat::Json | ConfigFormat::Sqlite) => {
+                self.merge_json_list(existing, &path, &entry)
+            }
+            (EntryShape::Named, [key], ConfigFormat::Yaml) => {
+                self.merge_yaml_list(existing, key, &entry)
+            }
+            _ => Err(unsupported_target(format, target)),
+        }
+    }
+
+    /// Add `entry` to the `key` servers of a config through its format's codec
+    fn merge_with(
+        &self,
+        codec: &dyn FormatCodec,
+        existing: &str,
+        key: &str,
+        entry: &JsonValue,
+    ) -> Result<String> {
+        let config = if existing.trim().is_empty() {
+            JsonValue::Object(serde_json::Map::new())
         } else {
-            serde_json::from_str(existing)?
+            codec.parse(existing)?
         };
 
+        // Entries of ours are compared as they read back once written, since
+        // not every format holds every JSON value (e.g. TOML has no null)
+        let expected = codec
+            .insert_server("", key, &self.server_name, entry)
+            .and_then(|written| codec.parse(&written))
+            .ok()
+            .and_then(|written| codec.find_server(&written, key, &self.server_name))
+            .unwrap_or_else(|| entry.clone());
+
         // Fast path: check if already configured
-        if let Some(servers) = config.get("mcpServers")
-            && servers.get("kodegen").is_some()
-        {
+        let Some(name) = self
+            .resolve_server_name_against(|name| codec.find_server(&config, key, name), &expected)?
+        else {
+            return Ok(existing.to_string());
+        };
+
+        codec.insert_server(existing, key, &name, entry)
+    }
+
+    /// Remove kodegen's server entries from an existing config
+    ///
+    /// Only entries of ours (see [`is_kodegen_entry`]) named `kodegen` or
+    /// `kodegen-<n>` are removed, along with those of the additional servers,
+    /// and `mcpServers` is dropped again if that leaves it empty. A config
+    /// without our entries is returned unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::ParseFailed`] if the config cannot be parsed for
+    /// the given format, or another [`ConfigError`] if serialization fails.
+    pub fn remove(&self, existing: &str, format: ConfigFormat) -> Result<String, ConfigError> {
+        self.remove_from(existing, format, &MergeTarget::default())
+    }
+
+    /// Like [`Self::remove`], for servers kept where [`Self::merge_into`] put them
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Self::remove`], or [`ConfigError::Other`] if
+    /// configs of the format can't hold servers as `target` asks.
+    pub fn remove_from(
+        &self,
+        existing: &str,
+        format: ConfigFormat,
+        target: &MergeTarget,
+    ) -> Result<String, ConfigError> {
+        if self.has_additional_servers() {
+            return self
+                .servers()
+                .try_fold(existing.to_string(), |removed, server| {
+                    server.remove_from(&removed, format, target)
+                });
+        }
+
+        self.remove_format(existing, format, target)
+            .map_err(|e| ConfigError::classify(e, format))
+    }
+
+    /// Remove from a config of the given format (see [`Self::remove_from`])
+    fn remove_format(
+        &self,
+        existing: &str,
+        format: ConfigFormat,
+        target: &MergeTarget,
+    ) -> Result<String> {
+        ensure_config_len(existing)?;
+        if existing.trim().is_empty() {
             return Ok(existing.to_string());
         }
 
-        // Merge efficiently
-        if let Some(obj) = config.as_object_mut() {
-            if !obj.contains_key("mcpServers") {
-                obj.insert("mcpServers".to_string(), serde_json::json!({}));
+        let path = target.path();
+        match (target.entry_shape, path.as_slice(), format) {
+            (EntryShape::Keyed, [key], _) => {
+                let codec = codec::get(format).ok_or(ConfigError::UnsupportedFormat(format))?;
+                self.remove_with(codec.as_ref(), existing, key)
             }
-
-            if let Some(servers) = obj.get_mut("mcpServers").and_then(|v| v.as_object_mut()) {
-                servers.insert(
-                    "kodegen".to_string(),
-                    self.kodegen_config.json["mcpServers"]["kodegen"].clone(),
-                );
+            (EntryShape::Keyed, _, ConfigFormat::Json | ConfigFormat::Sqlite) => {
+                self.remove_json_servers_at(existing, &path, JsonValue::clone)
             }
+            (EntryShape::Named, _, ConfigFormat::Json | ConfigFormat::Sqlite) => {
+                self.remove_json_list(existing, &path)
+            }
+            (EntryShape::Named, [key], ConfigFormat::Yaml) => self.remove_yaml_list(existing, key),
+            _ => Err(unsupported_target(format, target)),
         }
+    }
+
+    /// Remove our entries from the `key` servers of a config through its format's codec
+    fn remove_with(&self, codec: &dyn FormatCodec, existing: &str, key: &str) -> Result<String> {
+        let config = codec.parse(existing)?;
+        let own: Vec<&String> = config
+            .get(key)
+            .and_then(JsonValue::as_object)
+            .into_iter()
+            .flat_map(serde_json::Map::keys)
+            .filter(|name| {
+                codec
+                    .find_server(&config, key, name)
+                    .is_some_and(|entry| self.is_own_entry(name, &entry))
+            })
+            .collect();
 
-        Ok(serde_json::to_string_pretty(&config)?)
+        own.into_iter()
+            .try_fold(existing.to_string(), |content, name| {
+                codec.remove_server(&content, key, name)
+            })
     }
 
-    /// Merge TOML config with optimal performance
-    #[inline]
-    fn merge_toml(&self, existing: &str) -> Result<String> {
-        let mut config: TomlValue = if existing.trim().is_empty() {
-            toml::Value::Table(toml::map::Map::new())
-        } else {
-            toml::from_str(existing)?
-        };
+    /// Remove kodegen's entries from the `key` servers object of a JSON (or JSONC)
+    /// config, for clients whose entries have their own field names: `view`
+    /// maps an entry to a standard one (`command`, `args`, `env`)
+    ///
+    /// Like [`Self::merge_json_servers`], entries are cut out of the text so the
+    /// rest of the file is left as the user wrote it. The servers object itself
+    /// is removed once it is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config can't be parsed or has an unexpected shape.
+    pub(crate) fn remove_json_servers_as(
+        &self,
+        existing: &str,
+        key: &str,
+        view: impl Fn(&JsonValue) -> JsonValue,
+    ) -> Result<String> {
+        self.remove_json_servers_at(existing, &[key], view)
+    }
 
-        // Fast path: check if already configured
-        if let Some(table) = config.as_table()
-            && let Some(servers) = table.get("mcpServers").and_then(|v| v.as_table())
-            && servers.contains_key("kodegen")
-        {
+    /// Like [`Self::remove_json_servers_as`], for a servers object nested at `path`
+    /// (e.g. `["mcp", "servers"]`)
+    ///
+    /// Objects along the path that are left empty are removed as well.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config can't be parsed or has an unexpected shape.
+    pub(crate) fn remove_json_servers_at(
+        &self,
+        existing: &str,
+        path: &[&str],
+        view: impl Fn(&JsonValue) -> JsonValue,
+    ) -> Result<String> {
+        ensure_config_len(existing)?;
+        if existing.trim().is_empty() {
             return Ok(existing.to_string());
         }
 
-        // Merge efficiently
-        if let Some(table) = config.as_table_mut() {
-            if !table.contains_key("mcpServers") {
-                table.insert(
-                    "mcpServers".to_string(),
-                    TomlValue::Table(toml::map::Map::new()),
-                );
-            }
+        let config: JsonValue = serde_json::from_str(jsonc::Document::new(existing).stripped())?;
+        ensure_json_object_at(&config, path)?;
+        let own: Vec<&String> = json_at(&config, path)
+            .and_then(JsonValue::as_object)
+            .into_iter()
+            .flatten()
+            .filter(|(name, entry)| self.is_own_entry(name, &view(entry)))
+            .map(|(name, _)| name)
+            .collect();
 
-            if let Some(servers) = table.get_mut("mcpServers").and_then(|v| v.as_table_mut()) {
-                servers.insert(
-                    "kodegen".to_string(),
-                    self.kodegen_config.toml["mcpServers"]["kodegen"].clone(),
-                );
-            }
-        }
+        own.into_iter()
+            .try_fold(existing.to_string(), |content, name| {
+                remove_json_server(&content, path, name)
+            })
+    }
+
+    /// Check whether a named server entry is one we injected
+    pub(crate) fn is_own_entry(&self, name: &str, entry: &JsonValue) -> bool {
+        let is_our_name = name == self.server_name
+            || name
+                .strip_prefix(self.server_name.as_str())
+                .and_then(|suffix| suffix.strip_prefix('-'))
+                .is_some_and(|n| n.parse::<u32>().is_ok());
 
-        Ok(toml::to_string_pretty(&config)?)
+        is_our_name && self.is_ours(entry)
     }
 
-    /// Merge YAML config with proper YAML parsing and serialization
-    #[inline]
-    fn merge_yaml(&self, existing: &str) -> Result<String> {
-        let mut config: YamlValue = if existing.trim().is_empty() {
-            YamlValue::Mapping(serde_yaml::Mapping::new())
+    /// Check whether an entry runs kodegen, including a custom binary from
+    /// [`Self::with_config`] or endpoint from [`Self::with_http_config`]
+    pub(crate) fn is_ours(&self, entry: &JsonValue) -> bool {
+        is_kodegen_entry(entry)
+            || entry
+                .get("command")
+                .is_some_and(|command| command.as_str() == Some(&self.server_config.command))
+            || entry
+                .get("url")
+                .is_some_and(|url| url.as_str() == Some(&self.http_config.url))
+    }
+
+    /// Add `entry` to the `key` servers object of a JSON (or JSONC) config
+    ///
+    /// The entry is inserted as a text edit so comments, trailing commas, key
+    /// order and indentation elsewhere in the file are preserved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config can't be parsed or has an unexpected shape.
+    pub(crate) fn merge_json_servers(
+        &self,
+        existing: &str,
+        key: &str,
+        entry: &JsonValue,
+    ) -> Result<String> {
+        self.merge_json_servers_as(existing, key, entry, JsonValue::clone)
+    }
+
+    /// Like [`Self::merge_json_servers`], for clients whose entries have their own
+    /// field names: `view` maps an entry to a standard one (`command`, `args`, `env`)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config can't be parsed or has an unexpected shape.
+    pub(crate) fn merge_json_servers_as(
+        &self,
+        existing: &str,
+        key: &str,
+        entry: &JsonValue,
+        view: impl Fn(&JsonValue) -> JsonValue,
+    ) -> Result<String> {
+        self.merge_json_servers_at(existing, &[key], entry, view)
+    }
+
+    /// Like [`Self::merge_json_servers_as`], for a servers object nested at `path`
+    /// (e.g. `["mcp", "servers"]`)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config can't be parsed or has an unexpected shape.
+    pub(crate) fn merge_json_servers_at(
+        &self,
+        existing: &str,
+        path: &[&str],
+        entry: &JsonValue,
+        view: impl Fn(&JsonValue) -> JsonValue,
+    ) -> Result<String> {
+        ensure_config_len(existing)?;
+        let content = if existing.trim().is_empty() {
+            "{}"
         } else {
-            serde_yaml::from_str(existing)
-                .map_err(|e| anyhow!("Failed to parse existing YAML: {e}"))?
+            existing
         };
 
+        let config: JsonValue = serde_json::from_str(jsonc::Document::new(content).stripped())?;
+        ensure_json_object_at(&config, path)?;
+
         // Fast path: check if already configured
-        if let YamlValue::Mapping(ref map) = config
-            && let Some(YamlValue::Mapping(servers)) =
-                map.get(YamlValue::String("mcpServers".to_string()))
-            && servers.contains_key(YamlValue::String("kodegen".to_string()))
-        {
+        let Some(name) = self.resolve_server_name_against(
+            |name| {
+                json_at(&config, path)
+                    .and_then(|servers| servers.get(name))
+                    .map(&view)
+            },
+            &view(entry),
+        )?
+        else {
             return Ok(existing.to_string());
+        };
+
+        insert_json_server(existing, path, &name, entry)
+    }
+
+    /// Add `entry` to the list at `path` of a JSON (or JSONC) config whose items
+    /// are named by a `name` field
+    ///
+    /// The list is replaced as a whole in the text, so the rest of the file is
+    /// left as the user wrote it. An outdated item of ours is replaced in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config can't be parsed or has an unexpected shape.
+    pub(crate) fn merge_json_list(
+        &self,
+        existing: &str,
+        path: &[&str],
+        entry: &JsonValue,
+    ) -> Result<String> {
+        ensure_config_len(existing)?;
+        let content = if existing.trim().is_empty() {
+            "{}"
+        } else {
+            existing
+        };
+
+        let document = jsonc::Document::new(content);
+        let mut config: JsonValue = serde_json::from_str(document.stripped())?;
+        let mut items = json_list_at(&config, path)?;
+
+        let Some(name) = self.resolve_server_name_against(
+            |name| named_json_item(&items, name).map(|index| items[index].clone()),
+            entry,
+        )?
+        else {
+            return Ok(existing.to_string());
+        };
+
+        let mut item = serde_json::Map::new();
+        item.insert("name".to_string(), JsonValue::String(name.clone()));
+        if let JsonValue::Object(fields) = entry {
+            item.extend(fields.clone());
+        }
+        match named_json_item(&items, &name) {
+            Some(index) => items[index] = JsonValue::Object(item),
+            None => items.push(JsonValue::Object(item)),
         }
 
-        // Merge efficiently
-        if let YamlValue::Mapping(ref mut map) = config {
-            if !map.contains_key(YamlValue::String("mcpServers".to_string())) {
-                map.insert(
-                    YamlValue::String("mcpServers".to_string()),
-                    YamlValue::Mapping(serde_yaml::Mapping::new()),
-                );
-            }
+        set_json_list(&document, &mut config, path, items)
+    }
 
-            if let Some(YamlValue::Mapping(servers)) =
-                map.get_mut(YamlValue::String("mcpServers".to_string()))
-                && let YamlValue::Mapping(ref template_servers) = self.kodegen_config.yaml
-                && let Some(YamlValue::Mapping(kodegen_map)) =
-                    template_servers.get(YamlValue::String("mcpServers".to_string()))
-                && let Some(kodegen_entry) =
-                    kodegen_map.get(YamlValue::String("kodegen".to_string()))
-            {
-                servers.insert(
-                    YamlValue::String("kodegen".to_string()),
-                    kodegen_entry.clone(),
-                );
-            }
+    /// Remove our items from the list at `path` of a JSON (or JSONC) config
+    ///
+    /// A list left empty is removed, along with enclosing objects it leaves
+    /// empty, unless the user left comments in them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config can't be parsed or has an unexpected shape.
+    pub(crate) fn remove_json_list(&self, existing: &str, path: &[&str]) -> Result<String> {
+        ensure_config_len(existing)?;
+        if existing.trim().is_empty() {
+            return Ok(existing.to_string());
+        }
+
+        let document = jsonc::Document::new(existing);
+        let mut config: JsonValue = serde_json::from_str(document.stripped())?;
+        let mut items = json_list_at(&config, path)?;
+
+        let before = items.len();
+        items.retain(|item| {
+            !item
+                .get("name")
+                .and_then(JsonValue::as_str)
+                .is_some_and(|name| self.is_own_entry(name, item))
+        });
+        if items.len() == before {
+            return Ok(existing.to_string());
+        }
+        if !items.is_empty() {
+            return set_json_list(&document, &mut config, path, items);
         }
 
-        serde_yaml::to_string(&config).map_err(|e| anyhow!("Failed to serialize YAML: {e}"))
+        // Drop the list we emptied, and objects that leaves empty
+        let (key, parents) = path.split_last().context("Empty JSON path")?;
+        if let Some(parent) = json_object_at_mut(&mut config, parents) {
+            parent.remove(*key);
+        }
+        let content = checked_edit(&document, document.remove(parents, key), &config)?;
+        remove_blank_objects(content, parents)
     }
 
-    /// Merge Plist config with proper plist parsing and serialization (macOS only)
-    #[cfg(target_os = "macos")]
-    #[inline]
-    fn merge_plist(&self, existing: &str) -> Result<String> {
-        use plist::Value;
+    /// Add `entry` to the `key` list of a YAML config whose items are named by a `name` field
+    ///
+    /// For clients whose servers are a list of maps rather than a map keyed by
+    /// name (e.g. Continue). An outdated item of ours is replaced in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config can't be parsed or has an unexpected shape.
+    pub(crate) fn merge_yaml_list(
+        &self,
+        existing: &str,
+        key: &str,
+        entry: &JsonValue,
+    ) -> Result<String> {
+        ensure_config_len(existing)?;
+        let mut config = parse_yaml_mapping(existing)?;
+        if config.get(key).is_some_and(|items| !items.is_sequence()) {
+            bail!("`{key}` in existing config is not a list");
+        }
 
-        let mut config: Value = if existing.trim().is_empty() {
-            Value::Dictionary(plist::Dictionary::new())
-        } else {
-            plist::from_reader(std::io::Cursor::new(existing.as_bytes()))
-                .context("Failed to parse existing plist")?
+        let Some(name) = self.resolve_server_name(|name| {
+            named_item(config.get(key), name).map(|(_, item)| to_json(item))
+        })?
+        else {
+            return Ok(existing.to_string());
         };
 
-        // Fast path: check if already configured
-        if let Value::Dictionary(ref dict) = config
-            && let Some(Value::Dictionary(servers)) = dict.get("mcpServers")
-            && servers.contains_key("kodegen")
+        let mut item = serde_yaml::Mapping::new();
+        item.insert("name".into(), YamlValue::String(name.clone()));
+        if let YamlValue::Mapping(fields) = serde_yaml::to_value(entry)? {
+            item.extend(fields);
+        }
+
+        let position = named_item(config.get(key), &name).map(|(index, _)| index);
+        let item = YamlValue::Mapping(item);
+        let edited = yaml::upsert_item(existing, key, position, &item);
+        if let YamlValue::Mapping(ref mut map) = config
+            && let YamlValue::Sequence(items) = map
+                .entry(key.into())
+                .or_insert_with(|| YamlValue::Sequence(Vec::new()))
         {
+            match position {
+                Some(index) => items[index] = item,
+                None => items.push(item),
+            }
+        }
+
+        checked_yaml_edit(edited, &config)
+    }
+
+    /// Remove our items from the `key` list of a YAML config, dropping the list if it ends up empty
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config can't be parsed.
+    pub(crate) fn remove_yaml_list(&self, existing: &str, key: &str) -> Result<String> {
+        ensure_config_len(existing)?;
+        if existing.trim().is_empty() {
+            return Ok(existing.to_string());
+        }
+
+        let mut config: YamlValue =
+            serde_yaml::from_str(existing).context("Failed to parse existing YAML")?;
+
+        let Some(map) = config.as_mapping_mut() else {
+            return Ok(existing.to_string());
+        };
+        let Some(items) = map.get_mut(key).and_then(YamlValue::as_sequence_mut) else {
+            return Ok(existing.to_string());
+        };
+
+        let own: Vec<usize> = items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| {
+                item.get("name")
+                    .and_then(YamlValue::as_str)
+                    .is_some_and(|name| self.is_own_entry(name, &to_json(item)))
+            })
+            .map(|(index, _)| index)
+            .collect();
+        if own.is_empty() {
             return Ok(existing.to_string());
         }
 
-        // Merge efficiently
-        if let Value::Dictionary(ref mut dict) = config {
-            // Ensure mcpServers exists
-            if !dict.contains_key("mcpServers") {
-                dict.insert(
-                    "mcpServers".to_string(),
-                    Value::Dictionary(plist::Dictionary::new()),
-                );
+        for &index in own.iter().rev() {
+            items.remove(index);
+        }
+        if items.is_empty() {
+            map.remove(key);
+        }
+
+        checked_yaml_edit(yaml::remove_items(existing, key, &own), &config)
+    }
+}
+
+/// Reject existing configs larger than [`MAX_CONFIG_LEN`]
+///
+/// # Errors
+///
+/// Returns an error if the config is too large to merge.
+pub fn ensure_config_len(existing: &str) -> Result<()> {
+    if existing.len() > MAX_CONFIG_LEN {
+        bail!(
+            "Existing config is too large to merge ({} bytes, limit {MAX_CONFIG_LEN})",
+            existing.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Error for a target that configs of `format` can't hold servers at
+fn unsupported_target(format: ConfigFormat, target: &MergeTarget) -> anyhow::Error {
+    anyhow!(
+        "{format:?} configs can't hold {:?} servers at `{}`",
+        target.entry_shape,
+        target.key_path.join(".")
+    )
+}
+
+/// Parse a YAML config that must be a mapping, treating an empty file as one
+pub(crate) fn parse_yaml_mapping(existing: &str) -> Result<YamlValue> {
+    let config: YamlValue = if existing.trim().is_empty() {
+        YamlValue::Mapping(serde_yaml::Mapping::new())
+    } else {
+        serde_yaml::from_str(existing).context("Failed to parse existing YAML")?
+    };
+
+    // A file holding only comments parses as null
+    if config.is_null() {
+        return Ok(YamlValue::Mapping(serde_yaml::Mapping::new()));
+    }
+    if !config.is_mapping() {
+        bail!("Existing YAML config is not a mapping");
+    }
+    Ok(config)
+}
+
+/// Find the item with the given `name` field in a YAML list
+fn named_item<'a>(items: Option<&'a YamlValue>, name: &str) -> Option<(usize, &'a YamlValue)> {
+    items?
+        .as_sequence()?
+        .iter()
+        .enumerate()
+        .find(|(_, item)| item.get("name").and_then(YamlValue::as_str) == Some(name))
+}
+
+/// Accept an in-place JSONC edit only if it means the same as the structural merge
+///
+/// Falls back to rewriting the whole config from `expected` in the document's
+/// indentation, which loses comments, if the edit couldn't be made or doesn't
+/// parse back to it.
+pub(crate) fn checked_edit(
+    document: &jsonc::Document<'_>,
+    edited: Result<Option<String>>,
+    expected: &JsonValue,
+) -> Result<String> {
+    match edited {
+        Ok(Some(edited)) => {
+            let parsed: Option<JsonValue> =
+                serde_json::from_str(jsonc::Document::new(&edited).stripped()).ok();
+            if parsed.as_ref() == Some(expected) {
+                return Ok(edited);
             }
+            log::warn!("In-place JSON edit didn't match the merged config, rewriting it");
+        }
+        Ok(None) => log::warn!("Entry to edit not found in JSON config text, rewriting it"),
+        Err(e) => log::warn!("Couldn't edit JSON config in place, rewriting it: {e}"),
+    }
+
+    document.rewrite(expected)
+}
+
+/// Give changed config text the line endings and final newline of the original
+///
+/// Rewrites and inserted lines come out with `\n`; a file that consistently
+/// uses `\r\n` gets those throughout. Files with mixed line endings are left
+/// as they come, and a new file gets whatever the format's writer produced.
+pub(crate) fn match_line_endings(original: &str, text: &str) -> String {
+    if original.trim().is_empty() || original == text {
+        return text.to_string();
+    }
 
-            // Insert kodegen config
-            if let Some(Value::Dictionary(servers)) = dict.get_mut("mcpServers")
-                && let Value::Dictionary(ref template_root) = self.kodegen_config.plist
-                && let Some(Value::Dictionary(template_servers)) = template_root.get("mcpServers")
-                && let Some(kodegen_config) = template_servers.get("kodegen")
-            {
-                servers.insert("kodegen".to_string(), kodegen_config.clone());
+    let mut text = text.replace("\r\n", "\n");
+    if original.ends_with('\n') && !text.ends_with('\n') {
+        text.push('\n');
+    } else if !original.ends_with('\n') {
+        text.truncate(text.trim_end_matches('\n').len());
+    }
+
+    let crlf = original.matches("\r\n").count();
+    if crlf > 0 && crlf == original.matches('\n').count() {
+        text = text.replace('\n', "\r\n");
+    }
+    text
+}
+
+/// Accept an in-place YAML edit only if it means the same as the structural merge
+///
+/// Falls back to rewriting the whole config from `expected`, which loses
+/// comments and anchors, if the edit couldn't be made or doesn't parse back to it.
+pub(crate) fn checked_yaml_edit(edited: Option<String>, expected: &YamlValue) -> Result<String> {
+    match edited {
+        Some(edited) => {
+            if parse_yaml_mapping(&edited).ok().as_ref() == Some(expected) {
+                return Ok(edited);
             }
+            log::warn!("In-place YAML edit didn't match the merged config, rewriting it");
         }
+        None => log::warn!("Couldn't edit YAML config in place, rewriting it"),
+    }
 
-        // Serialize to XML plist format
-        let mut output = Vec::new();
-        plist::to_writer_xml(&mut output, &config).context("Failed to serialize plist")?;
+    serde_yaml::to_string(expected).map_err(|e| anyhow!("Failed to serialize YAML: {e}"))
+}
+
+/// Check that a parsed JSON config is an object whose `key` (if present) is an object too
+///
+/// Otherwise the entry would silently not be inserted.
+pub(crate) fn ensure_json_servers_object(config: &JsonValue, key: &str) -> Result<()> {
+    ensure_json_object_at(config, &[key])
+}
 
-        String::from_utf8(output).context("Failed to convert plist to UTF-8")
+/// Check that the config and every object along `path` that exists are objects
+pub(crate) fn ensure_json_object_at(config: &JsonValue, path: &[&str]) -> Result<()> {
+    if !config.is_object() {
+        bail!("Existing JSON config is not an object");
+    }
+    for depth in 1..=path.len() {
+        if json_at(config, &path[..depth]).is_some_and(|value| !value.is_object()) {
+            bail!(
+                "`{}` in existing config is not an object",
+                path[..depth].join(".")
+            );
+        }
     }
 
-    /// Plist format not supported on non-macOS platforms
-    #[cfg(not(target_os = "macos"))]
-    #[inline]
-    fn merge_plist(&self, _existing: &str) -> Result<String> {
-        Err(anyhow!("Plist format only supported on macOS"))
+    Ok(())
+}
+
+/// Get the value nested at `path` in a JSON config
+pub(crate) fn json_at<'a>(config: &'a JsonValue, path: &[&str]) -> Option<&'a JsonValue> {
+    path.iter().try_fold(config, |value, key| value.get(key))
+}
+
+/// Get the object nested at `path` in a JSON config, if there is one
+fn json_object_at_mut<'a>(
+    config: &'a mut JsonValue,
+    path: &[&str],
+) -> Option<&'a mut serde_json::Map<String, JsonValue>> {
+    path.iter()
+        .try_fold(config, |value, key| value.get_mut(key))?
+        .as_object_mut()
+}
+
+/// Get a copy of the list at `path` of a JSON config, empty if there's none yet
+pub(crate) fn json_list_at(config: &JsonValue, path: &[&str]) -> Result<Vec<JsonValue>> {
+    let (_, parents) = path.split_last().context("Empty JSON path")?;
+    ensure_json_object_at(config, parents)?;
+    match json_at(config, path) {
+        Some(items) => Ok(items
+            .as_array()
+            .with_context(|| format!("`{}` in existing config is not a list", path.join(".")))?
+            .clone()),
+        None => Ok(Vec::new()),
     }
 }
 
+/// Find the item of a JSON list whose `name` field is `name`
+fn named_json_item(items: &[JsonValue], name: &str) -> Option<usize> {
+    items
+        .iter()
+        .position(|item| item.get("name").and_then(JsonValue::as_str) == Some(name))
+}
+
+/// Replace the list at `path` of a JSON config in its text
+pub(crate) fn set_json_list(
+    document: &jsonc::Document<'_>,
+    config: &mut JsonValue,
+    path: &[&str],
+    items: Vec<JsonValue>,
+) -> Result<String> {
+    let (key, parents) = path.split_last().context("Empty JSON path")?;
+    let items = JsonValue::Array(items);
+    let edited = document.upsert(parents, key, &items).map(Some);
+
+    // Missing objects along the path are created; existing ones were checked
+    let list = path
+        .iter()
+        .fold(&mut *config, |value, key| &mut value[*key]);
+    *list = items;
+
+    checked_edit(document, edited, config)
+}
+
+/// Remove the empty object at `path`, then each enclosing one it leaves empty
+///
+/// Objects holding comments are kept, as are the config's own braces.
+pub(crate) fn remove_blank_objects(mut content: String, path: &[&str]) -> Result<String> {
+    for depth in (1..=path.len()).rev() {
+        let document = jsonc::Document::new(&content);
+        if !document.is_blank_object(&path[..depth])? {
+            break;
+        }
+
+        let mut config: JsonValue = serde_json::from_str(document.stripped())?;
+        let (key, parents) = path[..depth].split_last().context("Empty JSON path")?;
+        if let Some(parent) = json_object_at_mut(&mut config, parents) {
+            parent.remove(*key);
+        }
+        content = checked_edit(&document, document.remove(parents, key), &config)?;
+    }
+
+    Ok(content)
+}
+
+/// Add server `name` to the servers object at `path` of a JSON (or JSONC) config,
+/// or replace it
+///
+/// The entry is inserted as a text edit so comments, trailing commas, key
+/// order and indentation elsewhere in the file are preserved.
+///
+/// # Errors
+///
+/// Returns an error if the config can't be parsed or has an unexpected shape.
+pub(crate) fn insert_json_server(
+    existing: &str,
+    path: &[&str],
+    name: &str,
+    entry: &JsonValue,
+) -> Result<String> {
+    let content = if existing.trim().is_empty() {
+        "{}"
+    } else {
+        existing
+    };
+
+    let document = jsonc::Document::new(content);
+    let mut config: JsonValue = serde_json::from_str(document.stripped())?;
+    ensure_json_object_at(&config, path)?;
+    let edited = document.upsert(path, name, entry).map(Some);
+
+    // Missing objects along the path are created; existing ones were checked above
+    let servers = path.iter().fold(&mut config, |value, key| &mut value[*key]);
+    if servers.is_null() {
+        *servers = JsonValue::Object(serde_json::Map::new());
+    }
+    if let Some(servers) = servers.as_object_mut() {
+        servers.insert(name.to_string(), entry.clone());
+    }
+
+    checked_edit(&document, edited, &config)
+}
+
+/// Remove server `name` from the servers object at `path` of a JSON (or JSONC) config
+///
+/// The entry is cut out of the text. A servers object that leaves empty is
+/// removed too, along with enclosing ones, unless the user left comments in it.
+///
+/// # Errors
+///
+/// Returns an error if the config can't be parsed or has an unexpected shape.
+pub(crate) fn remove_json_server(existing: &str, path: &[&str], name: &str) -> Result<String> {
+    let document = jsonc::Document::new(existing);
+    let mut config: JsonValue = serde_json::from_str(document.stripped())?;
+    ensure_json_object_at(&config, path)?;
+
+    let Some(servers) = json_object_at_mut(&mut config, path) else {
+        return Ok(existing.to_string());
+    };
+    if servers.remove(name).is_none() {
+        return Ok(existing.to_string());
+    }
+    let emptied = servers.is_empty();
+
+    let content = checked_edit(&document, document.remove(path, name), &config)?;
+    if emptied {
+        remove_blank_objects(content, path)
+    } else {
+        Ok(content)
+    }
+}
+
+/// Add server `name` to the `key` servers table of a TOML config, or replace it
+///
+/// The entry is edited into the document (with `toml_edit`) rather than
+/// re-serializing the whole config, so comments, table order and whitespace
+/// survive. A new entry becomes a `[key.<name>]` table, or a key of the
+/// servers table when that's written inline.
+///
+/// # Errors
+///
+/// Returns an error if the config can't be parsed or has an unexpected shape.
+pub(crate) fn insert_toml_server(
+    existing: &str,
+    key: &str,
+    name: &str,
+    entry: &TomlValue,
+) -> Result<String> {
+    let mut document: DocumentMut = existing.parse()?;
+    let servers = document.entry(key).or_insert_with(|| {
+        // Only `[key.<name>]` headers, no empty `[key]` one
+        let mut servers = toml_edit::Table::new();
+        servers.set_implicit(true);
+        toml_edit::Item::Table(servers)
+    });
+
+    let entry = toml_edit_value(entry);
+    match servers {
+        toml_edit::Item::Table(servers) => {
+            let toml_edit::Value::InlineTable(entry) = entry else {
+                bail!("TOML server entry is not a table");
+            };
+            servers.insert(name, toml_edit::Item::Table(entry.into_table()));
+        }
+        toml_edit::Item::Value(toml_edit::Value::InlineTable(servers)) => {
+            servers.insert(name, entry);
+        }
+        _ => bail!("`{key}` in existing config is not a table"),
+    }
+
+    Ok(document.to_string())
+}
+
+/// Remove servers `names` from the `key` servers table of a TOML config,
+/// dropping the table if that empties it
+///
+/// # Errors
+///
+/// Returns an error if the config can't be parsed.
+pub(crate) fn remove_toml_entries(existing: &str, key: &str, names: &[&str]) -> Result<String> {
+    let mut document: DocumentMut = existing.parse()?;
+    let Some(servers) = document
+        .get_mut(key)
+        .and_then(toml_edit::Item::as_table_like_mut)
+    else {
+        return Ok(existing.to_string());
+    };
+    for name in names {
+        servers.remove(name);
+    }
+    if servers.is_empty() {
+        document.remove(key);
+    }
+
+    Ok(document.to_string())
+}
+
+/// Convert a JSON server entry to TOML
+///
+/// TOML has no null, and an empty table (like an empty `env`) is just noise,
+/// so fields holding either are left out.
+pub(crate) fn toml_entry(entry: &JsonValue) -> TomlValue {
+    let Some(fields) = entry.as_object() else {
+        return TomlValue::try_from(entry)
+            .unwrap_or_else(|_| TomlValue::Table(toml::map::Map::new()));
+    };
+
+    fields
+        .iter()
+        .filter(|(_, value)| {
+            !value.is_null() && value.as_object().is_none_or(|map| !map.is_empty())
+        })
+        .filter_map(|(field, value)| Some((field.clone(), TomlValue::try_from(value).ok()?)))
+        .collect::<toml::map::Map<_, _>>()
+        .into()
+}
+
+/// Convert any config value to JSON for format-independent inspection
+pub(crate) fn to_json(value: &impl Serialize) -> JsonValue {
+    serde_json::to_value(value).unwrap_or(JsonValue::Null)
+}
+
+/// Convert a TOML value to the `toml_edit` value written into documents (tables become inline)
+fn toml_edit_value(value: &TomlValue) -> toml_edit::Value {
+    match value {
+        TomlValue::String(s) => s.as_str().into(),
+        TomlValue::Integer(i) => (*i).into(),
+        TomlValue::Float(f) => (*f).into(),
+        TomlValue::Boolean(b) => (*b).into(),
+        TomlValue::Datetime(datetime) => (*datetime).into(),
+        TomlValue::Array(items) => items
+            .iter()
+            .map(toml_edit_value)
+            .collect::<toml_edit::Array>()
+            .into(),
+        TomlValue::Table(table) => table
+            .iter()
+            .map(|(key, value)| (key.as_str(), toml_edit_value(value)))
+            .collect::<toml_edit::InlineTable>()
+            .into(),
+    }
+}
+
+/// Check whether a server entry was written by us (it launches or points at kodegen)
+#[must_use]
+pub fn is_kodegen_entry(entry: &JsonValue) -> bool {
+    let command = entry.get("command").and_then(|command| {
+        // Zed's legacy format nests the command as `{ "path": ... }`
+        command
+            .as_str()
+            .or_else(|| command.get("path").and_then(JsonValue::as_str))
+    });
+
+    if let Some(command) = command {
+        // Windows-side clients configured from WSL start kodegen through `wsl.exe`
+        if command_stem(command) == "wsl" {
+            return entry
+                .get("args")
+                .and_then(JsonValue::as_array)
+                .is_some_and(|args| {
+                    args.iter()
+                        .filter_map(JsonValue::as_str)
+                        .any(|arg| command_stem(arg) == SERVER_NAME)
+                });
+        }
+        return command_stem(command) == SERVER_NAME;
+    }
+
+    entry
+        .get("url")
+        .and_then(JsonValue::as_str)
+        .is_some_and(|url| url.contains(SERVER_NAME))
+}
+
+/// Get a command's file name without extension, for Unix and Windows paths alike
+fn command_stem(command: &str) -> &str {
+    let name = command.rsplit(['/', '\\']).next().unwrap_or(command);
+    name.rsplit_once('.').map_or(name, |(stem, _)| stem)
+}
+
 impl Default for ConfigMerger {
     fn default() -> Self {
         Self::new()
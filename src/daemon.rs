@@ -0,0 +1,321 @@
+//! The watcher as a long-running service
+//!
+//! [`Daemon::start`] runs an [`AutoConfigWatcher`] in the background and
+//! [`Daemon::shutdown`] stops it cleanly, letting in-flight updates finish.
+//! While it runs it can be paused, resumed, asked to rescan or queried for its
+//! status, both in-process and, on Unix, by other processes (such as the
+//! bundler) over a socket in the state directory: each line sent is a
+//! [`DaemonCommand`] and each reply a line of [`DaemonStatus`] JSON.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow, bail};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+use crate::watcher::AutoConfigWatcher;
+
+/// Command accepted by a running [`Daemon`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DaemonCommand {
+    /// Report what the daemon is doing
+    Status,
+    /// Scan every client again, configuring new installations
+    Rescan,
+    /// Stop reacting to config changes
+    Pause,
+    /// React to config changes again, catching up on the ones missed while paused
+    Resume,
+}
+
+impl DaemonCommand {
+    /// Get the command as sent over the socket
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Status => "status",
+            Self::Rescan => "rescan",
+            Self::Pause => "pause",
+            Self::Resume => "resume",
+        }
+    }
+}
+
+impl fmt::Display for DaemonCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DaemonCommand {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "status" => Ok(Self::Status),
+            "rescan" => Ok(Self::Rescan),
+            "pause" => Ok(Self::Pause),
+            "resume" => Ok(Self::Resume),
+            other => bail!("Unknown daemon command: {other}"),
+        }
+    }
+}
+
+/// What a running [`Daemon`] is doing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    /// Config changes are being ignored
+    pub paused: bool,
+    /// Ids of the clients found installed
+    pub installed_clients: Vec<String>,
+    /// Configs waiting out their debounce window
+    pub pending_changes: usize,
+    /// Configs being processed
+    pub active_tasks: usize,
+}
+
+/// A watcher running in the background
+pub struct Daemon {
+    watcher: AutoConfigWatcher,
+    task: JoinHandle<Result<()>>,
+    #[cfg(unix)]
+    socket: Option<socket::Server>,
+}
+
+impl Daemon {
+    /// Start the watcher in the background, along with its command socket on Unix
+    ///
+    /// Must be called from within a tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if another daemon is already listening on the socket
+    /// or the socket can't be created.
+    pub fn start(watcher: AutoConfigWatcher) -> Result<Self> {
+        #[cfg(unix)]
+        let socket = Some(socket::Server::bind(watcher.clone())?);
+
+        let task = tokio::spawn(watcher.clone().run());
+        info!("🚀 Autoconfig daemon started");
+
+        Ok(Self {
+            watcher,
+            task,
+            #[cfg(unix)]
+            socket,
+        })
+    }
+
+    /// Run a command, returning the resulting status
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a rescan fails.
+    pub async fn execute(&self, command: DaemonCommand) -> Result<DaemonStatus> {
+        execute(&self.watcher, command).await
+    }
+
+    /// Report what the daemon is doing
+    #[must_use]
+    pub fn status(&self) -> DaemonStatus {
+        status(&self.watcher)
+    }
+
+    /// Check whether the watcher is still running (it stops on errors and signals)
+    #[must_use]
+    pub fn is_running(&self) -> bool {
+        !self.task.is_finished()
+    }
+
+    /// Stop the watcher once the configs being processed are done
+    ///
+    /// # Errors
+    ///
+    /// Returns the error the watcher stopped with, if any.
+    pub async fn shutdown(self) -> Result<()> {
+        #[cfg(unix)]
+        if let Some(socket) = self.socket {
+            socket.close();
+        }
+
+        self.watcher.request_shutdown();
+        let result = self
+            .task
+            .await
+            .map_err(|e| anyhow!("Watcher task failed: {e}"))?;
+        info!("Autoconfig daemon stopped");
+        result
+    }
+}
+
+/// Run a command against a watcher
+async fn execute(watcher: &AutoConfigWatcher, command: DaemonCommand) -> Result<DaemonStatus> {
+    match command {
+        DaemonCommand::Status => {}
+        DaemonCommand::Rescan => watcher.rescan().await?,
+        DaemonCommand::Pause => {
+            info!("⏸️  Pausing autoconfig");
+            watcher.set_paused(true);
+        }
+        DaemonCommand::Resume => {
+            info!("▶️  Resuming autoconfig");
+            watcher.set_paused(false);
+            if let Err(e) = watcher.rescan().await {
+                error!("Failed to catch up after resuming: {e}");
+            }
+        }
+    }
+
+    Ok(status(watcher))
+}
+
+/// Take a snapshot of a watcher's state
+fn status(watcher: &AutoConfigWatcher) -> DaemonStatus {
+    DaemonStatus {
+        paused: watcher.is_paused(),
+        installed_clients: watcher.installed_clients(),
+        pending_changes: watcher.pending_changes(),
+        active_tasks: watcher.active_tasks(),
+    }
+}
+
+/// Send a command to the daemon running for the current user
+///
+/// # Errors
+///
+/// Returns an error if no daemon is running or the command fails.
+#[cfg(unix)]
+pub async fn send_command(command: DaemonCommand) -> Result<DaemonStatus> {
+    socket::send(command).await
+}
+
+#[cfg(unix)]
+mod socket {
+    use std::io::ErrorKind;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+
+    use anyhow::{Context, Result, anyhow, bail};
+    use log::{debug, warn};
+    use serde::{Deserialize, Serialize};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{UnixListener, UnixStream};
+    use tokio::task::JoinHandle;
+
+    use super::{DaemonCommand, DaemonStatus};
+    use crate::state::StateDirs;
+    use crate::watcher::AutoConfigWatcher;
+
+    /// Reply to a command: the status, or why the command failed
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Reply {
+        Status(DaemonStatus),
+        Error { error: String },
+    }
+
+    /// The command socket of a running daemon
+    pub(super) struct Server {
+        path: PathBuf,
+        task: JoinHandle<()>,
+    }
+
+    impl Server {
+        /// Listen for commands, taking over the socket of a daemon that didn't shut down cleanly
+        pub(super) fn bind(watcher: AutoConfigWatcher) -> Result<Self> {
+            let state_dirs = StateDirs::resolve()?;
+            let path = state_dirs.daemon_socket_path();
+
+            if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+                bail!(
+                    "An autoconfig daemon is already running ({})",
+                    path.display()
+                );
+            }
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+            std::fs::create_dir_all(state_dirs.root())?;
+
+            let listener = UnixListener::bind(&path)
+                .with_context(|| format!("Failed to listen on {}", path.display()))?;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+            debug!("Listening for daemon commands on {}", path.display());
+
+            let task = tokio::spawn(async move {
+                loop {
+                    match listener.accept().await {
+                        Ok((stream, _)) => {
+                            tokio::spawn(serve(stream, watcher.clone()));
+                        }
+                        Err(e) => warn!("Failed to accept daemon connection: {e}"),
+                    }
+                }
+            });
+
+            Ok(Self { path, task })
+        }
+
+        /// Stop accepting commands and remove the socket
+        pub(super) fn close(self) {
+            self.task.abort();
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+
+    /// Answer the commands of one connection, a line each
+    async fn serve(stream: UnixStream, watcher: AutoConfigWatcher) {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let reply = match line.parse::<DaemonCommand>() {
+                Ok(command) => super::execute(&watcher, command).await,
+                Err(e) => Err(e),
+            };
+            let reply = match reply {
+                Ok(status) => Reply::Status(status),
+                Err(e) => Reply::Error {
+                    error: e.to_string(),
+                },
+            };
+
+            let Ok(mut json) = serde_json::to_string(&reply) else {
+                return;
+            };
+            json.push('\n');
+            if writer.write_all(json.as_bytes()).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Send a command to the daemon and wait for its reply
+    pub(super) async fn send(command: DaemonCommand) -> Result<DaemonStatus> {
+        let path = StateDirs::resolve()?.daemon_socket_path();
+        let stream = UnixStream::connect(&path)
+            .await
+            .with_context(|| format!("No autoconfig daemon is running ({})", path.display()))?;
+
+        let (reader, mut writer) = stream.into_split();
+        writer.write_all(format!("{command}\n").as_bytes()).await?;
+
+        let line = BufReader::new(reader)
+            .lines()
+            .next_line()
+            .await?
+            .ok_or_else(|| anyhow!("The autoconfig daemon closed the connection"))?;
+
+        match serde_json::from_str(&line)? {
+            Reply::Status(status) => Ok(status),
+            Reply::Error { error } => bail!(error),
+        }
+    }
+}
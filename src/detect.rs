@@ -0,0 +1,96 @@
+//! Finding out what an install would work with
+//!
+//! [`detect_clients`] scans for clients the way an install does, without
+//! writing anything, so GUIs can show which clients were found and which
+//! already have kodegen before the user decides to install.
+
+use std::path::PathBuf;
+
+use crate::error::ConfigError;
+use crate::settings::Settings;
+use crate::store::ConfigStore;
+use crate::version::Version;
+use crate::{ClientConfigPlugin, ConfigFormat};
+
+/// A client and what was found of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientStatus {
+    pub client_id: String,
+    pub client_name: String,
+    /// Whether the client was found installed (see [`Settings::is_client_installed`])
+    pub installed: bool,
+    /// Installed version, for clients that report it (see [`ClientConfigPlugin::detect_version`])
+    pub version: Option<Version>,
+    /// Format of the client's config files
+    pub format: ConfigFormat,
+    /// Every config location of the client, existing or not
+    pub configs: Vec<ConfigFileStatus>,
+}
+
+impl ClientStatus {
+    /// Whether any of the client's configs has kodegen
+    #[must_use]
+    pub fn is_configured(&self) -> bool {
+        self.configs.iter().any(|config| config.configured)
+    }
+}
+
+/// One config location of a client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigFileStatus {
+    pub path: PathBuf,
+    /// Whether the file exists
+    pub exists: bool,
+    /// Whether the file has kodegen's server entries
+    pub configured: bool,
+}
+
+/// Scan every registered client without changing anything
+///
+/// Covers the same clients and config locations (including those declared in
+/// the settings) an install would.
+///
+/// # Errors
+///
+/// Returns an error if the settings are invalid.
+pub fn detect_clients() -> Result<Vec<ClientStatus>, ConfigError> {
+    let settings = Settings::load()?;
+
+    Ok(crate::clients::registry()
+        .iter()
+        .map(|client| detect_client(client.as_ref(), &settings))
+        .collect())
+}
+
+/// Check a single client's installation and configs
+fn detect_client(client: &dyn ClientConfigPlugin, settings: &Settings) -> ClientStatus {
+    let installed = settings.is_client_installed(client);
+    let merger = settings.merger_for(client);
+
+    let configs = settings
+        .config_paths_for(client)
+        .into_iter()
+        .map(|config_path| {
+            let content = ConfigStore::new(client, &config_path.path).read().ok();
+            let merger = merger.clone().for_config(client, &config_path.path);
+            ConfigFileStatus {
+                exists: config_path.path.is_file(),
+                configured: content
+                    .is_some_and(|content| client.is_kodegen_configured(&content, &merger)),
+                path: config_path.path,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    ClientStatus {
+        client_id: client.client_id().to_string(),
+        client_name: client.client_name().to_string(),
+        installed,
+        version: configs
+            .iter()
+            .filter(|_| installed)
+            .find_map(|config| client.detect_version(&config.path)),
+        format: client.config_format(),
+        configs,
+    }
+}
@@ -0,0 +1,146 @@
+//! Writing configs the current user isn't allowed to write
+//!
+//! Configs of system-managed installs often live in root-owned directories.
+//! Installs report those as [`MessageCode::PermissionDenied`] with a
+//! `remediation` hint; an embedding application that can get elevated
+//! privileges (sudo with an askpass helper, a UAC prompt, a privileged helper
+//! service) passes an [`Elevation`] with
+//! [`InstallOptions::with_elevation`] to have them written anyway.
+//!
+//! [`MessageCode::PermissionDenied`]: crate::MessageCode::PermissionDenied
+//! [`InstallOptions::with_elevation`]: crate::InstallOptions::with_elevation
+
+use std::io;
+use std::path::Path;
+
+/// Writes a config with elevated privileges
+///
+/// Only called after a write was denied, with the complete new content.
+/// Implemented for closures, so `&|path: &Path, content: &[u8]| ...` works too.
+pub trait Elevation: Send + Sync {
+    /// Write `content` to `path`, creating its directory if needed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the privileges couldn't be obtained (e.g. the user
+    /// declined) or the write failed.
+    fn write_elevated(&self, path: &Path, content: &[u8]) -> io::Result<()>;
+}
+
+impl<F> Elevation for F
+where
+    F: Fn(&Path, &[u8]) -> io::Result<()> + Send + Sync,
+{
+    fn write_elevated(&self, path: &Path, content: &[u8]) -> io::Result<()> {
+        self(path, content)
+    }
+}
+
+/// Elevation through `sudo`
+///
+/// Asks for the password on the terminal, or through an askpass helper (see
+/// [`Sudo::with_askpass`]) for GUIs. The config is replaced through a temp
+/// file that takes over an existing config's owner and mode; a new config is
+/// created owned by root with mode 644.
+#[cfg(unix)]
+#[derive(Debug, Clone, Default)]
+pub struct Sudo {
+    /// Program asking for the password (`SUDO_ASKPASS`)
+    askpass: Option<std::path::PathBuf>,
+}
+
+#[cfg(unix)]
+impl Sudo {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask for the password with a graphical helper instead of the terminal
+    #[must_use]
+    pub fn with_askpass(mut self, askpass: impl Into<std::path::PathBuf>) -> Self {
+        self.askpass = Some(askpass.into());
+        self
+    }
+}
+
+/// Replace `$2` (in directory `$1`) with stdin the way [`atomic`](crate::atomic) does
+///
+/// The contents go to a private temp file next to the config, which takes
+/// over the config's mode and owner before being moved over it, so a failed
+/// write never leaves a truncated config behind. Also replaces configs on
+/// [`SshTarget`](crate::target::SshTarget)s.
+pub(crate) const REPLACE_SCRIPT: &str = r#"set -e
+mkdir -p -- "$1"
+tmp=$(mktemp "$2.kodegen-XXXXXX")
+trap 'rm -f -- "$tmp"' EXIT
+cat > "$tmp"
+if [ -e "$2" ]; then
+    chmod "$(stat -c %a -- "$2" 2>/dev/null || stat -f %Lp -- "$2")" -- "$tmp"
+    chown "$(stat -c %u:%g -- "$2" 2>/dev/null || stat -f %u:%g -- "$2")" -- "$tmp"
+else
+    chmod 644 -- "$tmp"
+fi
+mv -f -- "$tmp" "$2"
+"#;
+
+#[cfg(unix)]
+impl Elevation for Sudo {
+    fn write_elevated(&self, path: &Path, content: &[u8]) -> io::Result<()> {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        let path = crate::atomic::resolve_symlink(path)?;
+        let dir = path
+            .parent()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no directory"))?;
+
+        let mut command = Command::new("sudo");
+        if let Some(askpass) = &self.askpass {
+            command.arg("-A").env("SUDO_ASKPASS", askpass);
+        }
+        let mut child = command
+            .args(["--", "sh", "-c", REPLACE_SCRIPT, "sh"])
+            .arg(dir)
+            .arg(&path)
+            .stdin(Stdio::piped())
+            .spawn()?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(content)?;
+        }
+
+        let status = child.wait()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("sudo failed to write {} ({status})", path.display()),
+            ))
+        }
+    }
+}
+
+/// Suggest how to get a denied config written
+#[must_use]
+pub fn remediation(path: &Path) -> String {
+    // Writes replace the file, so it's the directory that has to be writable
+    let dir = path
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.is_dir())
+        .unwrap_or(path);
+
+    if cfg!(windows) {
+        format!(
+            "Run the install as administrator, or give your user write access to {}",
+            dir.display()
+        )
+    } else {
+        format!(
+            "Run the install with sudo, or give your user write access to {}",
+            dir.display()
+        )
+    }
+}
@@ -0,0 +1,144 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::ConfigFormat;
+use crate::config::ServerNameConflict;
+use crate::validate::ValidationIssue;
+
+/// Boxed underlying cause of a [`ConfigError`]
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Why configuring a client failed, for callers that branch on the cause
+///
+/// Returned by [`ConfigMerger::merge`](crate::ConfigMerger::merge),
+/// [`ClientConfigPlugin::inject_kodegen`](crate::ClientConfigPlugin::inject_kodegen)
+/// and the install APIs. Failures of individual clients during an install
+/// are reported in their [`InstallResult`](crate::InstallResult) instead.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    /// The existing config isn't valid for its format
+    #[error("Failed to parse {format:?} config")]
+    ParseFailed {
+        format: ConfigFormat,
+        #[source]
+        source: BoxError,
+    },
+    /// A config can't be written with the current permissions
+    #[error("Permission denied writing {}", path.display())]
+    WriteDenied {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// The format can't be handled on this platform (e.g. property lists off
+    /// macOS), or is a custom format with no codec registered
+    #[error("{0:?} configs are not supported on this platform")]
+    UnsupportedFormat(ConfigFormat),
+    /// The config couldn't be backed up, so it was left alone
+    #[error("Failed to back up {}", path.display())]
+    BackupFailed {
+        path: PathBuf,
+        #[source]
+        source: BoxError,
+    },
+    /// The merged config has issues its client would reject (see [`crate::validate`])
+    #[error("Merged config failed validation: {}", join_issues(issues))]
+    Invalid { issues: Vec<ValidationIssue> },
+    /// The merge would have dropped or changed settings of the existing config
+    /// (listed as JSON Pointers), so it wasn't written
+    #[error("Merging would have lost existing settings at {}", pointers.join(", "))]
+    SettingsLost { pointers: Vec<String> },
+    /// An unrelated server already uses kodegen's name
+    #[error(transparent)]
+    Conflict(#[from] ServerNameConflict),
+    /// Reading or writing a file failed
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// Any other failure (e.g. invalid settings)
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+impl ConfigError {
+    /// Classify a failure to handle a config of the given format
+    ///
+    /// Parser errors anywhere in the chain become [`Self::ParseFailed`].
+    #[must_use]
+    pub fn classify(error: anyhow::Error, format: ConfigFormat) -> Self {
+        if error.chain().any(is_parse_error) && error.downcast_ref::<Self>().is_none() {
+            return Self::ParseFailed {
+                format,
+                source: error.into(),
+            };
+        }
+
+        Self::from(error)
+    }
+
+    /// Wrap an error writing a config, telling permission problems apart
+    #[must_use]
+    pub fn write(path: &Path, error: io::Error) -> Self {
+        if error.kind() == io::ErrorKind::PermissionDenied {
+            Self::WriteDenied {
+                path: path.to_path_buf(),
+                source: error,
+            }
+        } else {
+            Self::Io(error)
+        }
+    }
+}
+
+impl From<anyhow::Error> for ConfigError {
+    fn from(error: anyhow::Error) -> Self {
+        let error = match error.downcast::<Self>() {
+            Ok(config_error) => return config_error,
+            Err(error) => error,
+        };
+        let error = match error.downcast::<ServerNameConflict>() {
+            Ok(conflict) => return Self::Conflict(conflict),
+            Err(error) => error,
+        };
+        match error.downcast::<io::Error>() {
+            Ok(io_error) => Self::Io(io_error),
+            Err(error) => Self::Other(error),
+        }
+    }
+}
+
+fn join_issues(issues: &[ValidationIssue]) -> String {
+    issues
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Find the name conflict behind an error, whether or not it went through a [`ConfigError`]
+pub(crate) fn server_name_conflict(error: &anyhow::Error) -> Option<&ServerNameConflict> {
+    match error.downcast_ref::<ConfigError>() {
+        Some(ConfigError::Conflict(conflict)) => Some(conflict),
+        _ => error.downcast_ref::<ServerNameConflict>(),
+    }
+}
+
+/// Check whether an error comes from one of the config parsers
+pub(crate) fn is_parse_error(cause: &(dyn std::error::Error + 'static)) -> bool {
+    cause.is::<serde_json::Error>()
+        || cause.is::<toml::de::Error>()
+        || cause.is::<toml_edit::TomlError>()
+        || cause.is::<serde_yaml::Error>()
+        || cause.is::<crate::ini::IniError>()
+        || cause.is::<crate::xml::XmlError>()
+        || is_plist_error(cause)
+}
+
+#[cfg(target_os = "macos")]
+fn is_plist_error(cause: &(dyn std::error::Error + 'static)) -> bool {
+    cause.is::<plist::Error>()
+}
+
+#[cfg(not(target_os = "macos"))]
+fn is_plist_error(_cause: &(dyn std::error::Error + 'static)) -> bool {
+    false
+}
@@ -0,0 +1,184 @@
+//! Diagnostics captured when a client config can't be updated
+//!
+//! A bare "Failed to configure" isn't enough to act on in a bug report, so
+//! failed installs carry the full error chain and, for parse errors, the
+//! position and a few surrounding lines of the config. String values in the
+//! snippet are redacted since configs commonly hold API keys.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Lines of context shown on each side of the offending line
+const CONTEXT_LINES: usize = 2;
+
+/// Snippet lines are cut off after this many characters
+const MAX_LINE_CHARS: usize = 120;
+
+/// Replacement for redacted values
+const REDACTED: &str = "***";
+
+/// Why updating a config file failed, in enough detail to diagnose it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailureDetail {
+    /// Error messages from the outermost context down to the root cause
+    pub error_chain: Vec<String>,
+    /// 1-based line of the offending input, when the parser reports one
+    pub line: Option<usize>,
+    /// 1-based column of the offending input, when the parser reports one
+    pub column: Option<usize>,
+    /// Numbered lines around the error with values redacted
+    pub snippet: Option<String>,
+}
+
+impl FailureDetail {
+    /// Capture the details of a failure to update `config_path`
+    #[must_use]
+    pub fn capture(error: &anyhow::Error, config_path: &Path) -> Self {
+        let content = std::fs::read_to_string(config_path).ok();
+        let position = error
+            .chain()
+            .find_map(|cause| error_position(cause, content.as_deref()));
+
+        Self {
+            error_chain: error.chain().map(ToString::to_string).collect(),
+            line: position.map(|(line, _)| line),
+            column: position.map(|(_, column)| column),
+            snippet: position
+                .zip(content.as_deref())
+                .map(|((line, _), content)| snippet(content, line)),
+        }
+    }
+}
+
+/// Get the 1-based line and column a parse error points at
+fn error_position(
+    cause: &(dyn std::error::Error + 'static),
+    content: Option<&str>,
+) -> Option<(usize, usize)> {
+    if let Some(e) = cause.downcast_ref::<serde_json::Error>() {
+        // Line 0 means the error isn't tied to a position (e.g. I/O)
+        return (e.line() > 0).then(|| (e.line(), e.column()));
+    }
+
+    if let Some(e) = cause.downcast_ref::<serde_yaml::Error>() {
+        return e
+            .location()
+            .map(|location| (location.line(), location.column()));
+    }
+
+    if let Some(e) = cause.downcast_ref::<toml::de::Error>() {
+        let span = e.span()?;
+        return content.map(|content| line_column(content, span.start));
+    }
+
+    if let Some(e) = cause.downcast_ref::<toml_edit::TomlError>() {
+        let span = e.span()?;
+        return content.map(|content| line_column(content, span.start));
+    }
+
+    None
+}
+
+/// Convert a byte offset into a 1-based line and column
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = content.get(..offset.min(content.len())).unwrap_or(content);
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+/// Render the lines around `line`, marking the offending one
+fn snippet(content: &str, line: usize) -> String {
+    let first = line.saturating_sub(CONTEXT_LINES).max(1);
+
+    content
+        .lines()
+        .enumerate()
+        .map(|(index, text)| (index + 1, text))
+        .skip(first - 1)
+        .take(line + CONTEXT_LINES + 1 - first)
+        .map(|(number, text)| {
+            let marker = if number == line { '>' } else { ' ' };
+            let text: String = redact_line(text).chars().take(MAX_LINE_CHARS).collect();
+            format!("{marker} {number:>4} | {text}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Redact the values on a config line, keeping keys and structure
+///
+/// Quoted strings are blanked unless they are keys (followed by `:` or `=`),
+/// and bare YAML scalars after `key:` are blanked unless they are numbers,
+/// booleans or null.
+fn redact_line(line: &str) -> String {
+    let mut redacted = String::with_capacity(line.len());
+    let mut chars = line.char_indices();
+
+    while let Some((start, c)) = chars.next() {
+        if c != '"' && c != '\'' {
+            redacted.push(c);
+            continue;
+        }
+
+        let mut escaped = false;
+        let end = chars.by_ref().find_map(|(index, next)| {
+            if escaped {
+                escaped = false;
+            } else if next == '\\' {
+                escaped = true;
+            } else if next == c {
+                return Some(index);
+            }
+            None
+        });
+
+        let Some(end) = end else {
+            // Unterminated string: hide everything after the quote
+            redacted.push(c);
+            redacted.push_str(REDACTED);
+            break;
+        };
+
+        let is_key = line[end + 1..].trim_start().starts_with([':', '=']);
+        if is_key {
+            redacted.push_str(&line[start..=end]);
+        } else {
+            redacted.push(c);
+            redacted.push_str(REDACTED);
+            redacted.push(c);
+        }
+    }
+
+    redact_bare_value(&redacted)
+}
+
+/// Blank an unquoted YAML scalar value (`key: value` or `- value`)
+fn redact_bare_value(line: &str) -> String {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('#') {
+        return line.to_string();
+    }
+
+    let value_start = if let Some(index) = line.find(": ") {
+        index + 2
+    } else if trimmed.starts_with("- ") {
+        line.len() - trimmed.len() + 2
+    } else {
+        return line.to_string();
+    };
+
+    let value = line[value_start..].trim().trim_end_matches(',');
+    let is_safe = value.is_empty()
+        || value.starts_with(['"', '\'', '{', '[', '&', '*', '|', '>', '#'])
+        || value.parse::<f64>().is_ok()
+        || matches!(value, "true" | "false" | "null" | "~");
+
+    if is_safe {
+        line.to_string()
+    } else {
+        format!("{}{REDACTED}", &line[..value_start])
+    }
+}
@@ -0,0 +1,88 @@
+//! Entry points for fuzzing the config mergers
+//!
+//! Each function takes arbitrary bytes, is deterministic and returns normally
+//! for every input; parse failures are expected and ignored. A successful
+//! merge is checked to be idempotent, so a panic means a real bug was found.
+//! The cargo-fuzz targets in `fuzz/` drive these with a seed corpus.
+
+use crate::config::{ConfigMerger, ConflictResolution, MAX_CONFIG_LEN};
+use crate::{ConfigFormat, KodegenConfig};
+
+/// Fuzz merging into a JSON config
+pub fn fuzz_merge_json(data: &[u8]) {
+    fuzz_merge(data, ConfigFormat::Json);
+}
+
+/// Fuzz merging into a TOML config
+pub fn fuzz_merge_toml(data: &[u8]) {
+    fuzz_merge(data, ConfigFormat::Toml);
+}
+
+/// Fuzz merging into a YAML config
+pub fn fuzz_merge_yaml(data: &[u8]) {
+    fuzz_merge(data, ConfigFormat::Yaml);
+}
+
+/// Fuzz merging into a property list (only parsed on macOS)
+pub fn fuzz_merge_plist(data: &[u8]) {
+    fuzz_merge(data, ConfigFormat::Plist);
+}
+
+/// Fuzz merging into an INI config
+pub fn fuzz_merge_ini(data: &[u8]) {
+    fuzz_merge(data, ConfigFormat::Ini);
+}
+
+/// Fuzz merging into an XML config
+pub fn fuzz_merge_xml(data: &[u8]) {
+    fuzz_merge(data, ConfigFormat::Xml);
+}
+
+/// Merge `data` with every merger configuration and check the results
+fn fuzz_merge(data: &[u8], format: ConfigFormat) {
+    let Ok(existing) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    for merger in mergers() {
+        let Ok(merged) = merger.merge(existing, format) else {
+            continue;
+        };
+
+        // Pretty-printing can push an input just under the limit over it
+        if merged.len() > MAX_CONFIG_LEN {
+            continue;
+        }
+
+        let remerged = merger
+            .merge(&merged, format)
+            .expect("merged config must parse again");
+        assert_eq!(remerged, merged, "merge must be idempotent");
+    }
+}
+
+/// Mergers covering each conflict resolution, update mode and a custom server
+fn mergers() -> Vec<ConfigMerger> {
+    let custom = KodegenConfig {
+        command: "/opt/kodegen/bin/kodegen".to_string(),
+        args: vec!["--stdio".to_string(), "--tools=all".to_string()],
+        env: Some(serde_json::json!({ "RUST_LOG": "info" })),
+    };
+
+    let mut mergers = Vec::new();
+    for conflict_resolution in [
+        ConflictResolution::Skip,
+        ConflictResolution::Suffix,
+        ConflictResolution::Replace,
+    ] {
+        mergers.push(ConfigMerger::new().with_conflict_resolution(conflict_resolution));
+        mergers.push(
+            ConfigMerger::new()
+                .with_config(custom.clone())
+                .with_conflict_resolution(conflict_resolution)
+                .with_update_existing(true),
+        );
+    }
+
+    mergers
+}
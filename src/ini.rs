@@ -0,0 +1,361 @@
+//! Line-based reading and editing of INI configs
+//!
+//! Servers live in `[<key>.<name>]` sections of `field = value` lines, e.g.
+//! `[mcpServers.kodegen]`. Values that are JSON arrays, objects or quoted
+//! strings (`args = ["--stdio"]`) are read as JSON; anything else is a plain
+//! string. Dotted section names nest, so `[mcpServers.kodegen.env]` holds the
+//! server's `env`.
+//!
+//! Edits are made as text: a server's sections are replaced or removed, and
+//! new ones go at the end of the file, so comments and the rest of the file
+//! stay byte-for-byte intact. Only whole-line `;` and `#` comments exist;
+//! values may contain either character.
+
+use std::ops::Range;
+
+use serde_json::{Map, Value as JsonValue};
+
+/// Why an INI config couldn't be read
+#[derive(Debug, thiserror::Error)]
+#[error("line {line}: {message}")]
+pub(crate) struct IniError {
+    line: usize,
+    message: String,
+}
+
+/// What a line of the document holds
+enum Kind {
+    /// Nothing but whitespace
+    Blank,
+    /// A `;` or `#` comment
+    Comment,
+    /// A `[section]` header, with its name
+    Header(String),
+    /// A `key = value` pair
+    Pair,
+}
+
+/// A line of the document
+struct Line {
+    /// Byte offset of the line
+    start: usize,
+    /// Byte offset after its line break
+    end: usize,
+    kind: Kind,
+}
+
+/// An INI document split into lines
+struct Document<'a> {
+    text: &'a str,
+    lines: Vec<Line>,
+}
+
+impl<'a> Document<'a> {
+    fn new(text: &'a str) -> Result<Self, IniError> {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for (number, raw) in text.split_inclusive('\n').enumerate() {
+            let body = raw.trim();
+            let kind = if body.is_empty() {
+                Kind::Blank
+            } else if body.starts_with([';', '#']) {
+                Kind::Comment
+            } else if let Some(header) = body.strip_prefix('[') {
+                let name = header.strip_suffix(']').ok_or_else(|| IniError {
+                    line: number + 1,
+                    message: "section header is missing its `]`".to_string(),
+                })?;
+                Kind::Header(name.trim().to_string())
+            } else if body
+                .split_once('=')
+                .is_some_and(|(key, _)| !key.trim().is_empty())
+            {
+                Kind::Pair
+            } else {
+                return Err(IniError {
+                    line: number + 1,
+                    message: format!("expected `key = value`, found `{body}`"),
+                });
+            };
+
+            lines.push(Line {
+                start,
+                end: start + raw.len(),
+                kind,
+            });
+            start += raw.len();
+        }
+
+        Ok(Self { text, lines })
+    }
+
+    /// Text of a line without surrounding whitespace
+    fn line(&self, index: usize) -> &'a str {
+        let line = &self.lines[index];
+        self.text[line.start..line.end].trim()
+    }
+
+    fn is_blank(&self, index: usize) -> bool {
+        self.lines
+            .get(index)
+            .is_some_and(|line| matches!(line.kind, Kind::Blank))
+    }
+
+    /// Lines of the sections named `section` or nested under it, from their
+    /// header to their last pair (later comments belong to what follows)
+    fn sections(&self, section: &str) -> Vec<Range<usize>> {
+        let nested = format!("{section}.");
+        let mut sections: Vec<Range<usize>> = Vec::new();
+        let mut current: Option<Range<usize>> = None;
+
+        for (index, line) in self.lines.iter().enumerate() {
+            match &line.kind {
+                Kind::Header(name) => {
+                    sections.extend(current.take());
+                    if name == section || name.starts_with(&nested) {
+                        current = Some(index..index + 1);
+                    }
+                }
+                Kind::Pair => {
+                    if let Some(current) = &mut current {
+                        current.end = index + 1;
+                    }
+                }
+                Kind::Blank | Kind::Comment => {}
+            }
+        }
+        sections.extend(current);
+        sections
+    }
+
+    /// Byte range of `lines`, widened to take one blank line with it when
+    /// that leaves a single one between what's around it
+    fn removal(&self, lines: &Range<usize>) -> Range<usize> {
+        let before = lines.start.checked_sub(1);
+        let after_blank = self.is_blank(lines.end);
+        let at_end = lines.end == self.lines.len();
+
+        let mut start = self.lines[lines.start].start;
+        let mut end = self.lines[lines.end - 1].end;
+        match before {
+            Some(before) if self.is_blank(before) && (after_blank || at_end) => {
+                start = self.lines[before].start;
+            }
+            None if after_blank => end = self.lines[lines.end].end,
+            _ => {}
+        }
+        start..end
+    }
+}
+
+/// Read an INI config as JSON: sections become (nested) objects
+pub(crate) fn parse(text: &str) -> Result<JsonValue, IniError> {
+    let document = Document::new(text)?;
+    let mut root = Map::new();
+    let mut path: Vec<String> = Vec::new();
+
+    for (index, line) in document.lines.iter().enumerate() {
+        let error = |message: String| IniError {
+            line: index + 1,
+            message,
+        };
+        match &line.kind {
+            Kind::Blank | Kind::Comment => {}
+            Kind::Header(name) => {
+                path = name
+                    .split('.')
+                    .map(|part| part.trim().to_string())
+                    .collect();
+                if path.iter().any(String::is_empty) {
+                    return Err(error(format!("invalid section name `{name}`")));
+                }
+                section_mut(&mut root, &path)
+                    .ok_or_else(|| error(format!("`{name}` is both a value and a section")))?;
+            }
+            Kind::Pair => {
+                let (key, value) = document.line(index).split_once('=').unwrap_or_default();
+                let section = section_mut(&mut root, &path)
+                    .ok_or_else(|| error(format!("`{}` is not a section", path.join("."))))?;
+                section.insert(key.trim().to_string(), decode(value.trim()));
+            }
+        }
+    }
+
+    Ok(JsonValue::Object(root))
+}
+
+/// Get the object at `path`, creating missing ones, or `None` if a value is in the way
+fn section_mut<'a>(
+    root: &'a mut Map<String, JsonValue>,
+    path: &[String],
+) -> Option<&'a mut Map<String, JsonValue>> {
+    path.iter().try_fold(root, |section, part| {
+        section
+            .entry(part.clone())
+            .or_insert_with(|| JsonValue::Object(Map::new()))
+            .as_object_mut()
+    })
+}
+
+/// Read a value: JSON if it looks like JSON and parses, otherwise the text itself
+fn decode(value: &str) -> JsonValue {
+    if value.starts_with(['"', '[', '{'])
+        && let Ok(json) = serde_json::from_str(value)
+    {
+        return json;
+    }
+    JsonValue::String(value.to_string())
+}
+
+/// Write a value so that [`decode`] reads it back
+fn encode(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(text)
+            if !text.is_empty()
+                && text.trim() == text
+                && !text.starts_with(['"', '[', '{'])
+                && !text.contains(['\n', '\r']) =>
+        {
+            text.clone()
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// Render an entry as a `[section]` of its fields
+fn render(section: &str, entry: &Map<String, JsonValue>) -> String {
+    let mut rendered = format!("[{section}]\n");
+    for (field, value) in entry {
+        rendered.push_str(&format!("{field} = {}\n", encode(value)));
+    }
+    rendered
+}
+
+/// Write a whole config: top-level values first, then a section per object,
+/// nested objects in sections of their own
+///
+/// `None` if the config isn't an object.
+pub(crate) fn render_document(config: &JsonValue) -> Option<String> {
+    fn sections(out: &mut String, section: &str, fields: &Map<String, JsonValue>) {
+        let (nested, values): (Map<_, _>, Map<_, _>) = fields
+            .clone()
+            .into_iter()
+            .partition(|(_, value)| value.is_object());
+        if !values.is_empty() || nested.is_empty() {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&render(section, &values));
+        }
+        for (field, value) in &nested {
+            if let JsonValue::Object(fields) = value {
+                sections(out, &format!("{section}.{field}"), fields);
+            }
+        }
+    }
+
+    let fields = config.as_object()?;
+    let mut out = String::new();
+    for (field, value) in fields.iter().filter(|(_, value)| !value.is_object()) {
+        out.push_str(&format!("{field} = {}\n", encode(value)));
+    }
+    for (field, value) in fields {
+        if let JsonValue::Object(fields) = value {
+            sections(&mut out, field, fields);
+        }
+    }
+    Some(out)
+}
+
+/// Get an entry the way it reads back once written
+///
+/// Scalars other than strings come back as strings, so existing entries are
+/// compared against this rather than the entry itself.
+pub(crate) fn written_form(entry: &JsonValue) -> JsonValue {
+    match entry {
+        JsonValue::Object(fields) => fields
+            .iter()
+            .map(|(field, value)| (field.clone(), decode(&encode(value))))
+            .collect::<Map<_, _>>()
+            .into(),
+        _ => entry.clone(),
+    }
+}
+
+/// Set server `name` under `key` to `entry`, replacing its sections where they
+/// are or adding a section at the end
+///
+/// `None` if the document can't be read or `entry` isn't an object.
+pub(crate) fn upsert_section(
+    text: &str,
+    key: &str,
+    name: &str,
+    entry: &JsonValue,
+) -> Option<String> {
+    let section = format!("{key}.{name}");
+    let rendered = render(&section, entry.as_object()?);
+
+    let document = Document::new(text).ok()?;
+    let sections = document.sections(&section);
+    let Some((first, rest)) = sections.split_first() else {
+        let mut out = text.to_string();
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        if !out.is_empty() && !out.ends_with("\n\n") && !out.ends_with("\n\r\n") {
+            out.push('\n');
+        }
+        out.push_str(&rendered);
+        return Some(out);
+    };
+
+    let mut edits = vec![(
+        document.lines[first.start].start..document.lines[first.end - 1].end,
+        rendered,
+    )];
+    edits.extend(
+        rest.iter()
+            .map(|lines| (document.removal(lines), String::new())),
+    );
+    Some(apply(text, edits))
+}
+
+/// Remove the sections of servers `names` under `key`, and a `[key]` header
+/// too if that leaves nothing under it
+pub(crate) fn remove_sections(text: &str, key: &str, names: &[String]) -> Option<String> {
+    let document = Document::new(text).ok()?;
+    let edits = names
+        .iter()
+        .flat_map(|name| document.sections(&format!("{key}.{name}")))
+        .map(|lines| (document.removal(&lines), String::new()))
+        .collect();
+    let removed = apply(text, edits);
+
+    let document = Document::new(&removed).ok()?;
+    let left = document.sections(key);
+    if left
+        .iter()
+        .any(|lines| lines.len() > 1 || document.line(lines.start) != format!("[{key}]"))
+    {
+        return Some(removed);
+    }
+    let edits = left
+        .iter()
+        .map(|lines| (document.removal(lines), String::new()))
+        .collect();
+    Some(apply(&removed, edits))
+}
+
+/// Apply text replacements; removals may overlap where they share a blank line
+fn apply(text: &str, mut edits: Vec<(Range<usize>, String)>) -> String {
+    edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+
+    let mut out = text.to_string();
+    let mut applied = text.len();
+    for (range, replacement) in edits {
+        let end = range.end.min(applied);
+        out.replace_range(range.start..end, &replacement);
+        applied = range.start;
+    }
+    out
+}
@@ -1,18 +1,547 @@
-use anyhow::{Context, Result};
-use log::{debug, error, info};
+use anyhow::{Context, Result, anyhow, bail};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 
-use crate::ClientConfigPlugin;
+use crate::backup;
+use crate::binary::{self, BinaryCheck, BinaryError};
+use crate::config::{ConfigMerger, ServerNameConflict, TransportPreference};
+use crate::elevation::{self, Elevation};
+use crate::error::{self, ConfigError};
+use crate::failure::FailureDetail;
+use crate::journal::{EntryState, Journal};
+use crate::lock;
+use crate::observer::{InstallEvent, InstallObserver};
+use crate::settings::Settings;
+use crate::state::StateDirs;
+use crate::store::ConfigStore;
+use crate::target::{InstallTarget, SshTarget};
+use crate::template::TemplateVars;
+use crate::{ClientConfigPlugin, ConfigFormat, Platform, notify, paths, validate, wsl};
+
+/// Stable, machine-parsable outcome code
+///
+/// The string form (e.g. `S_ALREADY_CONFIGURED`) never changes between
+/// releases, so wrappers can branch on it instead of on the human message.
+/// `S_` codes are successes, `W_` codes are warnings or skips and `E_` codes
+/// are failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "&'static str", try_from = "String")]
+pub enum MessageCode {
+    /// The config file didn't exist and was created
+    Created,
+    /// kodegen was added to an existing config file
+    Configured,
+    /// An outdated kodegen entry was rewritten (see [`InstallMode::Upgrade`])
+    Updated,
+    /// The config file already had an up-to-date kodegen entry
+    AlreadyConfigured,
+    /// kodegen was removed from the config file
+    Removed,
+    /// The config file had no kodegen entry to remove
+    NotConfigured,
+    /// The user removed kodegen from this config, so it was left alone
+    SkippedRemovedByUser,
+    /// The client isn't installed
+    NotInstalled,
+    /// The client is installed but has no MCP config to write (e.g. Aider)
+    Unsupported,
+    /// The configured kodegen command couldn't be found
+    BinaryMissing,
+    /// The configured kodegen command exists but can't be executed
+    BinaryNotExecutable,
+    /// Another server already uses kodegen's name
+    Conflict,
+    /// The existing config isn't valid JSON
+    ParseJson,
+    /// The existing config isn't valid TOML
+    ParseToml,
+    /// The existing config isn't valid YAML
+    ParseYaml,
+    /// The existing config isn't a valid property list
+    ParsePlist,
+    /// The existing config isn't valid INI
+    ParseIni,
+    /// The existing config isn't valid XML
+    ParseXml,
+    /// The merged config would be rejected by the client, so it wasn't written
+    InvalidConfig,
+    /// The merge would have lost existing settings, so it wasn't written
+    SettingsLost,
+    /// The config (or its directory) can't be written by the current user
+    PermissionDenied,
+    /// Reading or writing a file failed
+    Io,
+    /// Any other failure
+    Failed,
+}
+
+impl MessageCode {
+    /// Get the stable string form of the code
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Created => "S_CREATED",
+            Self::Configured => "S_CONFIGURED",
+            Self::Updated => "S_UPDATED",
+            Self::AlreadyConfigured => "S_ALREADY_CONFIGURED",
+            Self::Removed => "S_REMOVED",
+            Self::NotConfigured => "S_NOT_CONFIGURED",
+            Self::SkippedRemovedByUser => "S_SKIPPED_REMOVED_BY_USER",
+            Self::NotInstalled => "W_NOT_INSTALLED",
+            Self::Unsupported => "W_UNSUPPORTED",
+            Self::BinaryMissing => "W_BINARY_MISSING",
+            Self::BinaryNotExecutable => "W_BINARY_NOT_EXECUTABLE",
+            Self::Conflict => "E_CONFLICT",
+            Self::ParseJson => "E_PARSE_JSON",
+            Self::ParseToml => "E_PARSE_TOML",
+            Self::ParseYaml => "E_PARSE_YAML",
+            Self::ParsePlist => "E_PARSE_PLIST",
+            Self::ParseIni => "E_PARSE_INI",
+            Self::ParseXml => "E_PARSE_XML",
+            Self::InvalidConfig => "E_INVALID_CONFIG",
+            Self::SettingsLost => "E_SETTINGS_LOST",
+            Self::PermissionDenied => "E_PERMISSION_DENIED",
+            Self::Io => "E_IO",
+            Self::Failed => "E_FAILED",
+        }
+    }
+
+    /// Every code, in declaration order
+    const ALL: [Self; 23] = [
+        Self::Created,
+        Self::Configured,
+        Self::Updated,
+        Self::AlreadyConfigured,
+        Self::Removed,
+        Self::NotConfigured,
+        Self::SkippedRemovedByUser,
+        Self::NotInstalled,
+        Self::Unsupported,
+        Self::BinaryMissing,
+        Self::BinaryNotExecutable,
+        Self::Conflict,
+        Self::ParseJson,
+        Self::ParseToml,
+        Self::ParseYaml,
+        Self::ParsePlist,
+        Self::ParseIni,
+        Self::ParseXml,
+        Self::InvalidConfig,
+        Self::SettingsLost,
+        Self::PermissionDenied,
+        Self::Io,
+        Self::Failed,
+    ];
+
+    /// Whether the code describes a successful install
+    #[must_use]
+    pub fn is_success(self) -> bool {
+        self.as_str().starts_with("S_")
+    }
+
+    /// Whether the code means the config file was written
+    #[must_use]
+    pub fn is_change(self) -> bool {
+        matches!(self, Self::Created | Self::Configured | Self::Updated)
+    }
+
+    /// Get the broad action a wrapper would show for the code
+    #[must_use]
+    pub fn action(self) -> InstallAction {
+        match self {
+            Self::Created => InstallAction::Created,
+            Self::Configured | Self::Updated | Self::Removed => InstallAction::Updated,
+            Self::AlreadyConfigured
+            | Self::NotConfigured
+            | Self::SkippedRemovedByUser
+            | Self::NotInstalled
+            | Self::Unsupported
+            | Self::BinaryMissing
+            | Self::BinaryNotExecutable => InstallAction::Skipped,
+            Self::Conflict
+            | Self::ParseJson
+            | Self::ParseToml
+            | Self::ParseYaml
+            | Self::ParsePlist
+            | Self::ParseIni
+            | Self::ParseXml
+            | Self::InvalidConfig
+            | Self::SettingsLost
+            | Self::PermissionDenied
+            | Self::Io
+            | Self::Failed => InstallAction::Failed,
+        }
+    }
+
+    /// Get the default human-readable message for the code
+    #[must_use]
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::Created => "Created new config",
+            Self::Configured => "Configured successfully",
+            Self::Updated => "Updated kodegen entry",
+            Self::AlreadyConfigured => "Already configured",
+            Self::Removed => "Removed kodegen",
+            Self::NotConfigured => "kodegen not configured",
+            Self::SkippedRemovedByUser => "Skipped: removed by user",
+            Self::NotInstalled => "Not installed",
+            Self::Unsupported => "Detected, but has no MCP config to write",
+            Self::BinaryMissing => "kodegen binary not found",
+            Self::BinaryNotExecutable => "kodegen binary is not executable",
+            Self::Conflict => "Conflict with an existing server",
+            Self::ParseJson
+            | Self::ParseToml
+            | Self::ParseYaml
+            | Self::ParsePlist
+            | Self::ParseIni
+            | Self::ParseXml => "Existing config could not be parsed",
+            Self::InvalidConfig => "Merged config failed validation",
+            Self::SettingsLost => "Merge would have lost existing settings",
+            Self::PermissionDenied => "Permission denied",
+            Self::Io | Self::Failed => "Failed to configure",
+        }
+    }
+
+    /// Classify a failed config update by the errors in its chain
+    fn from_error(error: &anyhow::Error, format: ConfigFormat) -> Self {
+        if error::server_name_conflict(error).is_some() {
+            return Self::Conflict;
+        }
+
+        let format = match error.downcast_ref::<ConfigError>() {
+            Some(ConfigError::ParseFailed { format, .. }) => *format,
+            _ => format,
+        };
+
+        for cause in error.chain() {
+            match cause.downcast_ref::<ConfigError>() {
+                Some(ConfigError::Invalid { .. }) => return Self::InvalidConfig,
+                Some(ConfigError::SettingsLost { .. }) => return Self::SettingsLost,
+                _ => {}
+            }
+
+            if error::is_parse_error(cause) {
+                return match format {
+                    ConfigFormat::Json | ConfigFormat::Sqlite => Self::ParseJson,
+                    ConfigFormat::Toml => Self::ParseToml,
+                    ConfigFormat::Yaml => Self::ParseYaml,
+                    ConfigFormat::Plist => Self::ParsePlist,
+                    ConfigFormat::Ini => Self::ParseIni,
+                    ConfigFormat::Xml => Self::ParseXml,
+                    ConfigFormat::Custom(_) => Self::Failed,
+                };
+            }
+
+            if let Some(io_error) = cause.downcast_ref::<std::io::Error>() {
+                return if io_error.kind() == ErrorKind::PermissionDenied {
+                    Self::PermissionDenied
+                } else {
+                    Self::Io
+                };
+            }
+        }
+
+        Self::Failed
+    }
+}
+
+impl fmt::Display for MessageCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<MessageCode> for &'static str {
+    fn from(code: MessageCode) -> Self {
+        code.as_str()
+    }
+}
+
+impl TryFrom<String> for MessageCode {
+    type Error = String;
+
+    fn try_from(code: String) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|known| known.as_str() == code)
+            .ok_or_else(|| format!("unknown message code `{code}`"))
+    }
+}
+
+/// What an install did to a client, in the terms an installer UI shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InstallAction {
+    /// A new config file was written
+    Created,
+    /// An existing config file was changed
+    Updated,
+    /// Nothing needed to (or could) be done
+    Skipped,
+    /// The config couldn't be updated
+    Failed,
+}
+
+/// A non-fatal problem noticed while installing for a client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallWarning {
+    pub code: MessageCode,
+    pub message: String,
+    pub params: BTreeMap<String, String>,
+}
 
 /// Result of installing kodegen for a single client
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InstallResult {
     pub client_name: String,
     pub client_id: String,
     pub success: bool,
     pub message: String,
+    /// Stable outcome code, for wrappers that map results to their own UX
+    pub code: MessageCode,
+    /// Broad action taken, derived from `code`
+    pub action: InstallAction,
+    /// Structured values referenced by the message (e.g. `config_path`, `error`)
+    pub params: BTreeMap<String, String>,
+    pub warnings: Vec<InstallWarning>,
+    /// Diagnostics for a failed config update
+    pub failure: Option<FailureDetail>,
+    /// Unified diff of the changes a dry run would have made
+    pub diff: Option<String>,
     pub config_path: Option<PathBuf>,
+    /// Transport the client was configured with
+    #[serde(default)]
+    pub transport: TransportPreference,
+    /// Backup taken of the config before it was changed
+    pub backup_path: Option<PathBuf>,
+    /// Home directory of the user this result applies to, for machine-wide installs
+    pub user_home: Option<PathBuf>,
+    /// Time spent detecting and configuring the client
+    #[serde(rename = "duration_ms", with = "duration_ms")]
+    pub duration: Duration,
+}
+
+impl InstallResult {
+    /// Whether this install wrote the client's config file
+    #[must_use]
+    pub fn config_changed(&self) -> bool {
+        self.code.is_change()
+    }
+
+    fn new(
+        client: &dyn ClientConfigPlugin,
+        code: MessageCode,
+        message: impl Into<String>,
+        config_path: Option<PathBuf>,
+    ) -> Self {
+        let mut params = BTreeMap::new();
+        if let Some(path) = &config_path {
+            params.insert("config_path".to_string(), path.display().to_string());
+        }
+
+        Self {
+            client_name: client.client_name().to_string(),
+            client_id: client.client_id().to_string(),
+            success: code.is_success(),
+            message: message.into(),
+            code,
+            action: code.action(),
+            params,
+            warnings: Vec::new(),
+            failure: None,
+            diff: None,
+            config_path,
+            transport: TransportPreference::default(),
+            backup_path: None,
+            user_home: paths::user_home_override(),
+            duration: Duration::ZERO,
+        }
+    }
+
+    fn with_param(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.params.insert(key.to_string(), value.into());
+        self
+    }
+}
+
+/// Results of a whole install, for wrapping installers and GUIs to render
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InstallReport {
+    pub results: Vec<InstallResult>,
+}
+
+impl InstallReport {
+    /// Serialize the report as pretty-printed JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a result cannot be serialized (e.g. a non-UTF-8 path).
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl From<Vec<InstallResult>> for InstallReport {
+    fn from(results: Vec<InstallResult>) -> Self {
+        Self { results }
+    }
+}
+
+/// Serialize a [`Duration`] as whole milliseconds
+mod duration_ms {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+/// Result of removing kodegen from a single client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UninstallResult {
+    pub client_name: String,
+    pub client_id: String,
+    pub success: bool,
+    pub message: String,
+    pub code: MessageCode,
+    /// Config files kodegen was removed from
+    pub config_paths: Vec<PathBuf>,
+}
+
+/// What an install does with kodegen entries that are already present
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InstallMode {
+    /// Add kodegen where it's missing and leave existing entries as they are
+    #[default]
+    Install,
+    /// Also rewrite existing entries whose command, args or env differ from
+    /// the current server definition (e.g. after kodegen moved)
+    Upgrade,
+}
+
+/// How an install treats the configs it finds
+///
+/// Passed to [`install_all_clients_with`]; the other install functions are
+/// shorthands for common options.
+#[derive(Clone, Default)]
+pub struct InstallOptions<'a> {
+    /// What to do with existing kodegen entries
+    mode: InstallMode,
+    /// Receiver of live progress events
+    observer: Option<&'a dyn InstallObserver>,
+    /// Leave configs alone that the user removed kodegen from
+    respect_removals: bool,
+    /// Compute the changes without writing anything
+    dry_run: bool,
+    /// Transport overriding the one from the settings
+    transport: Option<TransportPreference>,
+    /// Project directory the configs belong to, for `${WORKSPACE}`
+    workspace: Option<&'a Path>,
+    /// Ids of the only clients to configure, if any are given
+    include: Vec<String>,
+    /// Ids of clients to leave alone
+    exclude: Vec<String>,
+    /// Configs belong to Windows-side clients starting kodegen through WSL
+    windows_host: bool,
+    /// Writer for configs the current user isn't allowed to write
+    elevation: Option<&'a dyn Elevation>,
+    /// Settings profile applied instead of the default one
+    profile: Option<String>,
+}
+
+impl<'a> InstallOptions<'a> {
+    /// Set what to do with existing kodegen entries
+    #[must_use]
+    pub fn with_mode(mut self, mode: InstallMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Report progress to `observer` as the install goes
+    #[must_use]
+    pub fn with_observer(mut self, observer: &'a dyn InstallObserver) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Write configs the current user can't write through `elevation`
+    ///
+    /// Without it, those configs are reported as [`MessageCode::PermissionDenied`].
+    #[must_use]
+    pub fn with_elevation(mut self, elevation: &'a dyn Elevation) -> Self {
+        self.elevation = Some(elevation);
+        self
+    }
+
+    /// Only compute the changes (see [`install_all_clients_dry_run`])
+    #[must_use]
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Configure clients that support `transport` with it instead of the settings' transport
+    ///
+    /// Other clients are configured with stdio.
+    #[must_use]
+    pub fn with_transport(mut self, transport: TransportPreference) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Apply the named settings profile (see [`Settings::with_profile`]) instead of the default one
+    #[must_use]
+    pub fn with_profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    /// Only configure the clients with these ids (e.g. `["cursor", "zed"]`)
+    #[must_use]
+    pub fn with_include(mut self, client_ids: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.include = client_ids.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Leave the clients with these ids alone (e.g. `["claude-desktop"]`)
+    #[must_use]
+    pub fn with_exclude(mut self, client_ids: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.exclude = client_ids.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Check whether the include and exclude lists let a client be configured
+    #[must_use]
+    pub fn selects(&self, client_id: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|id| id == client_id))
+            && !self.exclude.iter().any(|id| id == client_id)
+    }
+
+    /// Load the settings with the selected profile applied
+    fn load_settings(&self) -> anyhow::Result<Settings> {
+        Settings::load_with_profile(self.profile.as_deref())
+    }
+
+    /// Report an event to the observer, if there is one
+    fn emit(&self, event: impl FnOnce() -> InstallEvent) {
+        if let Some(observer) = self.observer {
+            observer.on_event(&event());
+        }
+    }
 }
 
 /// Install kodegen for all detected clients
@@ -20,118 +549,1184 @@ pub struct InstallResult {
 /// # Errors
 ///
 /// Returns an error if there are issues scanning for clients or processing configurations.
-pub fn install_all_clients() -> Result<Vec<InstallResult>> {
-    let clients = crate::clients::all_clients();
+pub fn install_all_clients() -> Result<Vec<InstallResult>, ConfigError> {
+    install_all_clients_with(InstallOptions::default())
+}
+
+/// Install kodegen for all detected clients in the given mode
+///
+/// With [`InstallMode::Upgrade`], outdated kodegen entries are rewritten and
+/// reported as [`MessageCode::Updated`].
+///
+/// # Errors
+///
+/// Returns an error if there are issues scanning for clients or processing configurations.
+pub fn install_all_clients_with_mode(mode: InstallMode) -> Result<Vec<InstallResult>, ConfigError> {
+    install_all_clients_with(InstallOptions {
+        mode,
+        ..InstallOptions::default()
+    })
+}
+
+/// Install kodegen for all detected clients, reporting progress to `observer` as it goes
+///
+/// # Errors
+///
+/// Returns an error if there are issues scanning for clients or processing configurations.
+pub fn install_all_clients_observed(
+    mode: InstallMode,
+    observer: &dyn InstallObserver,
+) -> Result<Vec<InstallResult>, ConfigError> {
+    install_all_clients_with(InstallOptions {
+        mode,
+        observer: Some(observer),
+        ..InstallOptions::default()
+    })
+}
+
+/// Preview installing kodegen for all detected clients without touching any file
+///
+/// Results report what would happen (e.g. [`MessageCode::Configured`]) and
+/// carry the would-be changes as a unified diff in [`InstallResult::diff`].
+///
+/// # Errors
+///
+/// Returns an error if there are issues scanning for clients or processing configurations.
+pub fn install_all_clients_dry_run() -> Result<Vec<InstallResult>, ConfigError> {
+    install_all_clients_with(InstallOptions {
+        dry_run: true,
+        ..InstallOptions::default()
+    })
+}
+
+/// Install kodegen for all detected clients with a settings profile applied
+///
+/// The profile's server definition, transport and endpoint replace the
+/// top-level ones from the settings (see [`Settings::with_profile`]).
+///
+/// # Errors
+///
+/// Returns an error if the profile isn't defined, or there are issues
+/// scanning for clients or processing configurations.
+pub fn install_all_clients_with_profile(profile: &str) -> Result<Vec<InstallResult>, ConfigError> {
+    install_all_clients_with(InstallOptions::default().with_profile(profile))
+}
+
+/// Install kodegen for all detected clients of the current user with the given options
+///
+/// # Errors
+///
+/// Returns an error if there are issues scanning for clients or processing configurations.
+pub fn install_all_clients_with(
+    options: InstallOptions,
+) -> Result<Vec<InstallResult>, ConfigError> {
+    let clients = crate::clients::registry();
+    let settings = options.load_settings()?;
     let mut results = Vec::new();
 
+    for id in options.include.iter().chain(&options.exclude) {
+        if clients.get(id).is_none() {
+            warn!("Unknown client `{id}` in the install's client selection");
+        }
+    }
+
+    info!("🔍 Scanning for MCP-compatible editors...");
+
+    for client in clients.filter(|client| options.selects(client.client_id())) {
+        let result = install_client(client.as_ref(), &settings, &options);
+        results.push(result);
+    }
+
+    Ok(results)
+}
+
+/// Install kodegen for all detected clients, checking them concurrently
+///
+/// Each client's detection and config I/O runs on tokio's blocking pool, with
+/// at most `max_concurrent` clients in flight (at least one). Results are in
+/// the same order as [`install_all_clients`] returns them.
+///
+/// # Errors
+///
+/// Returns an error if the settings are invalid or a client's task panicked.
+pub async fn install_all_clients_async(
+    max_concurrent: usize,
+) -> Result<Vec<InstallResult>, ConfigError> {
+    let clients = crate::clients::registry();
+    let settings = Arc::new(Settings::load()?);
+    // The override is thread-local, so carry it over to the worker threads
+    let home = paths::user_home_override();
+    let limit = Arc::new(Semaphore::new(max_concurrent.max(1)));
+    let mut tasks = JoinSet::new();
+
     info!("🔍 Scanning for MCP-compatible editors...");
 
-    for client in clients {
-        let result = install_client(client.as_ref());
+    for (index, client) in clients.into_iter().enumerate() {
+        let permit = Arc::clone(&limit)
+            .acquire_owned()
+            .await
+            .context("Client install limit was closed")?;
+        let settings = Arc::clone(&settings);
+        let home = home.clone();
+
+        tasks.spawn_blocking(move || {
+            let _permit = permit;
+            let install = || install_client(client.as_ref(), &settings, &InstallOptions::default());
+            let result = match &home {
+                Some(home) => paths::with_user_home(home, install),
+                None => install(),
+            };
+            (index, result)
+        });
+    }
+
+    let mut results = Vec::with_capacity(tasks.len());
+    while let Some(joined) = tasks.join_next().await {
+        results.push(joined.context("Client install task failed")?);
+    }
+    results.sort_by_key(|(index, _)| *index);
+
+    Ok(results.into_iter().map(|(_, result)| result).collect())
+}
+
+/// Remove kodegen from every client config it was added to
+///
+/// Only kodegen's own server entries are removed; the rest of each config is
+/// left as it was. Each changed config is backed up first and journaled as
+/// uninstalled, so the watcher won't add kodegen back.
+///
+/// # Errors
+///
+/// Returns an error if the settings are invalid.
+pub fn uninstall_all_clients() -> Result<Vec<UninstallResult>, ConfigError> {
+    let clients = crate::clients::registry();
+    let settings = Settings::load()?;
+
+    info!("🧹 Removing KODEGEN.ᴀɪ from MCP client configs...");
+
+    Ok(clients
+        .iter()
+        .map(|client| uninstall_client(client.as_ref(), &settings))
+        .collect())
+}
+
+/// Install kodegen machine-wide
+///
+/// Configures each client's machine-wide config locations (ProgramData,
+/// `/etc`, `/Library/Application Support`), then every user's home directory
+/// using that user's own settings. Needs elevated privileges; files created
+/// in a user's home are handed over to that user.
+///
+/// Users with an opt-out marker are skipped, and configs a user removed
+/// kodegen from (per their journal) are left alone.
+///
+/// # Errors
+///
+/// Returns an error if the invoking user's settings are invalid.
+pub fn install_all_clients_system() -> Result<Vec<InstallResult>, ConfigError> {
+    let clients = crate::clients::registry();
+    let settings = Settings::load()?;
+    let mut results = Vec::new();
+
+    info!("🔍 Configuring machine-wide MCP client locations...");
+
+    for client in &clients {
+        let system_paths: Vec<PathBuf> = client
+            .system_config_paths()
+            .into_iter()
+            .map(|config_path| config_path.path)
+            .filter(|path| path.parent().is_some_and(Path::is_dir))
+            .collect();
+
+        if !system_paths.is_empty() {
+            results.push(configure_paths(
+                client.as_ref(),
+                system_paths,
+                &settings,
+                &InstallOptions::default(),
+            ));
+        }
+    }
+
+    for home in paths::user_homes() {
+        if paths::with_user_home(&home, Settings::is_opted_out) {
+            info!("⏭️  {} opted out of autoconfig, skipping", home.display());
+            continue;
+        }
+
+        info!(
+            "🔍 Scanning {} for MCP-compatible editors...",
+            home.display()
+        );
+
+        // Never fight a user who removed kodegen from one of their configs
+        let user_results = paths::with_user_home(&home, || -> Result<Vec<InstallResult>> {
+            let user_settings = Settings::load()?;
+            let options = InstallOptions {
+                respect_removals: true,
+                ..InstallOptions::default()
+            };
+            Ok(clients
+                .iter()
+                .map(|client| install_client(client.as_ref(), &user_settings, &options))
+                .collect())
+        });
+
+        match user_results {
+            Ok(user_results) => results.extend(user_results),
+            Err(e) => error!("Skipping {}: {e}", home.display()),
+        }
+    }
+
+    Ok(results)
+}
+
+/// Install kodegen into the project configs of a workspace directory
+///
+/// Writes the files clients read from a project (e.g. `.vscode/mcp.json` or
+/// Claude Code's `.mcp.json`) so teams can check kodegen's config into their
+/// repository. Clients are configured if they are installed here or the
+/// workspace already has their config file or config directory.
+///
+/// # Errors
+///
+/// Returns an error if the settings are invalid.
+pub fn install_for_workspace(workspace: &Path) -> Result<Vec<InstallResult>, ConfigError> {
+    let clients = crate::clients::registry();
+    let settings = Settings::load()?;
+    let mut results = Vec::new();
+
+    info!(
+        "🔍 Configuring project configs in {}...",
+        workspace.display()
+    );
+
+    for client in &clients {
+        let config_paths: Vec<PathBuf> = client
+            .workspace_config_paths(workspace)
+            .into_iter()
+            .map(|config_path| config_path.path)
+            .collect();
+        if config_paths.is_empty() {
+            continue;
+        }
+
+        // A config dir of the client's own (e.g. `.vscode`), not the workspace itself
+        let in_workspace = config_paths.iter().any(|path| {
+            path.is_file()
+                || path
+                    .parent()
+                    .is_some_and(|parent| parent != workspace && parent.is_dir())
+        });
+        if !in_workspace && !settings.is_client_installed(client.as_ref()) {
+            let code = MessageCode::NotInstalled;
+            results.push(InstallResult::new(
+                client.as_ref(),
+                code,
+                code.message(),
+                None,
+            ));
+            continue;
+        }
+
+        let started = Instant::now();
+        let mut result = configure_paths(
+            client.as_ref(),
+            config_paths,
+            &settings,
+            &InstallOptions {
+                workspace: Some(workspace),
+                ..InstallOptions::default()
+            },
+        );
+        result.duration = started.elapsed();
+        results.push(result);
+    }
+
+    Ok(results)
+}
+
+/// Install kodegen into the Windows host's clients, from inside WSL
+///
+/// Clients whose config directory exists in the Windows user profile (see
+/// [`wsl::host_config_paths`]) get a server definition that starts kodegen
+/// through `wsl.exe`. Returns no results outside WSL.
+///
+/// # Errors
+///
+/// Returns an error if the settings are invalid.
+pub fn install_windows_host_clients(
+    options: InstallOptions<'_>,
+) -> Result<Vec<InstallResult>, ConfigError> {
+    let Some(profile) = wsl::windows_user_profile() else {
+        return Ok(Vec::new());
+    };
+    let clients = crate::clients::registry();
+    let settings = options.load_settings()?;
+    let options = InstallOptions {
+        windows_host: true,
+        ..options
+    };
+    let mut results = Vec::new();
+
+    info!("🔍 Configuring Windows clients in {}...", profile.display());
+
+    for (client_id, config_paths) in wsl::host_config_paths(&profile) {
+        let Some(client) = clients.get(&client_id) else {
+            continue;
+        };
+        if !options.selects(&client_id) {
+            continue;
+        }
+
+        let started = Instant::now();
+        let mut result = configure_paths(client.as_ref(), config_paths, &settings, &options);
+        result.duration = started.elapsed();
         results.push(result);
     }
 
     Ok(results)
 }
 
+/// Install kodegen into the configs of a target: this machine, a machine
+/// reached over SSH, or a workspace's dev container definition
+///
+/// Over SSH, each client's configs are looked for where this machine keeps
+/// them, below the remote user's home, and written where their directory
+/// exists; the remote machine has to run the same OS. Existing configs are
+/// backed up into the local state directory. The server definition is the one
+/// from the local settings, with `${HOME}` as the remote home; kodegen has to
+/// be installed there.
+///
+/// # Errors
+///
+/// Returns an error if the settings are invalid, the remote machine can't be
+/// reached or runs another OS.
+pub fn install_to(
+    target: &InstallTarget,
+    options: InstallOptions<'_>,
+) -> Result<Vec<InstallResult>, ConfigError> {
+    match target {
+        InstallTarget::Local => install_all_clients_with(options),
+        InstallTarget::Ssh(ssh) => install_over_ssh(ssh, &options),
+        InstallTarget::Devcontainer(workspace) => {
+            let client = crate::clients::devcontainer::DevcontainerPlugin;
+            let settings = options.load_settings()?;
+            let config_paths = client
+                .workspace_config_paths(workspace)
+                .into_iter()
+                .map(|config_path| config_path.path)
+                .collect();
+
+            let started = Instant::now();
+            let mut result = configure_paths(
+                &client,
+                config_paths,
+                &settings,
+                &InstallOptions {
+                    workspace: Some(workspace),
+                    ..options
+                },
+            );
+            result.duration = started.elapsed();
+            Ok(vec![result])
+        }
+    }
+}
+
+/// A client with its watch paths and user config paths on a remote machine
+type RemoteClient = (Arc<dyn ClientConfigPlugin>, Vec<PathBuf>, Vec<PathBuf>);
+
+/// Configure the clients found on a machine reached over SSH, for [`install_to`]
+fn install_over_ssh(
+    ssh: &SshTarget,
+    options: &InstallOptions,
+) -> Result<Vec<InstallResult>, ConfigError> {
+    let settings = options.load_settings()?;
+    let state_dirs = StateDirs::resolve()?;
+    let home = ssh.home()?;
+
+    // Clients are looked for where this machine's platform keeps them
+    let os = ssh.os()?;
+    let expected = match Platform::native() {
+        Platform::Linux => "Linux",
+        Platform::MacOS => "Darwin",
+        Platform::Windows | Platform::All => "",
+    };
+    if os != expected {
+        return Err(anyhow!(
+            "{} runs {os}, but only machines running the same OS as this one ({}) can be configured",
+            ssh.destination(),
+            Platform::native().as_str()
+        )
+        .into());
+    }
+    let clients = crate::clients::registry().filter(|client| options.selects(client.client_id()));
+
+    info!(
+        "🔍 Scanning {} for MCP-compatible editors...",
+        ssh.destination()
+    );
+
+    // Where each client lives and keeps its user configs, as if the remote user were local
+    let candidates: Vec<RemoteClient> = paths::with_user_home(&home, || {
+        clients
+            .iter()
+            .map(|client| {
+                let in_home = |path: &PathBuf| path.starts_with(&home);
+                let watch_paths = client.watch_paths().into_iter().filter(in_home).collect();
+                let config_paths = client
+                    .config_paths()
+                    .into_iter()
+                    .map(|config_path| config_path.path)
+                    .filter(in_home)
+                    .collect();
+                (Arc::clone(client), watch_paths, config_paths)
+            })
+            .collect()
+    });
+    let dirs: Vec<PathBuf> = candidates
+        .iter()
+        .flat_map(|(_, watch_paths, config_paths)| {
+            watch_paths
+                .iter()
+                .map(PathBuf::as_path)
+                .chain(config_paths.iter().filter_map(|path| path.parent()))
+        })
+        .map(Path::to_path_buf)
+        .collect();
+    let existing = ssh.existing_dirs(&dirs)?;
+
+    let vars = TemplateVars {
+        home: Some(home),
+        kodegen_bin: None,
+        workspace: None,
+    };
+    let mut results = Vec::new();
+    for (client, watch_paths, config_paths) in candidates {
+        let client = client.as_ref();
+        let exists = |dir: &Path| existing.iter().any(|existing| existing == dir);
+        let config_paths: Vec<PathBuf> = config_paths
+            .into_iter()
+            .filter(|path| path.parent().is_some_and(exists))
+            .collect();
+        if config_paths.is_empty() || !watch_paths.iter().any(|dir| exists(dir)) {
+            let code = MessageCode::NotInstalled;
+            results.push(InstallResult::new(client, code, code.message(), None));
+            continue;
+        }
+
+        let started = Instant::now();
+        let merger = settings
+            .merger_with(&vars)
+            .for_client(client)
+            .with_update_existing(options.mode == InstallMode::Upgrade);
+
+        let mut result = None;
+        for config_path in config_paths {
+            let outcome = update_remote_config(
+                ssh,
+                &state_dirs,
+                client,
+                &config_path,
+                &merger,
+                options.dry_run,
+            );
+            let attempt = match outcome {
+                Ok((code, diff, backup_path)) => {
+                    let mut attempt =
+                        InstallResult::new(client, code, code.message(), Some(config_path));
+                    attempt.diff = diff;
+                    attempt.backup_path = backup_path;
+                    attempt.transport = merger.transport();
+                    attempt
+                }
+                Err(e) => {
+                    error!("Failed to process {}: {e:#}", config_path.display());
+                    let code = MessageCode::from_error(&e, client.config_format());
+                    InstallResult::new(client, code, code.message(), None)
+                        .with_param("config_path", config_path.display().to_string())
+                        .with_param("error", format!("{e:#}"))
+                }
+            };
+            // Report the first success, like local installs
+            if result
+                .as_ref()
+                .is_none_or(|result: &InstallResult| !result.success)
+            {
+                result = Some(attempt);
+            }
+        }
+
+        if let Some(mut result) = result {
+            result.duration = started.elapsed();
+            results.push(result.with_param("target", ssh.destination()));
+        }
+    }
+
+    Ok(results)
+}
+
+/// Add kodegen to one remote config, returning the outcome, the diff of a dry
+/// run and the backup taken
+fn update_remote_config(
+    ssh: &SshTarget,
+    state_dirs: &StateDirs,
+    client: &dyn ClientConfigPlugin,
+    path: &Path,
+    merger: &ConfigMerger,
+    dry_run: bool,
+) -> Result<(MessageCode, Option<String>, Option<PathBuf>)> {
+    // Remote files are only handled as text
+    if client.config_format() == ConfigFormat::Sqlite {
+        return Err(ConfigError::UnsupportedFormat(ConfigFormat::Sqlite).into());
+    }
+
+    let original = ssh.read(path)?;
+    let merger = merger.clone().for_config(client, path);
+    let updated =
+        validate::inject_validated(client, original.as_deref().unwrap_or_default(), &merger)?;
+
+    let code = match original.as_deref() {
+        Some(original) if original == updated => {
+            return Ok((MessageCode::AlreadyConfigured, None, None));
+        }
+        Some(original) if client.is_kodegen_configured(original, &merger) => MessageCode::Updated,
+        Some(_) => MessageCode::Configured,
+        None => MessageCode::Created,
+    };
+    if dry_run {
+        let diff = unified_diff(path, original.as_deref(), &updated);
+        return Ok((code, Some(diff), None));
+    }
+
+    let backup_path = match &original {
+        Some(original) => Some(backup::create_remote_backup(
+            state_dirs,
+            client.client_id(),
+            ssh.destination(),
+            path,
+            original,
+        )?),
+        None => None,
+    };
+    ssh.write(path, &updated)?;
+    info!(
+        "✅ Wrote kodegen config for {} to {}:{}",
+        client.client_name(),
+        ssh.destination(),
+        path.display()
+    );
+    Ok((code, None, backup_path))
+}
+
 /// Install kodegen for a single client
-fn install_client(client: &dyn ClientConfigPlugin) -> InstallResult {
+fn install_client(
+    client: &dyn ClientConfigPlugin,
+    settings: &Settings,
+    options: &InstallOptions,
+) -> InstallResult {
+    let started = Instant::now();
+    let mut result = detect_and_configure(client, settings, options);
+    result.duration = started.elapsed();
+    result
+}
+
+/// Configure a single client if it is installed
+fn detect_and_configure(
+    client: &dyn ClientConfigPlugin,
+    settings: &Settings,
+    options: &InstallOptions,
+) -> InstallResult {
     debug!("Checking {} installation", client.client_name());
 
-    // Check if client is installed (copied from watcher.rs perform_initial_scan)
-    let watch_paths = client.watch_paths();
-    let is_installed = watch_paths.iter().any(|p| client.is_installed(p));
+    // Check if client is installed, including user-declared config paths
+    if !settings.is_client_installed(client) {
+        let code = MessageCode::NotInstalled;
+        options.emit(|| InstallEvent::ClientSkipped {
+            client_id: client.client_id().to_string(),
+            code,
+        });
+        return InstallResult::new(client, code, code.message(), None);
+    }
 
-    if !is_installed {
-        return InstallResult {
-            client_name: client.client_name().to_string(),
+    info!("Found {} installation", client.client_name());
+    options.emit(|| InstallEvent::ClientDetected {
+        client_id: client.client_id().to_string(),
+        client_name: client.client_name().to_string(),
+    });
+
+    let config_paths: Vec<PathBuf> = settings
+        .config_paths_for(client)
+        .into_iter()
+        .map(|config_path| config_path.path)
+        .collect();
+
+    // Reported so users of clients without MCP support know they were seen
+    if config_paths.is_empty() {
+        let code = MessageCode::Unsupported;
+        options.emit(|| InstallEvent::ClientSkipped {
             client_id: client.client_id().to_string(),
-            success: false,
-            message: "Not installed".to_string(),
-            config_path: None,
+            code,
+        });
+        return InstallResult::new(client, code, code.message(), None);
+    }
+
+    configure_paths(client, config_paths, settings, options)
+}
+
+/// Remove kodegen from every config path of a single client
+fn uninstall_client(client: &dyn ClientConfigPlugin, settings: &Settings) -> UninstallResult {
+    let merger = settings.merger();
+    let mut config_paths = Vec::new();
+    let mut failure = None;
+
+    for config_path in settings.config_paths_for(client) {
+        let merger = merger.clone().for_config(client, &config_path.path);
+        match remove_from_config_file(client, &config_path.path, &merger) {
+            Ok(true) => config_paths.push(config_path.path),
+            Ok(false) => {}
+            Err(e) => {
+                error!(
+                    "Failed to remove kodegen from {}: {e}",
+                    config_path.path.display()
+                );
+                failure = Some(MessageCode::from_error(&e, client.config_format()));
+            }
+        }
+    }
+
+    let code = match failure {
+        Some(code) => code,
+        None if config_paths.is_empty() => MessageCode::NotConfigured,
+        None => MessageCode::Removed,
+    };
+
+    UninstallResult {
+        client_name: client.client_name().to_string(),
+        client_id: client.client_id().to_string(),
+        success: code.is_success(),
+        message: code.message().to_string(),
+        code,
+        config_paths,
+    }
+}
+
+/// Remove kodegen from one config file, returning whether it was changed
+fn remove_from_config_file(
+    client: &dyn ClientConfigPlugin,
+    path: &Path,
+    merger: &ConfigMerger,
+) -> Result<bool> {
+    let state_dirs = StateDirs::resolve()?;
+    let _lock = lock::lock_config_blocking(&state_dirs, path)?;
+
+    // An editor may rewrite the config while we work; recompute until we win
+    for _ in 0..lock::MAX_UPDATE_ATTEMPTS {
+        let store = ConfigStore::new(client, path);
+        let config_content = match store.read() {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e.into()),
         };
+
+        let format = merger.config_format(client.config_format());
+        let updated_config = client.remove_servers(&config_content, format, merger)?;
+        if updated_config == config_content {
+            return Ok(false);
+        }
+
+        let written = lock::commit_if_unchanged_blocking(store, Some(&config_content), || {
+            backup_config(client, path, &state_dirs)?;
+            store
+                .write(&updated_config)
+                .map_err(|e| ConfigError::write(path, e).into())
+        })?;
+        if !written {
+            debug!("{} changed while being updated, retrying", path.display());
+            continue;
+        }
+
+        let mut journal = Journal::load(&state_dirs)?;
+        record_in_journal(
+            client,
+            path,
+            &mut journal,
+            &state_dirs,
+            EntryState::Uninstalled,
+        )?;
+
+        info!(
+            "✅ Removed kodegen from {} ({})",
+            client.client_name(),
+            path.display()
+        );
+        return Ok(true);
     }
 
-    info!("Found {} installation", client.client_name());
+    bail!("{} kept changing while being updated", path.display())
+}
 
-    // Try to process each config path
-    for config_path in client.config_paths() {
-        match process_config_file(client, &config_path.path) {
-            Ok(status) => {
-                return InstallResult {
-                    client_name: client.client_name().to_string(),
-                    client_id: client.client_id().to_string(),
-                    success: true,
-                    message: status,
-                    config_path: Some(config_path.path),
-                };
+/// Configure every given config path (e.g. one per editor profile), reporting the first success
+///
+/// The first path is the client's primary location and is created if missing;
+/// the others are alternatives and only updated when they already exist.
+fn configure_paths(
+    client: &dyn ClientConfigPlugin,
+    config_paths: Vec<PathBuf>,
+    settings: &Settings,
+    options: &InstallOptions,
+) -> InstallResult {
+    let config_paths = paths::primary_or_existing(config_paths);
+
+    let vars = match options.workspace {
+        Some(workspace) => TemplateVars::current().with_workspace(workspace),
+        None => TemplateVars::for_client(client),
+    };
+    let merger = settings.merger_with(&vars);
+    let merger = match options.transport {
+        Some(transport) => merger.with_transport(transport),
+        None => merger,
+    }
+    .for_client(client)
+    .with_update_existing(options.mode == InstallMode::Upgrade);
+
+    // Clients fail to start a command that can't be run, so check before writing it
+    let binary_warning = binary::verify_merger(&merger).err().map(binary_warning);
+    if let Some(warning) = &binary_warning
+        && settings.binary_check == BinaryCheck::Refuse
+    {
+        options.emit(|| InstallEvent::ClientSkipped {
+            client_id: client.client_id().to_string(),
+            code: warning.code,
+        });
+        let mut result = InstallResult::new(client, warning.code, &warning.message, None);
+        result.params.extend(warning.params.clone());
+        return result;
+    }
+
+    // Checked above as the Linux command; Windows starts it through WSL
+    let merger = if options.windows_host {
+        merger.map_server_configs(wsl::host_server_config)
+    } else {
+        merger
+    };
+
+    let mut first_success: Option<(MessageCode, PathBuf, Option<PathBuf>)> = None;
+    let mut conflict: Option<InstallResult> = None;
+    let mut failure: Option<InstallResult> = None;
+    let mut diffs = Vec::new();
+    for config_path in config_paths {
+        let merger = merger.clone().for_config(client, &config_path);
+        let outcome = if options.dry_run {
+            preview_config_file(client, &config_path, &merger, options.respect_removals)
+                .map(|(code, diff)| (code, diff, None))
+        } else {
+            process_config_file(
+                client,
+                &config_path,
+                &merger,
+                options.respect_removals,
+                options.elevation,
+            )
+            .map(|(code, backup_path)| (code, None, backup_path))
+        };
+
+        match outcome {
+            Ok((code, diff, backup_path)) => {
+                if let Some(backup_path) = &backup_path {
+                    options.emit(|| InstallEvent::BackupCreated {
+                        client_id: client.client_id().to_string(),
+                        config_path: config_path.clone(),
+                        backup_path: backup_path.clone(),
+                    });
+                }
+
+                if code.is_change() && !options.dry_run {
+                    options.emit(|| InstallEvent::ConfigWritten {
+                        client_id: client.client_id().to_string(),
+                        config_path: config_path.clone(),
+                        code,
+                    });
+
+                    if settings.notifications {
+                        notify::notify_configured(client, &config_path, None);
+                    }
+                }
+
+                diffs.extend(diff);
+
+                if first_success.is_none() {
+                    first_success = Some((code, config_path, backup_path));
+                }
             }
             Err(e) => {
-                error!("Failed to process {}: {}", config_path.path.display(), e);
-                // Continue to try next config path
+                let code = MessageCode::from_error(&e, client.config_format());
+                options.emit(|| InstallEvent::ConfigFailed {
+                    client_id: client.client_id().to_string(),
+                    config_path: config_path.clone(),
+                    code,
+                    error: format!("{e:#}"),
+                });
+                let mut result = InstallResult::new(client, code, code.message(), None)
+                    .with_param("config_path", config_path.display().to_string())
+                    .with_param("error", format!("{e:#}"));
+                result.failure = Some(FailureDetail::capture(&e, &config_path));
+                if code == MessageCode::PermissionDenied {
+                    result.message = format!("Permission denied writing {}", config_path.display());
+                    result = result.with_param("remediation", elevation::remediation(&config_path));
+                }
+
+                if let Some(ServerNameConflict { name }) = error::server_name_conflict(&e) {
+                    warn!("Skipping {}: {}", config_path.display(), e);
+                    let mut result = result.with_param("server_name", name.clone());
+                    result.message = format!("Conflict: {e}");
+                    conflict = Some(result);
+                } else {
+                    error!("Failed to process {}: {}", config_path.display(), e);
+                    // Continue to try next config path
+                    failure = Some(result);
+                }
             }
         }
     }
 
+    if let Some((code, path, backup_path)) = first_success {
+        if !code.is_change() && !options.dry_run {
+            options.emit(|| InstallEvent::ClientSkipped {
+                client_id: client.client_id().to_string(),
+                code,
+            });
+        }
+
+        let mut result = InstallResult::new(client, code, code.message(), Some(path));
+        result.backup_path = backup_path;
+        result.transport = merger.transport();
+        result.warnings.extend(binary_warning);
+        result.diff = (!diffs.is_empty()).then(|| diffs.concat());
+        return result;
+    }
+
     // All config paths failed
-    InstallResult {
-        client_name: client.client_name().to_string(),
-        client_id: client.client_id().to_string(),
-        success: false,
-        message: "Failed to configure".to_string(),
-        config_path: None,
+    conflict.or(failure).unwrap_or_else(|| {
+        let code = MessageCode::Failed;
+        InstallResult::new(client, code, code.message(), None)
+    })
+}
+
+/// Report a configured command that can't be run, as the client would fail to start it
+fn binary_warning(error: BinaryError) -> InstallWarning {
+    warn!("⚠️  {error}; clients won't be able to start it");
+
+    let mut params = BTreeMap::from([("command".to_string(), error.command())]);
+    let code = match error {
+        BinaryError::NotFound { found, .. } => {
+            if let Some(found) = found {
+                info!("💡 kodegen was found at {}", found.display());
+                params.insert("found".to_string(), found.display().to_string());
+            }
+            MessageCode::BinaryMissing
+        }
+        BinaryError::NotExecutable { .. } => MessageCode::BinaryNotExecutable,
+    };
+
+    InstallWarning {
+        code,
+        message: code.message().to_string(),
+        params,
     }
 }
 
 /// Process a config file - sync version adapted from watcher.rs
-fn process_config_file(client: &dyn ClientConfigPlugin, path: &Path) -> Result<String> {
-    use std::fs;
+///
+/// With `respect_removals`, a config whose kodegen entry (or the whole file)
+/// disappeared after we wrote it is left alone and marked as removed by the user.
+/// Writes the current user isn't allowed to make go through `elevation`, if given.
+///
+/// Returns the outcome along with the backup taken of the config, if any.
+fn process_config_file(
+    client: &dyn ClientConfigPlugin,
+    path: &Path,
+    merger: &ConfigMerger,
+    respect_removals: bool,
+    elevation: Option<&dyn Elevation>,
+) -> Result<(MessageCode, Option<PathBuf>)> {
+    let state_dirs = StateDirs::resolve()?;
+
+    // A machine-wide install must not be steered outside the user's home by a symlink
+    for target in [path, state_dirs.root()] {
+        paths::check_user_home_path(target).map_err(|e| ConfigError::write(target, e))?;
+    }
+
+    // Hold the file against the watcher (or another install) until we're done
+    let _lock = lock::lock_config_blocking(&state_dirs, path)?;
+    match_home_ownership(&state_dirs.lock_path(path))?;
+
+    let mut journal = Journal::load(&state_dirs)?;
+    let managed_before = respect_removals && journal.get(path).is_some();
 
+    // An editor may rewrite the config while we work; recompute until we win
+    for _ in 0..lock::MAX_UPDATE_ATTEMPTS {
+        if let Some(outcome) = update_config_file(
+            client,
+            path,
+            merger,
+            managed_before,
+            &mut journal,
+            &state_dirs,
+            elevation,
+        )? {
+            return Ok(outcome);
+        }
+        debug!("{} changed while being updated, retrying", path.display());
+    }
+
+    bail!("{} kept changing while being updated", path.display())
+}
+
+/// Read, update and write back a config once for [`process_config_file`]
+///
+/// Returns `None` if the config changed before the update could be written.
+fn update_config_file(
+    client: &dyn ClientConfigPlugin,
+    path: &Path,
+    merger: &ConfigMerger,
+    managed_before: bool,
+    journal: &mut Journal,
+    state_dirs: &StateDirs,
+    elevation: Option<&dyn Elevation>,
+) -> Result<Option<(MessageCode, Option<PathBuf>)>> {
     // Read existing config (adapted from watcher.rs line 193-209)
-    let config_content = match fs::read_to_string(path) {
+    let store = ConfigStore::new(client, path);
+    let config_content = match store.read() {
         Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound && managed_before => {
+            return skip_removed_by_user(client, path, journal, state_dirs)
+                .map(|code| Some((code, None)));
+        }
         Err(e) if e.kind() == ErrorKind::NotFound => {
             // Config doesn't exist - create it
-            let new_config = client.inject_kodegen("{}", client.config_format())?;
+            let new_config = validate::inject_validated(client, "", merger)?;
 
-            // Ensure directory exists
-            if let Some(parent) = path.parent() {
-                fs::create_dir_all(parent)?;
+            let written = lock::commit_if_unchanged_blocking(store, None, || {
+                write_config(store, &new_config, elevation)
+            })?;
+            if !written {
+                return Ok(None);
             }
 
-            // Write new config
-            fs::write(path, &new_config)?;
+            record_in_journal(client, path, journal, state_dirs, EntryState::Installed)?;
             info!("✅ Created kodegen config for {}", client.client_name());
-            return Ok("Created new config".to_string());
+            return Ok(Some((MessageCode::Created, None)));
         }
         Err(e) => return Err(e.into()),
     };
 
-    // Fast-path check: already configured? (watcher.rs line 220-223)
-    if config_content.contains("kodegen") {
+    // Inject kodegen config (watcher.rs line 242)
+    let updated_config = validate::inject_validated(client, &config_content, merger)?;
+
+    // Already configured: the merge left the config untouched
+    if updated_config == config_content {
         debug!("Already configured, skipping");
-        return Ok("Already configured".to_string());
+        record_in_journal(client, path, journal, state_dirs, EntryState::Installed)?;
+        return Ok(Some((MessageCode::AlreadyConfigured, None)));
     }
 
-    // Create backup (watcher.rs line 229-237)
-    let backup_path = {
-        let mut bp = path.to_path_buf();
-        if let Some(filename) = bp.file_name() {
-            let mut new_name = filename.to_os_string();
-            new_name.push(".backup");
-            bp.set_file_name(new_name);
+    // We configured this file before, so a missing entry means the user removed
+    // it (an entry that is merely outdated gets updated)
+    if managed_before && !client.is_kodegen_configured(&config_content, merger) {
+        return skip_removed_by_user(client, path, journal, state_dirs)
+            .map(|code| Some((code, None)));
+    }
+
+    let mut backup_path = None;
+    let written = lock::commit_if_unchanged_blocking(store, Some(&config_content), || {
+        backup_path = Some(backup_config(client, path, state_dirs)?);
+
+        // Write updated config (watcher.rs line 245)
+        write_config(store, &updated_config, elevation)
+    })?;
+    if !written {
+        return Ok(None);
+    }
+
+    record_in_journal(client, path, journal, state_dirs, EntryState::Installed)?;
+
+    let code = if client.is_kodegen_configured(&config_content, merger) {
+        info!("✅ Updated kodegen config for {}", client.client_name());
+        MessageCode::Updated
+    } else {
+        info!("✅ Injected kodegen config for {}", client.client_name());
+        MessageCode::Configured
+    };
+    Ok(Some((code, backup_path)))
+}
+
+/// Write a config and its directory, falling back to `elevation` when permission is denied
+fn write_config(
+    store: ConfigStore<'_>,
+    content: &str,
+    elevation: Option<&dyn Elevation>,
+) -> Result<()> {
+    let path = store.path();
+    let written = match path.parent() {
+        Some(parent) => std::fs::create_dir_all(parent),
+        None => Ok(()),
+    }
+    .and_then(|()| store.write(content));
+
+    let Err(e) = written else {
+        return match_home_ownership(path);
+    };
+    match elevation {
+        Some(elevation) if e.kind() == ErrorKind::PermissionDenied => {
+            info!("🔐 Writing {} with elevated privileges", path.display());
+            store
+                .encode(content)
+                .and_then(|content| elevation.write_elevated(path, &content))
+                .map_err(|e| ConfigError::write(path, e).into())
         }
-        bp
+        _ => Err(ConfigError::write(path, e).into()),
+    }
+}
+
+/// Work out what [`process_config_file`] would do, without writing anything
+///
+/// Returns the outcome along with a unified diff of the change, if any.
+fn preview_config_file(
+    client: &dyn ClientConfigPlugin,
+    path: &Path,
+    merger: &ConfigMerger,
+    respect_removals: bool,
+) -> Result<(MessageCode, Option<String>)> {
+    let journal = Journal::load(&StateDirs::resolve()?)?;
+    let managed_before = respect_removals && journal.get(path).is_some();
+
+    let original = match ConfigStore::new(client, path).read() {
+        Ok(content) => Some(content),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
     };
 
-    fs::copy(path, &backup_path).context("Failed to create backup")?;
+    let updated_config =
+        validate::inject_validated(client, original.as_deref().unwrap_or_default(), merger)?;
 
-    // Inject kodegen config (watcher.rs line 242)
-    let updated_config = client.inject_kodegen(&config_content, client.config_format())?;
+    let code = match &original {
+        Some(content) if *content == updated_config => {
+            return Ok((MessageCode::AlreadyConfigured, None));
+        }
+        Some(content) if managed_before && !client.is_kodegen_configured(content, merger) => {
+            return Ok((MessageCode::SkippedRemovedByUser, None));
+        }
+        None if managed_before => return Ok((MessageCode::SkippedRemovedByUser, None)),
+        Some(content) if client.is_kodegen_configured(content, merger) => MessageCode::Updated,
+        Some(_) => MessageCode::Configured,
+        None => MessageCode::Created,
+    };
 
-    // Write updated config (watcher.rs line 245)
-    fs::write(path, &updated_config)?;
+    let diff = unified_diff(path, original.as_deref(), &updated_config);
+    Ok((code, Some(diff)))
+}
+
+/// Render a change to a config file as a unified diff (`original` is `None` for new files)
+fn unified_diff(path: &Path, original: Option<&str>, updated: &str) -> String {
+    let path = path.display().to_string();
+    let old_name = if original.is_some() {
+        path.as_str()
+    } else {
+        "/dev/null"
+    };
+
+    similar::TextDiff::from_lines(original.unwrap_or_default(), updated)
+        .unified_diff()
+        .context_radius(3)
+        .header(old_name, &path)
+        .to_string()
+}
+
+/// Back up a config file into the autoconfig state directory before changing it
+///
+/// Returns the location of the backup.
+fn backup_config(
+    client: &dyn ClientConfigPlugin,
+    path: &Path,
+    state_dirs: &StateDirs,
+) -> Result<PathBuf> {
+    let backup_path = backup::create_backup(state_dirs, client.client_id(), path).map_err(|e| {
+        ConfigError::BackupFailed {
+            path: path.to_path_buf(),
+            source: e.into(),
+        }
+    })?;
+    match_home_ownership(&backup_path)?;
+    Ok(backup_path)
+}
+
+/// Leave a config alone because the user removed kodegen from it
+fn skip_removed_by_user(
+    client: &dyn ClientConfigPlugin,
+    path: &Path,
+    journal: &mut Journal,
+    state_dirs: &StateDirs,
+) -> Result<MessageCode> {
+    info!(
+        "⏭️  kodegen was removed from {} by the user, not re-adding it",
+        path.display()
+    );
+    record_in_journal(client, path, journal, state_dirs, EntryState::UserRemoved)?;
+    Ok(MessageCode::SkippedRemovedByUser)
+}
+
+/// Record a config file's state in the journal and persist it
+fn record_in_journal(
+    client: &dyn ClientConfigPlugin,
+    path: &Path,
+    journal: &mut Journal,
+    state_dirs: &StateDirs,
+    state: EntryState,
+) -> Result<()> {
+    if journal
+        .get(path)
+        .is_some_and(|entry| entry.state == state && entry.client_id == client.client_id())
+    {
+        return Ok(());
+    }
+
+    journal.commit(state_dirs, client.client_id(), path, state)?;
+    match_home_ownership(&state_dirs.journal_lock_path())?;
+    match_home_ownership(&Journal::path(state_dirs))
+}
+
+/// Hand files created in another user's home (during a machine-wide install) over to that user
+///
+/// Symlinks are never followed, so the user can't redirect the change of
+/// owner onto a file outside their home.
+#[cfg(unix)]
+fn match_home_ownership(path: &Path) -> Result<()> {
+    use std::os::unix::fs::{MetadataExt, lchown};
+
+    let Some(home) = paths::user_home_override() else {
+        return Ok(());
+    };
+    paths::check_user_home_path(path).map_err(|e| ConfigError::write(path, e))?;
+
+    let owner = std::fs::metadata(&home)?;
+    for target in path
+        .ancestors()
+        .take_while(|ancestor| ancestor.starts_with(&home) && *ancestor != home)
+    {
+        if std::fs::symlink_metadata(target)?.uid() != owner.uid() {
+            lchown(target, Some(owner.uid()), Some(owner.gid()))
+                .with_context(|| format!("Failed to change owner of {}", target.display()))?;
+        }
+    }
+
+    Ok(())
+}
 
-    info!("✅ Injected kodegen config for {}", client.client_name());
-    Ok("Configured successfully".to_string())
+/// Windows ACLs inherit from the user profile, so there is nothing to fix up
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+fn match_home_ownership(_path: &Path) -> Result<()> {
+    Ok(())
 }
@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::state::StateDirs;
+use crate::{atomic, lock};
+
+/// What autoconfig last knew about the kodegen entry in a config file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EntryState {
+    /// We wrote the entry (or found it already present)
+    Installed,
+    /// The entry disappeared after we wrote it, so the user removed it
+    UserRemoved,
+    /// The entry was removed by an uninstall
+    Uninstalled,
+}
+
+impl EntryState {
+    /// Whether kodegen was deliberately taken out of the config and must not be re-added automatically
+    #[must_use]
+    pub fn is_removed(self) -> bool {
+        matches!(self, Self::UserRemoved | Self::Uninstalled)
+    }
+}
+
+/// Journal record for a single config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub client_id: String,
+    pub state: EntryState,
+    /// Seconds since the Unix epoch
+    pub updated_at: u64,
+}
+
+/// Per-user record of the config files autoconfig has managed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Journal {
+    entries: BTreeMap<PathBuf, JournalEntry>,
+}
+
+impl Journal {
+    /// Get the journal file location within a state directory
+    #[must_use]
+    pub fn path(state_dirs: &StateDirs) -> PathBuf {
+        state_dirs.journal_dir().join("entries.json")
+    }
+
+    /// Load the journal, starting empty when none has been written yet
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the journal exists but cannot be read or parsed.
+    pub fn load(state_dirs: &StateDirs) -> Result<Self> {
+        let path = Self::path(state_dirs);
+
+        match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse journal at {}", path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).context(format!("Failed to read journal at {}", path.display())),
+        }
+    }
+
+    /// Write the journal back to the state directory
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the journal cannot be written.
+    pub fn save(&self, state_dirs: &StateDirs) -> Result<()> {
+        let path = Self::path(state_dirs);
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create journal directory")?;
+        }
+
+        atomic::write_atomic(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write journal at {}", path.display()))
+    }
+
+    /// Record the current state of a config file and persist it
+    ///
+    /// Installs of other clients and the watcher may have saved the journal
+    /// since this copy was loaded, so the record is applied to the journal on
+    /// disk, under its lock, and this copy is refreshed with the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the journal cannot be locked, read or written.
+    pub fn commit(
+        &mut self,
+        state_dirs: &StateDirs,
+        client_id: &str,
+        config_path: &Path,
+        state: EntryState,
+    ) -> Result<()> {
+        let _lock = lock::lock_file(&state_dirs.journal_lock_path())?;
+
+        let mut current = Self::load(state_dirs)?;
+        current.record(client_id, config_path, state);
+        current.save(state_dirs)?;
+
+        *self = current;
+        Ok(())
+    }
+
+    /// Get the record for a config file
+    #[must_use]
+    pub fn get(&self, config_path: &Path) -> Option<&JournalEntry> {
+        self.entries.get(config_path)
+    }
+
+    /// Record the current state of a config file
+    pub fn record(&mut self, client_id: &str, config_path: &Path, state: EntryState) {
+        let updated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+
+        self.entries.insert(
+            config_path.to_path_buf(),
+            JournalEntry {
+                client_id: client_id.to_string(),
+                state,
+                updated_at,
+            },
+        );
+    }
+
+    /// Iterate over all records
+    pub fn entries(&self) -> impl Iterator<Item = (&Path, &JournalEntry)> {
+        self.entries
+            .iter()
+            .map(|(path, entry)| (path.as_path(), entry))
+    }
+}
@@ -0,0 +1,548 @@
+//! Span-based editing of JSON with comments (JSONC)
+//!
+//! Editor settings files (VS Code, Zed, Cursor) commonly contain comments and
+//! trailing commas. Rather than parsing and re-serializing them, which loses
+//! both, edits are made as text insertions and deletions at the spans of the
+//! affected object members, so everything else stays byte-for-byte intact.
+//!
+//! All spans are byte offsets into the original text. They are found on a
+//! copy with comments and trailing commas blanked out by spaces, which keeps
+//! every offset valid for the original.
+
+use std::ops::Range;
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use serde_json::ser::{PrettyFormatter, Serializer};
+
+/// Indentation used when the document gives no hint
+const DEFAULT_INDENT: &str = "  ";
+
+/// A JSONC document with the blanked copies used to locate spans
+pub(crate) struct Document<'a> {
+    original: &'a str,
+    /// Comments blanked out
+    no_comments: String,
+    /// Comments and trailing commas blanked out; parses as plain JSON
+    stripped: String,
+}
+
+/// A member of an object: `"key": value`
+struct Member {
+    key: String,
+    /// Offset of the key's opening quote
+    start: usize,
+    value: Range<usize>,
+}
+
+/// An object with the spans of its braces and members
+struct Object {
+    open: usize,
+    close: usize,
+    members: Vec<Member>,
+}
+
+impl Object {
+    /// Find a member by key (the last one wins, as with serde_json)
+    fn member(&self, key: &str) -> Option<usize> {
+        self.members.iter().rposition(|member| member.key == key)
+    }
+}
+
+/// A text replacement
+type Edit = (Range<usize>, String);
+
+impl<'a> Document<'a> {
+    /// Prepare a document for span lookups
+    pub(crate) fn new(original: &'a str) -> Self {
+        let no_comments = blank_comments(original);
+        let stripped = blank_trailing_commas(&no_comments);
+
+        Self {
+            original,
+            no_comments,
+            stripped,
+        }
+    }
+
+    /// The document as plain JSON, with the same byte offsets as the original
+    pub(crate) fn stripped(&self) -> &str {
+        &self.stripped
+    }
+
+    /// Insert `value` under `key` in the object at `path`, or replace the existing value
+    ///
+    /// Objects along `path` that don't exist yet are created. Returns the edited text.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document or an object along `path` isn't a JSON object.
+    pub(crate) fn upsert(&self, path: &[&str], key: &str, value: &JsonValue) -> Result<String> {
+        let mut object = self.root()?;
+        let mut value = value.clone();
+        let mut key = key;
+
+        for (depth, name) in path.iter().enumerate() {
+            let Some(index) = object.member(name) else {
+                // Wrap the value in the missing objects and insert those instead
+                for missing in path[depth + 1..].iter().rev() {
+                    value = JsonValue::Object([(key.to_string(), value)].into_iter().collect());
+                    key = *missing;
+                }
+                value = JsonValue::Object([(key.to_string(), value)].into_iter().collect());
+                key = *name;
+                break;
+            };
+            object = self.object_at(object.members[index].value.start)?;
+        }
+
+        let edits = match object.member(key) {
+            Some(index) => {
+                let member = &object.members[index];
+                let indent = self.line_indent(member.start);
+                let rendered = self.render(&value, &indent)?;
+                vec![(member.value.clone(), rendered)]
+            }
+            None => self.insert_member(&object, key, &value)?,
+        };
+
+        Ok(self.apply(edits))
+    }
+
+    /// Remove the member `key` from the object at `path`
+    ///
+    /// Returns the edited text, or `None` if there is no such member.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document or an object along `path` isn't a JSON object.
+    pub(crate) fn remove(&self, path: &[&str], key: &str) -> Result<Option<String>> {
+        let mut object = self.root()?;
+        for name in path {
+            let Some(index) = object.member(name) else {
+                return Ok(None);
+            };
+            object = self.object_at(object.members[index].value.start)?;
+        }
+
+        let Some(index) = object.member(key) else {
+            return Ok(None);
+        };
+
+        Ok(Some(self.apply(self.remove_member(&object, index))))
+    }
+
+    /// Whether the object at `path` exists and has neither members nor comments
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document or an object along `path` isn't a JSON object.
+    pub(crate) fn is_blank_object(&self, path: &[&str]) -> Result<bool> {
+        let mut object = self.root()?;
+        for name in path {
+            let Some(index) = object.member(name) else {
+                return Ok(false);
+            };
+            object = self.object_at(object.members[index].value.start)?;
+        }
+
+        Ok(object.members.is_empty()
+            && self.original[object.open + 1..object.close]
+                .trim()
+                .is_empty())
+    }
+
+    /// Parse the top-level object
+    fn root(&self) -> Result<Object> {
+        let start = self
+            .stripped
+            .find(|c: char| !c.is_whitespace())
+            .context("Empty JSON document")?;
+        self.object_at(start)
+    }
+
+    /// Parse the object whose opening brace is at `open`
+    fn object_at(&self, open: usize) -> Result<Object> {
+        let bytes = self.stripped.as_bytes();
+        if bytes.get(open) != Some(&b'{') {
+            bail!("Expected a JSON object at byte {open}");
+        }
+
+        let mut members = Vec::new();
+        let mut pos = skip_whitespace(bytes, open + 1);
+        if bytes.get(pos) == Some(&b'}') {
+            return Ok(Object {
+                open,
+                close: pos,
+                members,
+            });
+        }
+
+        loop {
+            if bytes.get(pos) != Some(&b'"') {
+                bail!("Expected an object key at byte {pos}");
+            }
+            let key_end = skip_string(bytes, pos)?;
+            let key: String = serde_json::from_str(&self.stripped[pos..key_end])
+                .with_context(|| format!("Invalid object key at byte {pos}"))?;
+
+            let colon = skip_whitespace(bytes, key_end);
+            if bytes.get(colon) != Some(&b':') {
+                bail!("Expected `:` at byte {colon}");
+            }
+
+            let value_start = skip_whitespace(bytes, colon + 1);
+            let value_end = skip_value(bytes, value_start)?;
+            members.push(Member {
+                key,
+                start: pos,
+                value: value_start..value_end,
+            });
+
+            pos = skip_whitespace(bytes, value_end);
+            match bytes.get(pos) {
+                Some(b',') => pos = skip_whitespace(bytes, pos + 1),
+                Some(b'}') => {
+                    return Ok(Object {
+                        open,
+                        close: pos,
+                        members,
+                    });
+                }
+                _ => bail!("Expected `,` or `}}` at byte {pos}"),
+            }
+        }
+    }
+
+    /// Edits adding a member at the end of an object, following the surrounding style
+    fn insert_member(&self, object: &Object, key: &str, value: &JsonValue) -> Result<Vec<Edit>> {
+        let eol = self.eol();
+        let key = serde_json::to_string(key)?;
+
+        let Some(last) = object.members.last() else {
+            let interior = object.open + 1..object.close;
+            // Keep comments inside an otherwise empty object
+            let blank = self.original[interior.clone()].trim().is_empty();
+            let after_open = object.open + 1..object.open + 1;
+
+            // Single-line document: stay on the line
+            if !self.original.contains('\n') {
+                let member = format!("{key}: {}", serde_json::to_string(value)?);
+                return Ok(vec![(if blank { interior } else { after_open }, member)]);
+            }
+
+            let outer = self.line_indent(object.open);
+            let inner = format!("{outer}{}", self.indent_unit());
+            let member = format!("{key}: {}", self.render(value, &inner)?);
+            return Ok(if blank {
+                vec![(interior, format!("{eol}{inner}{member}{eol}{outer}"))]
+            } else {
+                vec![(after_open, format!("{eol}{inner}{member}"))]
+            });
+        };
+
+        let indent = self
+            .own_line_indent(object.members[0].start)
+            .unwrap_or_else(|| format!("{}{}", self.line_indent(object.open), self.indent_unit()));
+        let trailing_comma = self.comma_after(last.value.end, object.close);
+        let anchor = trailing_comma.map_or(last.value.end, |comma| comma + 1);
+
+        let Some(line_end) = self.line_end_after(anchor, object.close) else {
+            // Single-line object: stay on the line
+            let member = format!("{key}: {}", serde_json::to_string(value)?);
+            return Ok(match trailing_comma {
+                Some(_) => vec![(anchor..anchor, format!(" {member},"))],
+                None => vec![(anchor..anchor, format!(", {member}"))],
+            });
+        };
+
+        let member = format!("{key}: {}", self.render(value, &indent)?);
+        Ok(match trailing_comma {
+            Some(_) => vec![(line_end..line_end, format!("{eol}{indent}{member},"))],
+            None => vec![
+                (anchor..anchor, ",".to_string()),
+                (line_end..line_end, format!("{eol}{indent}{member}")),
+            ],
+        })
+    }
+
+    /// Edits removing a member, mirroring [`Self::insert_member`]
+    fn remove_member(&self, object: &Object, index: usize) -> Vec<Edit> {
+        let member = &object.members[index];
+        let trailing_comma = self.comma_after(member.value.end, object.close);
+        let line_start = self.line_start(member.start);
+        let own_line = self.own_line_indent(member.start).is_some();
+
+        if object.members.len() == 1 {
+            let interior = object.open + 1..object.close;
+            if self.no_comments[interior.clone()] == self.original[interior.clone()] {
+                return vec![(interior, String::new())];
+            }
+            let end = trailing_comma.map_or(member.value.end, |comma| comma + 1);
+            let start = if own_line {
+                self.line_break_before(line_start)
+            } else {
+                member.start
+            };
+            return vec![(start..end, String::new())];
+        }
+
+        if index + 1 < object.members.len() {
+            // Followed by another member, so there is always a separating comma
+            let comma = trailing_comma.unwrap_or(member.value.end);
+            if own_line && let Some(line_end) = self.line_end_after(comma + 1, object.close) {
+                let next_line =
+                    line_end + self.original[line_end..].find('\n').map_or(0, |i| i + 1);
+                return vec![(line_start..next_line, String::new())];
+            }
+            let end = skip_whitespace(self.stripped.as_bytes(), comma + 1);
+            return vec![(member.start..end, String::new())];
+        }
+
+        // Last member: drop the comma separating it from the previous one
+        let previous = &object.members[index - 1];
+        let Some(separator) = self.comma_after(previous.value.end, member.start) else {
+            return vec![(previous.value.end..member.value.end, String::new())];
+        };
+
+        if !own_line {
+            return vec![(separator..member.value.end, String::new())];
+        }
+
+        let newline = self.line_break_before(line_start);
+        match trailing_comma {
+            Some(comma) => vec![(newline..comma + 1, String::new())],
+            None => vec![
+                (separator..separator + 1, String::new()),
+                (newline..member.value.end, String::new()),
+            ],
+        }
+    }
+
+    /// Pretty-print `value` as a whole new document, in this one's indentation and line endings
+    pub(crate) fn rewrite(&self, value: &JsonValue) -> Result<String> {
+        self.render(value, "")
+    }
+
+    /// Render a value pretty-printed in the document's style, continuing at `indent`
+    fn render(&self, value: &JsonValue, indent: &str) -> Result<String> {
+        let mut buffer = Vec::new();
+        let indent_unit = self.indent_unit();
+        let formatter = PrettyFormatter::with_indent(indent_unit.as_bytes());
+        value.serialize(&mut Serializer::with_formatter(&mut buffer, formatter))?;
+
+        let rendered = String::from_utf8(buffer)?;
+        Ok(rendered.replace('\n', &format!("{}{indent}", self.eol())))
+    }
+
+    /// Apply non-overlapping edits to the original text
+    ///
+    /// Insertions at the same offset end up in the order they were given.
+    fn apply(&self, edits: Vec<Edit>) -> String {
+        let mut edits: Vec<_> = edits.into_iter().enumerate().collect();
+        edits.sort_by_key(|(index, (range, _))| std::cmp::Reverse((range.start, *index)));
+
+        let mut text = self.original.to_string();
+        for (_, (range, replacement)) in edits {
+            text.replace_range(range, &replacement);
+        }
+        text
+    }
+
+    /// Find a real (non-comment) comma between `from` and `until`
+    fn comma_after(&self, from: usize, until: usize) -> Option<usize> {
+        let between = &self.no_comments[from..until];
+        let offset = between.find(|c: char| !c.is_whitespace())?;
+        (between.as_bytes()[offset] == b',').then_some(from + offset)
+    }
+
+    /// Where the line containing `from` ends, if only whitespace or comments follow on it
+    ///
+    /// Points at the line break (before any `\r`), and only if that comes before `until`.
+    fn line_end_after(&self, from: usize, until: usize) -> Option<usize> {
+        let newline = from + self.stripped[from..].find('\n')?;
+        if newline >= until || !self.stripped[from..newline].trim().is_empty() {
+            return None;
+        }
+
+        Some(if self.original[..newline].ends_with('\r') {
+            newline - 1
+        } else {
+            newline
+        })
+    }
+
+    /// Offset of the start of the line containing `pos`
+    fn line_start(&self, pos: usize) -> usize {
+        self.original[..pos]
+            .rfind('\n')
+            .map_or(0, |newline| newline + 1)
+    }
+
+    /// Offset of the line break (including any `\r`) ending the line before `line_start`
+    fn line_break_before(&self, line_start: usize) -> usize {
+        let Some(newline) = line_start.checked_sub(1) else {
+            return 0;
+        };
+        if self.original[..newline].ends_with('\r') {
+            newline - 1
+        } else {
+            newline
+        }
+    }
+
+    /// Leading whitespace of the line containing `pos`
+    fn line_indent(&self, pos: usize) -> String {
+        let line = &self.original[self.line_start(pos)..];
+        let indent_len = line.len() - line.trim_start_matches([' ', '\t']).len();
+        line[..indent_len].to_string()
+    }
+
+    /// The indentation before `pos`, if nothing else precedes it on its line
+    fn own_line_indent(&self, pos: usize) -> Option<String> {
+        let before = &self.original[self.line_start(pos)..pos];
+        before
+            .chars()
+            .all(|c| c == ' ' || c == '\t')
+            .then(|| before.to_string())
+    }
+
+    /// One level of indentation, taken from the first member of the top-level object
+    fn indent_unit(&self) -> String {
+        self.root()
+            .ok()
+            .and_then(|root| root.members.first().map(|member| member.start))
+            .and_then(|start| self.own_line_indent(start))
+            .filter(|indent| !indent.is_empty())
+            .unwrap_or_else(|| DEFAULT_INDENT.to_string())
+    }
+
+    /// The document's line ending
+    fn eol(&self) -> &'static str {
+        if self.original.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        }
+    }
+}
+
+/// Replace comments with spaces, keeping line breaks and byte offsets
+fn blank_comments(text: &str) -> String {
+    let mut bytes = text.as_bytes().to_vec();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        match (bytes[pos], bytes.get(pos + 1)) {
+            (b'"', _) => pos = skip_string(&bytes, pos).unwrap_or(bytes.len()),
+            (b'/', Some(b'/')) => {
+                while pos < bytes.len() && bytes[pos] != b'\n' {
+                    if bytes[pos] != b'\r' {
+                        bytes[pos] = b' ';
+                    }
+                    pos += 1;
+                }
+            }
+            (b'/', Some(b'*')) => {
+                let end = text[pos + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |offset| pos + 2 + offset + 2);
+                for byte in &mut bytes[pos..end] {
+                    if *byte != b'\n' && *byte != b'\r' {
+                        *byte = b' ';
+                    }
+                }
+                pos = end;
+            }
+            _ => pos += 1,
+        }
+    }
+
+    // Only whole characters inside comments were replaced, byte by byte, with ASCII
+    String::from_utf8(bytes).unwrap_or_else(|_| text.to_string())
+}
+
+/// Replace commas directly followed by a closing bracket with spaces
+fn blank_trailing_commas(text: &str) -> String {
+    let mut bytes = text.as_bytes().to_vec();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'"' => pos = skip_string(&bytes, pos).unwrap_or(bytes.len()),
+            b',' => {
+                let next = skip_whitespace(&bytes, pos + 1);
+                if matches!(bytes.get(next), Some(b'}' | b']')) {
+                    bytes[pos] = b' ';
+                }
+                pos += 1;
+            }
+            _ => pos += 1,
+        }
+    }
+
+    String::from_utf8(bytes).unwrap_or_else(|_| text.to_string())
+}
+
+/// Skip whitespace, returning the offset of the next other byte
+fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+    while bytes.get(pos).is_some_and(u8::is_ascii_whitespace) {
+        pos += 1;
+    }
+    pos
+}
+
+/// Skip a string starting at its opening quote, returning the offset after the closing quote
+fn skip_string(bytes: &[u8], start: usize) -> Result<usize> {
+    let mut pos = start + 1;
+    while let Some(&byte) = bytes.get(pos) {
+        match byte {
+            b'\\' => pos += 2,
+            b'"' => return Ok(pos + 1),
+            _ => pos += 1,
+        }
+    }
+
+    bail!("Unterminated string at byte {start}")
+}
+
+/// Skip a value starting at `start`, returning the offset after it
+fn skip_value(bytes: &[u8], start: usize) -> Result<usize> {
+    match bytes.get(start) {
+        Some(b'"') => skip_string(bytes, start),
+        Some(b'{' | b'[') => {
+            let mut depth = 0usize;
+            let mut pos = start;
+            while let Some(&byte) = bytes.get(pos) {
+                match byte {
+                    b'"' => {
+                        pos = skip_string(bytes, pos)?;
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Ok(pos + 1);
+                        }
+                    }
+                    _ => {}
+                }
+                pos += 1;
+            }
+            bail!("Unterminated value at byte {start}")
+        }
+        Some(_) => {
+            let mut pos = start;
+            while bytes
+                .get(pos)
+                .is_some_and(|byte| !byte.is_ascii_whitespace() && !b",}]".contains(byte))
+            {
+                pos += 1;
+            }
+            Ok(pos)
+        }
+        None => bail!("Expected a value at byte {start}"),
+    }
+}
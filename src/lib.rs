@@ -1,15 +1,70 @@
+mod atomic;
+pub mod backup;
+pub mod binary;
 pub mod clients;
+pub mod codec;
 pub mod config;
+pub mod daemon;
+pub mod detect;
+pub mod elevation;
+pub mod error;
+pub mod failure;
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
+mod ini;
 pub mod install;
+pub mod journal;
+mod jsonc;
+pub mod lock;
+pub mod notify;
+pub mod observer;
+pub mod paths;
+pub mod registry;
+pub mod restart;
+pub mod service;
+pub mod settings;
+pub mod state;
+mod store;
+pub mod target;
+pub mod template;
+pub mod validate;
+pub mod verify;
+pub mod version;
 pub mod watcher;
+pub mod wsl;
+mod xml;
+mod yaml;
 
 // Re-export commonly used types
+use std::collections::BTreeMap;
+use std::fmt;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use anyhow::Result;
-pub use config::ConfigMerger;
-pub use install::{InstallResult, install_all_clients};
+pub use backup::{Backup, cleanup_backups, list_backups, restore_backup, stale_backups};
+pub use binary::{BinaryCheck, BinaryError, resolve_kodegen_binary};
+pub use clients::ClientRegistry;
+pub use config::{ConfigMerger, ConflictResolution, EntryShape, MergeTarget, TransportPreference};
+pub use daemon::{Daemon, DaemonCommand, DaemonStatus};
+pub use detect::{ClientStatus, ConfigFileStatus, detect_clients};
+pub use elevation::Elevation;
+pub use error::ConfigError;
+pub use failure::FailureDetail;
+pub use install::{
+    InstallAction, InstallMode, InstallOptions, InstallReport, InstallResult, MessageCode,
+    UninstallResult, install_all_clients, install_all_clients_async, install_all_clients_dry_run,
+    install_all_clients_observed, install_all_clients_system, install_all_clients_with,
+    install_all_clients_with_mode, install_all_clients_with_profile, install_for_workspace,
+    install_to, install_windows_host_clients, uninstall_all_clients,
+};
+pub use observer::{InstallEvent, InstallObserver};
 use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+pub use target::{InstallTarget, SshTarget};
+pub use template::TemplateVars;
+pub use validate::{ValidationIssue, validate_config};
+pub use version::Version;
 
 /// Core trait for MCP client configuration plugins
 pub trait ClientConfigPlugin: Send + Sync {
@@ -25,36 +80,250 @@ pub trait ClientConfigPlugin: Send + Sync {
     /// Get the config file path(s) for this client
     fn config_paths(&self) -> Vec<ConfigPath>;
 
+    /// Get machine-wide config path(s) applying to every user, if the client supports them
+    ///
+    /// Only used by machine-wide installs; most clients have none.
+    fn system_config_paths(&self) -> Vec<ConfigPath> {
+        Vec::new()
+    }
+
+    /// Get the project config path(s) the client reads from a workspace directory
+    ///
+    /// Only used by [`install_for_workspace`]; most clients have none.
+    fn workspace_config_paths(&self, _workspace: &Path) -> Vec<ConfigPath> {
+        Vec::new()
+    }
+
+    /// Get the process names the client runs under, used to restart it after configuration
+    fn process_names(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Get the names the client's app is listed under in Windows' installed
+    /// apps or Start Menu (e.g. `Cursor`)
+    ///
+    /// Where given, Windows installs are detected from that list rather than
+    /// from leftover folders; see [`registry`].
+    fn windows_app_names(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Get the client's own variable for the open workspace (e.g. `${workspaceFolder}`)
+    ///
+    /// Used for `${WORKSPACE}` in the server definition of user configs; see
+    /// [`TemplateVars`].
+    fn workspace_variable(&self) -> Option<&str> {
+        None
+    }
+
+    /// Find out the version of the client that reads a config file, if possible
+    ///
+    /// Installs pass it to [`inject_kodegen_with`](Self::inject_kodegen_with) as
+    /// [`ConfigMerger::client_version`], so plugins can write the schema that
+    /// release understands. Most clients don't need it and report none.
+    fn detect_version(&self, _config_path: &Path) -> Option<Version> {
+        None
+    }
+
+    /// Get the transports the client can reach kodegen over
+    ///
+    /// Plugins listing HTTP transports write the matching entry in
+    /// [`inject_kodegen_with`](Self::inject_kodegen_with) when
+    /// [`ConfigMerger::transport`] selects one.
+    fn supported_transports(&self) -> &[TransportPreference] {
+        &[TransportPreference::Stdio]
+    }
+
     /// Check if config indicates client is installed
     fn is_installed(&self, path: &Path) -> bool;
 
-    /// Inject KODEGEN.ᴀɪ into existing config
+    /// Inject KODEGEN.ᴀɪ into existing config using the default merger
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::ParseFailed`] if the config cannot be parsed for
+    /// the given format, or another [`ConfigError`] if injecting fails.
+    fn inject_kodegen(
+        &self,
+        config_content: &str,
+        format: ConfigFormat,
+    ) -> std::result::Result<String, ConfigError> {
+        self.inject_kodegen_with(config_content, format, &ConfigMerger::new())
+            .map_err(|e| ConfigError::classify(e, format))
+    }
+
+    /// Get where in its config the client keeps its servers, and how
+    ///
+    /// The default is the standard `mcpServers` object. Clients that only
+    /// differ in where their servers go (`context_servers`, a named list) say
+    /// so here instead of overriding the injection.
+    fn merge_target(&self) -> MergeTarget {
+        MergeTarget::default()
+    }
+
+    /// Inject KODEGEN.ᴀɪ into existing config using a configured merger
+    ///
+    /// The default merges into the client's [`merge_target`](Self::merge_target);
+    /// plugins with a client-specific schema override this.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config cannot be parsed or serialized for the given format,
+    /// or an unrelated server already uses kodegen's name.
+    fn inject_kodegen_with(
+        &self,
+        config_content: &str,
+        format: ConfigFormat,
+        merger: &ConfigMerger,
+    ) -> Result<String> {
+        Ok(merger.merge_into(config_content, format, &self.merge_target())?)
+    }
+
+    /// Remove KODEGEN.ᴀɪ from existing config, leaving everything else intact
+    ///
+    /// The default removes our entries from the client's
+    /// [`merge_target`](Self::merge_target); plugins with a
+    /// client-specific schema override this along with
+    /// [`inject_kodegen_with`](Self::inject_kodegen_with).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config cannot be parsed or serialized for the given format.
+    fn remove_kodegen(
+        &self,
+        config_content: &str,
+        format: ConfigFormat,
+        merger: &ConfigMerger,
+    ) -> Result<String> {
+        Ok(merger.remove_from(config_content, format, &self.merge_target())?)
+    }
+
+    /// Inject every server of a merger (see [`ConfigMerger::with_additional_servers`])
+    ///
+    /// Each server goes through [`inject_kodegen_with`](Self::inject_kodegen_with)
+    /// in turn, so client-specific schemas get all of them too, and servers
+    /// already present are left as they are.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if injecting any of the servers fails.
+    fn inject_servers(
+        &self,
+        config_content: &str,
+        format: ConfigFormat,
+        merger: &ConfigMerger,
+    ) -> Result<String> {
+        let merged = merger
+            .servers()
+            .try_fold(config_content.to_string(), |content, server| {
+                self.inject_kodegen_with(&content, format, &server)
+            })?;
+        Ok(config::match_line_endings(config_content, &merged))
+    }
+
+    /// Remove every server of a merger, like [`inject_servers`](Self::inject_servers)
     ///
     /// # Errors
     ///
     /// Returns an error if the config cannot be parsed or serialized for the given format.
-    fn inject_kodegen(&self, config_content: &str, format: ConfigFormat) -> Result<String>;
+    fn remove_servers(
+        &self,
+        config_content: &str,
+        format: ConfigFormat,
+        merger: &ConfigMerger,
+    ) -> Result<String> {
+        let removed = merger
+            .servers()
+            .try_fold(config_content.to_string(), |content, server| {
+                self.remove_kodegen(&content, format, &server)
+            })?;
+        Ok(config::match_line_endings(config_content, &removed))
+    }
+
+    /// Check whether a config already holds a server entry of ours
+    ///
+    /// The config is parsed rather than searched for "kodegen", so mentions in
+    /// comments or unrelated paths don't count. The default asks
+    /// [`remove_kodegen`](Self::remove_kodegen) whether it finds anything to
+    /// remove, which covers plugins with a client-specific schema as well.
+    fn is_kodegen_configured(&self, config_content: &str, merger: &ConfigMerger) -> bool {
+        let format = merger.config_format(self.config_format());
+        self.remove_kodegen(config_content, format, merger)
+            .is_ok_and(|updated| updated != config_content)
+    }
 
     /// Get the default config format for this client
     fn config_format(&self) -> ConfigFormat;
+
+    /// Get the key of the row holding the config, for [`ConfigFormat::Sqlite`] clients
+    ///
+    /// The config is the JSON value under this key of the database's
+    /// `ItemTable (key, value)` table, as in VS Code's `state.vscdb`, and is
+    /// merged like a JSON config. Needs the `sqlite` feature.
+    fn sqlite_key(&self) -> Option<&str> {
+        None
+    }
+
+    /// Get the schema the client's configs are checked against (see [`validate`])
+    ///
+    /// Installs refuse to write a merged config with issues the original
+    /// didn't have. Clients without a schema aren't checked.
+    fn schema(&self) -> Option<JsonValue> {
+        None
+    }
 }
 
-#[derive(Debug, Clone)]
+/// A client's config file
+///
+/// Written as text (see [`fmt::Display`]) as `<format>:<path>`, e.g.
+/// `json:/home/me/.cursor/mcp.json`. Parsed text may leave the format out when
+/// the file's extension tells it, and gets the current platform and user scope.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ConfigPath {
     pub path: PathBuf,
     pub format: ConfigFormat,
     pub platform: Platform,
+    #[serde(default)]
+    pub scope: Scope,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Who a config file applies to
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scope {
+    /// The user's own config (or the machine's, for system config paths)
+    #[default]
+    User,
+    /// A project's config, for everyone who opens the workspace directory
+    Workspace(PathBuf),
+}
+
+/// A config file format
+///
+/// Written as text, and serialized, as its lowercase name (`json`, `toml`,
+/// ...) or a custom format's codec name. Custom formats only parse once their
+/// codec is registered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(into = "String")]
 pub enum ConfigFormat {
     Json,
     Toml,
     Yaml,
     Plist,
+    /// JSON kept under a key of a SQLite key-value table (see [`ClientConfigPlugin::sqlite_key`])
+    Sqlite,
+    /// INI, with each server in a `[mcpServers.<name>]` section
+    Ini,
+    /// XML, with each server a `<server name="...">` in an `<mcpServers>` element
+    Xml,
+    /// A format handled by the codec registered under this name (see [`codec::register`])
+    Custom(&'static str),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An operating system a config path applies on, written as `windows`,
+/// `macos`, `linux` or `all`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
 pub enum Platform {
     Windows,
     MacOS,
@@ -63,8 +332,16 @@ pub enum Platform {
 }
 
 impl Platform {
+    /// Get the platform configs are laid out for: this machine's, unless
+    /// [`paths::with_platform`] says otherwise
+    #[must_use]
+    pub fn current() -> Self {
+        paths::platform_override().unwrap_or(Self::native())
+    }
+
+    /// Get the platform this machine runs
     #[must_use]
-    pub const fn current() -> Self {
+    pub const fn native() -> Self {
         #[cfg(target_os = "windows")]
         return Self::Windows;
 
@@ -77,10 +354,179 @@ impl Platform {
         #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
         return Self::All;
     }
+
+    /// Get the platform's name, as written and parsed
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Windows => "windows",
+            Self::MacOS => "macos",
+            Self::Linux => "linux",
+            Self::All => "all",
+        }
+    }
+}
+
+impl ConfigFormat {
+    /// Get the format's name, as written and parsed
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Toml => "toml",
+            Self::Yaml => "yaml",
+            Self::Plist => "plist",
+            Self::Sqlite => "sqlite",
+            Self::Ini => "ini",
+            Self::Xml => "xml",
+            Self::Custom(name) => name,
+        }
+    }
+
+    /// Get the format a file's extension stands for, if any
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "json" | "jsonc" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "plist" => Some(Self::Plist),
+            "vscdb" | "sqlite" => Some(Self::Sqlite),
+            "ini" => Some(Self::Ini),
+            "xml" => Some(Self::Xml),
+            _ => None,
+        }
+    }
+}
+
+/// Text that isn't a [`Platform`], [`ConfigFormat`] or [`ConfigPath`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid {kind} `{text}`")]
+pub struct ParseError {
+    kind: &'static str,
+    text: String,
+}
+
+impl ParseError {
+    fn new(kind: &'static str, text: &str) -> Self {
+        Self {
+            kind,
+            text: text.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Platform {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> std::result::Result<Self, Self::Err> {
+        match text.trim().to_ascii_lowercase().as_str() {
+            "windows" => Ok(Self::Windows),
+            "macos" => Ok(Self::MacOS),
+            "linux" => Ok(Self::Linux),
+            "all" => Ok(Self::All),
+            _ => Err(ParseError::new("platform", text)),
+        }
+    }
+}
+
+impl From<Platform> for String {
+    fn from(platform: Platform) -> Self {
+        platform.as_str().to_string()
+    }
+}
+
+impl TryFrom<String> for Platform {
+    type Error = ParseError;
+
+    fn try_from(text: String) -> std::result::Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ConfigFormat {
+    type Err = ParseError;
+
+    /// Parse a built-in format's name (in any case) or a registered codec's
+    fn from_str(text: &str) -> std::result::Result<Self, Self::Err> {
+        let name = text.trim();
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "toml" => Ok(Self::Toml),
+            "yaml" => Ok(Self::Yaml),
+            "plist" => Ok(Self::Plist),
+            "sqlite" => Ok(Self::Sqlite),
+            "ini" => Ok(Self::Ini),
+            "xml" => Ok(Self::Xml),
+            _ => codec::custom_format(name).ok_or_else(|| ParseError::new("config format", text)),
+        }
+    }
+}
+
+impl From<ConfigFormat> for String {
+    fn from(format: ConfigFormat) -> Self {
+        format.as_str().to_string()
+    }
+}
+
+// Not derived: serde would tie the deserializer's lifetime to `Custom`'s name
+impl<'de> Deserialize<'de> for ConfigFormat {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for ConfigPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.format, self.path.display())
+    }
+}
+
+impl FromStr for ConfigPath {
+    type Err = ParseError;
+
+    /// Parse `<format>:<path>`, or a bare path whose extension tells its format
+    fn from_str(text: &str) -> std::result::Result<Self, Self::Err> {
+        let (format, path) = match text.split_once(':') {
+            Some((format, path)) if !path.is_empty() => match format.parse() {
+                Ok(format) => (Some(format), path),
+                Err(_) => (None, text),
+            },
+            _ => (None, text),
+        };
+        let path = PathBuf::from(path);
+        let format = format
+            .or_else(|| ConfigFormat::from_path(&path))
+            .ok_or_else(|| ParseError::new("config path", text))?;
+
+        Ok(Self {
+            path,
+            format,
+            platform: Platform::current(),
+            scope: Scope::User,
+        })
+    }
 }
 
 /// Standard KODEGEN server configuration
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
 pub struct KodegenConfig {
     pub command: String,
     pub args: Vec<String>,
@@ -99,11 +545,46 @@ impl Default for KodegenConfig {
 }
 
 /// Alternative HTTP-based config for clients that support it
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
 pub struct KodegenHttpConfig {
     #[serde(rename = "type")]
     pub transport_type: String,
     pub url: String,
+    /// HTTP headers sent with every request (e.g. `Authorization`)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Environment variable holding an API key, sent as `Authorization: Bearer <key>`
+    ///
+    /// Configs only reference the variable, so the key itself is never written
+    /// to disk. An `Authorization` entry in `headers` takes precedence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+}
+
+impl KodegenHttpConfig {
+    /// Get the headers to send, including the API key's `Authorization` header
+    ///
+    /// `reference` renders the client's syntax for reading an environment
+    /// variable (e.g. `${env:KODEGEN_API_KEY}` for Cursor).
+    #[must_use]
+    pub fn headers_with(&self, reference: impl FnOnce(&str) -> String) -> BTreeMap<String, String> {
+        let mut headers = self.headers.clone();
+        let has_authorization = headers
+            .keys()
+            .any(|name| name.eq_ignore_ascii_case("authorization"));
+
+        if let Some(var) = &self.api_key_env
+            && !has_authorization
+        {
+            headers.insert(
+                "Authorization".to_string(),
+                format!("Bearer {}", reference(var)),
+            );
+        }
+
+        headers
+    }
 }
 
 impl Default for KodegenHttpConfig {
@@ -111,6 +592,8 @@ impl Default for KodegenHttpConfig {
         Self {
             transport_type: "streamable-http".to_string(),
             url: "https://kodegen.kodegen.dev:8443".to_string(),
+            headers: BTreeMap::new(),
+            api_key_env: None,
         }
     }
 }
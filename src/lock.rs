@@ -0,0 +1,228 @@
+//! Coordination of writes to client config files
+//!
+//! The installer and the watcher both read, back up and rewrite client
+//! configs. Every such update holds a [`ConfigWriteLock`] for the file: a
+//! per-path mutex serializes tasks within this process, and an exclusive lock
+//! on a file in the state directory serializes separate processes (e.g. a
+//! manual install while the daemon is running).
+//!
+//! Editors don't take part in either, and rewrite their settings whenever they
+//! like. So updates are committed with [`commit_if_unchanged`], which locks
+//! the config itself (for editors that do lock while saving) and only writes if
+//! the file still holds what the update was computed from; otherwise the
+//! caller recomputes it, up to [`MAX_UPDATE_ATTEMPTS`] times.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use dashmap::DashMap;
+use log::debug;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+use crate::state::StateDirs;
+use crate::store::ConfigStore;
+
+/// In-process locks, one per config path
+static PATH_LOCKS: LazyLock<DashMap<PathBuf, Arc<Mutex<()>>>> = LazyLock::new(DashMap::new);
+
+/// How often a held lock is retried
+const RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long to wait for another program to release its lock on a config
+const CONFIG_LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How many times an update is recomputed when the config changes underneath it
+pub const MAX_UPDATE_ATTEMPTS: usize = 5;
+
+/// Exclusive right to update a config file, released when dropped
+#[derive(Debug)]
+pub struct ConfigWriteLock {
+    _file: File,
+    _guard: OwnedMutexGuard<()>,
+}
+
+/// Wait for exclusive access to a config file
+///
+/// # Errors
+///
+/// Returns an error if the lock file cannot be created or locked.
+pub async fn lock_config(state_dirs: &StateDirs, config_path: &Path) -> Result<ConfigWriteLock> {
+    let guard = path_mutex(config_path).lock_owned().await;
+
+    let lock_path = state_dirs.lock_path(config_path);
+    let file = tokio::task::spawn_blocking(move || lock_file(&lock_path)).await??;
+
+    Ok(ConfigWriteLock {
+        _file: file,
+        _guard: guard,
+    })
+}
+
+/// Block the current thread until it has exclusive access to a config file
+///
+/// Async code should use [`lock_config`] instead, so waiting doesn't tie up
+/// a runtime worker.
+///
+/// # Errors
+///
+/// Returns an error if the lock file cannot be created or locked.
+pub fn lock_config_blocking(state_dirs: &StateDirs, config_path: &Path) -> Result<ConfigWriteLock> {
+    let mutex = path_mutex(config_path);
+    let guard = loop {
+        if let Ok(guard) = Arc::clone(&mutex).try_lock_owned() {
+            break guard;
+        }
+        std::thread::sleep(RETRY_INTERVAL);
+    };
+
+    let file = lock_file(&state_dirs.lock_path(config_path))?;
+
+    Ok(ConfigWriteLock {
+        _file: file,
+        _guard: guard,
+    })
+}
+
+/// Run `write` only if a config still holds `expected` (`None`: doesn't exist yet)
+///
+/// The check and the write happen under an advisory lock on the config file
+/// itself. Returns `false` without writing when the config has changed since
+/// `expected` was read, so the caller can recompute its update.
+///
+/// Must be called while holding the config's [`ConfigWriteLock`].
+///
+/// # Errors
+///
+/// Returns an error if the config cannot be read, stays locked by another
+/// program, or `write` fails.
+pub(crate) async fn commit_if_unchanged(
+    store: ConfigStore<'_>,
+    expected: Option<&str>,
+    write: impl FnOnce() -> Result<()>,
+) -> Result<bool> {
+    let Some(file) = open_config(store.path())? else {
+        return commit_missing(expected, write);
+    };
+    let Some(expected) = expected else {
+        return Ok(false);
+    };
+
+    let started = Instant::now();
+    while !try_lock_config(&file, store.path(), started)? {
+        tokio::time::sleep(RETRY_INTERVAL).await;
+    }
+
+    commit_locked(store, &file, expected, write)
+}
+
+/// Block the current thread until `write` is committed, like [`commit_if_unchanged`]
+///
+/// # Errors
+///
+/// Returns an error if the config cannot be read, stays locked by another
+/// program, or `write` fails.
+pub(crate) fn commit_if_unchanged_blocking(
+    store: ConfigStore<'_>,
+    expected: Option<&str>,
+    write: impl FnOnce() -> Result<()>,
+) -> Result<bool> {
+    let Some(file) = open_config(store.path())? else {
+        return commit_missing(expected, write);
+    };
+    let Some(expected) = expected else {
+        return Ok(false);
+    };
+
+    let started = Instant::now();
+    while !try_lock_config(&file, store.path(), started)? {
+        std::thread::sleep(RETRY_INTERVAL);
+    }
+
+    commit_locked(store, &file, expected, write)
+}
+
+/// Open a config to lock it, or `None` if it doesn't exist
+fn open_config(config_path: &Path) -> Result<Option<File>> {
+    match File::open(config_path) {
+        Ok(file) => Ok(Some(file)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to open {}", config_path.display())),
+    }
+}
+
+/// Write a config that doesn't exist, if it wasn't expected to
+fn commit_missing(expected: Option<&str>, write: impl FnOnce() -> Result<()>) -> Result<bool> {
+    if expected.is_none() {
+        write().map(|()| true)
+    } else {
+        Ok(false)
+    }
+}
+
+/// Try to lock a config once, returning `false` while another program holds it
+fn try_lock_config(file: &File, config_path: &Path, started: Instant) -> Result<bool> {
+    match file.try_lock() {
+        Ok(()) => Ok(true),
+        Err(TryLockError::WouldBlock) if started.elapsed() < CONFIG_LOCK_TIMEOUT => Ok(false),
+        Err(TryLockError::WouldBlock) => {
+            bail!("{} is locked by another program", config_path.display());
+        }
+        Err(TryLockError::Error(e)) => {
+            // Some filesystems (e.g. network shares) don't support locking
+            debug!("Could not lock {}: {e}", config_path.display());
+            Ok(true)
+        }
+    }
+}
+
+/// Run `write` if the locked config still holds `expected`
+fn commit_locked(
+    store: ConfigStore<'_>,
+    file: &File,
+    expected: &str,
+    write: impl FnOnce() -> Result<()>,
+) -> Result<bool> {
+    let current = store
+        .read_from(file)
+        .with_context(|| format!("Failed to read {}", store.path().display()))?;
+    if current != expected {
+        return Ok(false);
+    }
+
+    write()?;
+    Ok(true)
+}
+
+/// Get the in-process mutex for a config path
+fn path_mutex(config_path: &Path) -> Arc<Mutex<()>> {
+    Arc::clone(&PATH_LOCKS.entry(config_path.to_path_buf()).or_default())
+}
+
+/// Open a lock file and wait for an exclusive lock on it
+pub(crate) fn lock_file(lock_path: &Path) -> Result<File> {
+    if let Some(parent) = lock_path.parent() {
+        std::fs::create_dir_all(parent).context("Failed to create lock directory")?;
+    }
+
+    let file = match OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(lock_path)
+    {
+        Ok(file) => file,
+        // Created by another user (e.g. root during a machine-wide install); reading is enough to lock
+        Err(e) if e.kind() == ErrorKind::PermissionDenied => File::open(lock_path)?,
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to open {}", lock_path.display()));
+        }
+    };
+
+    file.lock()
+        .with_context(|| format!("Failed to lock {}", lock_path.display()))?;
+    Ok(file)
+}
@@ -0,0 +1,191 @@
+//! Desktop notifications about configuration changes
+//!
+//! Enabled with `notifications = true` in settings, so changes made in the
+//! background are visible instead of silent. On Linux, notifications go to the
+//! freedesktop notification service (`org.freedesktop.Notifications`) and
+//! offer an "Undo" action when the change can be reverted. Windows toasts,
+//! with the same action, need the `windows-toast` feature.
+
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::Result;
+use log::debug;
+
+use crate::backup::{self, Undo};
+use crate::{ClientConfigPlugin, Platform};
+
+/// Title shown on every notification
+const TITLE: &str = "KODEGEN.ᴀɪ";
+
+/// Action key of the "Undo" button on Linux notifications and Windows toasts
+const UNDO_ACTION: &str = "undo";
+
+/// Callback run when the user clicks a notification's "Undo" button
+type UndoCallback = Box<dyn FnOnce() + Send>;
+
+/// Tell the user kodegen was added to a client's config
+///
+/// With `undo`, notification services that support actions offer a button
+/// that reverts the change (see [`backup::undo_change`]). Failures are logged
+/// and otherwise ignored; notifications are best-effort.
+pub fn notify_configured(client: &dyn ClientConfigPlugin, config_path: &Path, undo: Option<Undo>) {
+    let body = format!(
+        "Added kodegen to {}. Restart it to load the new MCP server.",
+        client.client_name()
+    );
+    debug!(
+        "Notifying about {} ({})",
+        client.client_name(),
+        config_path.display()
+    );
+
+    let on_undo = undo.map(|undo| {
+        let client_id = client.client_id().to_string();
+        let config_path = config_path.to_path_buf();
+        Box::new(move || {
+            if let Err(e) = backup::undo_change(&client_id, &config_path, &undo) {
+                debug!("Failed to undo change to {}: {e}", config_path.display());
+            }
+        }) as UndoCallback
+    });
+
+    if let Err(e) = send(TITLE, &body, on_undo) {
+        debug!("Failed to show notification: {e}");
+    }
+}
+
+/// Post a notification with the platform's notification service
+fn send(title: &str, body: &str, on_undo: Option<UndoCallback>) -> Result<()> {
+    match Platform::native() {
+        Platform::MacOS => send_macos(title, body),
+        Platform::Linux => send_linux(title, body, on_undo),
+        #[cfg(all(windows, feature = "windows-toast"))]
+        Platform::Windows => send_windows(title, body, on_undo),
+        _ => Ok(()),
+    }
+}
+
+/// Post to the freedesktop notification service through libnotify's `notify-send`
+///
+/// With an undo callback the notification carries an "Undo" action; a
+/// background thread waits for the notification to close and runs the
+/// callback if the action was chosen.
+fn send_linux(title: &str, body: &str, on_undo: Option<UndoCallback>) -> Result<()> {
+    let Some(on_undo) = on_undo else {
+        return run_notify_send(&[title, body]);
+    };
+
+    let action = format!("--action={UNDO_ACTION}=Undo");
+    let child = Command::new("notify-send")
+        .args([
+            &format!("--app-name={TITLE}"),
+            &action,
+            "--wait",
+            title,
+            body,
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let title = title.to_string();
+    let body = body.to_string();
+    std::thread::spawn(move || {
+        let output = match child.wait_with_output() {
+            Ok(output) => output,
+            Err(e) => {
+                debug!("Failed to wait for notification: {e}");
+                return;
+            }
+        };
+
+        if output.status.success() {
+            if String::from_utf8_lossy(&output.stdout).trim() == UNDO_ACTION {
+                on_undo();
+            }
+        } else if let Err(e) = run_notify_send(&[&title, &body]) {
+            // notify-send before libnotify 0.7.10 doesn't know about actions
+            debug!("Failed to show notification: {e}");
+        }
+    });
+
+    Ok(())
+}
+
+/// Show a Windows toast, with an "Undo" button when there's an undo callback
+///
+/// Toasts from unpackaged apps need a registered AppUserModelID, so they are
+/// posted under PowerShell's. The button only works while this process is
+/// still running, which holds for the watcher.
+#[cfg(all(windows, feature = "windows-toast"))]
+fn send_windows(title: &str, body: &str, on_undo: Option<UndoCallback>) -> Result<()> {
+    use tauri_winrt_notification::Toast;
+
+    let mut toast = Toast::new(Toast::POWERSHELL_APP_ID)
+        .title(title)
+        .text1(body);
+
+    if let Some(on_undo) = on_undo {
+        let mut on_undo = Some(on_undo);
+        toast = toast
+            .add_button("Undo", UNDO_ACTION)
+            .on_activated(move |action| {
+                if action.as_deref() == Some(UNDO_ACTION)
+                    && let Some(on_undo) = on_undo.take()
+                {
+                    on_undo();
+                }
+                Ok(())
+            });
+    }
+
+    toast.show()?;
+    Ok(())
+}
+
+/// Run `notify-send` with the given arguments
+fn run_notify_send(args: &[&str]) -> Result<()> {
+    let mut command = Command::new("notify-send");
+    command.arg(format!("--app-name={TITLE}")).args(args);
+    spawn_detached(command)
+}
+
+/// Post to Notification Center through AppleScript
+fn send_macos(title: &str, body: &str) -> Result<()> {
+    let script = format!(
+        "display notification \"{}\" with title \"{}\"",
+        applescript_escape(body),
+        applescript_escape(title)
+    );
+
+    let mut command = Command::new("osascript");
+    command.args(["-e", &script]);
+    spawn_detached(command)
+}
+
+/// Start a notification command without waiting for it
+///
+/// Notifications are sent from the watcher's async tasks, which must not
+/// block; a background thread reaps the process and logs a failed exit.
+fn spawn_detached(mut command: Command) -> Result<()> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    std::thread::spawn(move || match child.wait() {
+        Ok(status) if !status.success() => debug!("{program} exited with {status}"),
+        Ok(_) => {}
+        Err(e) => debug!("Failed to wait for {program}: {e}"),
+    });
+
+    Ok(())
+}
+
+/// Escape a string for use inside an AppleScript string literal
+fn applescript_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,73 @@
+//! Live progress of installs
+//!
+//! GUI installers pass an [`InstallObserver`] to
+//! [`install_all_clients_observed`](crate::install_all_clients_observed) to
+//! show what happens as it happens, instead of scraping the `log` output.
+//! Events are delivered on the installing thread, in order.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::install::MessageCode;
+
+/// Something that happened while installing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum InstallEvent {
+    /// A client was found installed and is about to be configured
+    ClientDetected {
+        client_id: String,
+        client_name: String,
+    },
+    /// A client was left alone (not installed, unsupported, already configured, ...)
+    ClientSkipped {
+        client_id: String,
+        code: MessageCode,
+    },
+    /// A config was backed up before being changed
+    BackupCreated {
+        client_id: String,
+        config_path: PathBuf,
+        backup_path: PathBuf,
+    },
+    /// A config was created or changed
+    ConfigWritten {
+        client_id: String,
+        config_path: PathBuf,
+        code: MessageCode,
+    },
+    /// A config couldn't be updated
+    ConfigFailed {
+        client_id: String,
+        config_path: PathBuf,
+        code: MessageCode,
+        error: String,
+    },
+    /// A config the watcher manages lost its kodegen entry
+    ///
+    /// `reinjected` tells whether it was added back or, with
+    /// [`DriftPolicy::Respect`](crate::watcher::DriftPolicy::Respect), left out.
+    DriftDetected {
+        client_id: String,
+        config_path: PathBuf,
+        reinjected: bool,
+    },
+}
+
+/// Receiver of [`InstallEvent`]s
+///
+/// Implemented for closures, so `&|event: &InstallEvent| ...` works as an observer.
+pub trait InstallObserver: Send + Sync {
+    /// Handle an event; called synchronously, so keep it quick
+    fn on_event(&self, event: &InstallEvent);
+}
+
+impl<F> InstallObserver for F
+where
+    F: Fn(&InstallEvent) + Send + Sync,
+{
+    fn on_event(&self, event: &InstallEvent) {
+        self(event);
+    }
+}
@@ -0,0 +1,330 @@
+//! Shared path construction for client plugins
+//!
+//! Plugins should build their locations from these helpers rather than from
+//! `directories` or hardcoded `.config` joins, so `XDG_*` overrides are
+//! honored the same way for every client.
+
+use std::cell::{Cell, RefCell};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::Platform;
+
+thread_local! {
+    /// Home directory of the user being configured during a machine-wide install
+    static HOME_OVERRIDE: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+
+    /// Platform whose layout is resolved instead of this machine's
+    static PLATFORM_OVERRIDE: Cell<Option<Platform>> = const { Cell::new(None) };
+}
+
+/// Run `f` with every helper resolving paths for the user owning `home`
+///
+/// The current process's `XDG_*` and `%APPDATA%` variables describe the
+/// invoking user, so they are ignored in favor of the platform defaults
+/// below `home`.
+pub fn with_user_home<T>(home: &Path, f: impl FnOnce() -> T) -> T {
+    let previous = HOME_OVERRIDE.with(|cell| cell.replace(Some(home.to_path_buf())));
+    let result = f();
+    HOME_OVERRIDE.with(|cell| *cell.borrow_mut() = previous);
+    result
+}
+
+/// Get the home directory set by [`with_user_home`], if any
+#[must_use]
+pub fn user_home_override() -> Option<PathBuf> {
+    HOME_OVERRIDE.with(|cell| cell.borrow().clone())
+}
+
+/// Run `f` with [`Platform::current`] reporting `platform`, so plugins lay out
+/// their paths as they would there
+///
+/// Used with [`with_user_home`] to find a Windows host's configs from WSL.
+pub fn with_platform<T>(platform: Platform, f: impl FnOnce() -> T) -> T {
+    let previous = PLATFORM_OVERRIDE.with(|cell| cell.replace(Some(platform)));
+    let result = f();
+    PLATFORM_OVERRIDE.with(|cell| cell.set(previous));
+    result
+}
+
+/// Get the platform set by [`with_platform`], if any
+#[must_use]
+pub fn platform_override() -> Option<Platform> {
+    PLATFORM_OVERRIDE.with(Cell::get)
+}
+
+/// Read a path from a variable of the current session (e.g. `CODEX_HOME`)
+///
+/// The environment describes the invoking user's session, not the user a
+/// machine-wide install configures, so it's ignored under [`with_user_home`].
+/// Empty values count as unset.
+#[must_use]
+pub fn session_env_path(var: &str) -> Option<PathBuf> {
+    if user_home_override().is_some() {
+        return None;
+    }
+    std::env::var_os(var)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Refuse a path a machine-wide install must not write inside another user's home
+///
+/// While [`with_user_home`] is active we run with more privileges than the
+/// home's owner, so a path must stay inside that home without passing through
+/// a symlink the user could point at a file they are not allowed to change.
+pub(crate) fn check_user_home_path(path: &Path) -> io::Result<()> {
+    let Some(home) = user_home_override() else {
+        return Ok(());
+    };
+    let refuse = |reason: String| Err(io::Error::new(io::ErrorKind::PermissionDenied, reason));
+
+    let Ok(relative) = path.strip_prefix(&home) else {
+        return refuse(format!("{} is outside {}", path.display(), home.display()));
+    };
+
+    let mut current = home;
+    for component in relative.components() {
+        let Component::Normal(name) = component else {
+            return refuse(format!("{} is not a plain path", path.display()));
+        };
+        current.push(name);
+
+        match std::fs::symlink_metadata(&current) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                return refuse(format!("{} is a symlink", current.display()));
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => break,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Keep the config paths an install may write: the first, primary one and any others that exist
+///
+/// Clients list alternatives (profiles, variants, older locations) after
+/// their primary config; a new config is only ever created at the primary
+/// one, so kodegen doesn't scatter fresh files across all of them.
+pub(crate) fn primary_or_existing(paths: impl IntoIterator<Item = PathBuf>) -> Vec<PathBuf> {
+    paths
+        .into_iter()
+        .enumerate()
+        .filter(|(index, path)| *index == 0 || path.exists())
+        .map(|(_, path)| path)
+        .collect()
+}
+
+/// Get the current user's home directory
+#[must_use]
+pub fn home_dir() -> Option<PathBuf> {
+    user_home_override().or_else(|| {
+        directories::BaseDirs::new().map(|base_dirs| base_dirs.home_dir().to_path_buf())
+    })
+}
+
+/// Enumerate the home directories of all regular users on this machine
+#[must_use]
+pub fn user_homes() -> Vec<PathBuf> {
+    let (root, skip): (PathBuf, &[&str]) = match Platform::native() {
+        Platform::Windows => {
+            let drive = std::env::var("SystemDrive").unwrap_or_else(|_| "C:".to_string());
+            (
+                PathBuf::from(format!("{drive}\\Users")),
+                &["Public", "Default", "Default User", "All Users"],
+            )
+        }
+        Platform::MacOS => (PathBuf::from("/Users"), &["Shared", "Guest"]),
+        Platform::Linux | Platform::All => (PathBuf::from("/home"), &["lost+found"]),
+    };
+
+    let Ok(entries) = std::fs::read_dir(&root) else {
+        return Vec::new();
+    };
+
+    let mut homes: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            !name.starts_with('.') && !skip.contains(&name.as_ref())
+        })
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+
+    homes.sort();
+    homes
+}
+
+/// Get `$XDG_CONFIG_HOME`, falling back to `~/.config`
+///
+/// Used on every Unix platform, including macOS, for clients that keep
+/// dotfile-style config (e.g. Zed, Goose).
+#[must_use]
+pub fn config_home() -> Option<PathBuf> {
+    if let Some(home) = user_home_override() {
+        return Some(home.join(".config"));
+    }
+
+    xdg_dir("XDG_CONFIG_HOME").or_else(|| home_dir().map(|home| home.join(".config")))
+}
+
+/// Get `$XDG_DATA_HOME`, falling back to `~/.local/share`
+#[must_use]
+pub fn data_home() -> Option<PathBuf> {
+    if let Some(home) = user_home_override() {
+        return Some(home.join(".local").join("share"));
+    }
+
+    xdg_dir("XDG_DATA_HOME").or_else(|| home_dir().map(|home| home.join(".local").join("share")))
+}
+
+/// Get the platform's per-user application config root
+///
+/// `%APPDATA%` on Windows, `~/Library/Application Support` on macOS and
+/// the XDG config home on Linux.
+#[must_use]
+pub fn app_config_dir() -> Option<PathBuf> {
+    match Platform::current() {
+        Platform::Windows => match user_home_override() {
+            Some(home) => Some(home.join("AppData").join("Roaming")),
+            None => std::env::var_os("APPDATA").map(PathBuf::from),
+        },
+        Platform::MacOS => home_dir().map(|home| home.join("Library/Application Support")),
+        Platform::Linux => config_home(),
+        Platform::All => None,
+    }
+}
+
+/// Get the platform's per-user application data root
+///
+/// `%LOCALAPPDATA%` on Windows, `~/Library/Application Support` on macOS
+/// and the XDG data home on Linux.
+#[must_use]
+pub fn data_dir() -> Option<PathBuf> {
+    match Platform::current() {
+        Platform::Windows => match user_home_override() {
+            Some(home) => Some(home.join("AppData").join("Local")),
+            None => std::env::var_os("LOCALAPPDATA").map(PathBuf::from),
+        },
+        Platform::MacOS => home_dir().map(|home| home.join("Library/Application Support")),
+        Platform::Linux | Platform::All => data_home(),
+    }
+}
+
+/// Get the platform's machine-wide application config root
+///
+/// `%ProgramData%` on Windows, `/Library/Application Support` on macOS and
+/// `/etc` on Linux.
+#[must_use]
+pub fn system_config_dir() -> Option<PathBuf> {
+    match Platform::current() {
+        Platform::Windows => std::env::var_os("ProgramData").map(PathBuf::from),
+        Platform::MacOS => Some(PathBuf::from("/Library/Application Support")),
+        Platform::Linux => Some(PathBuf::from("/etc")),
+        Platform::All => None,
+    }
+}
+
+/// A Linux sandbox package format a client can be installed with
+///
+/// Sandboxed apps keep their config away from the usual XDG locations, so
+/// plugins list the packages their client ships as and probe those too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sandbox {
+    /// A Flatpak app id (e.g. `com.visualstudio.code`)
+    Flatpak(&'static str),
+    /// A Snap name (e.g. `code`)
+    Snap(&'static str),
+}
+
+impl Sandbox {
+    /// Get the sandbox's per-user directory, if the app has one here
+    ///
+    /// `~/.var/app/<id>` for Flatpak and `~/snap/<name>` for Snap; only
+    /// returned on Linux, once the app has been run.
+    #[must_use]
+    pub fn root(self) -> Option<PathBuf> {
+        if Platform::current() != Platform::Linux {
+            return None;
+        }
+
+        let home = home_dir()?;
+        let root = match self {
+            Self::Flatpak(id) => home.join(".var").join("app").join(id),
+            Self::Snap(name) => home.join("snap").join(name),
+        };
+        root.is_dir().then_some(root)
+    }
+
+    /// Get the home directory the sandboxed app sees
+    ///
+    /// Flatpak apps share the real home (only their XDG directories move);
+    /// Snap apps get `~/snap/<name>/current`.
+    #[must_use]
+    pub fn home(self) -> Option<PathBuf> {
+        let root = self.root()?;
+        match self {
+            Self::Flatpak(_) => home_dir(),
+            Self::Snap(_) => Some(root.join("current")),
+        }
+    }
+
+    /// Get the sandboxed app's `$XDG_CONFIG_HOME`
+    ///
+    /// `~/.var/app/<id>/config` for Flatpak and `~/snap/<name>/current/.config`
+    /// for Snap.
+    #[must_use]
+    pub fn config_home(self) -> Option<PathBuf> {
+        let root = self.root()?;
+        Some(match self {
+            Self::Flatpak(_) => root.join("config"),
+            Self::Snap(_) => root.join("current").join(".config"),
+        })
+    }
+}
+
+/// Get the places a macOS app bundle can be installed, machine-wide or per user
+#[must_use]
+pub fn app_bundle_paths(bundle_name: &str) -> Vec<PathBuf> {
+    std::iter::once(PathBuf::from("/Applications"))
+        .chain(home_dir().map(|home| home.join("Applications")))
+        .map(|applications| applications.join(bundle_name))
+        .collect()
+}
+
+/// Expand a leading `~` in a user-supplied path to the home directory
+#[must_use]
+pub fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Find an executable in the directories listed in `PATH`
+#[must_use]
+pub fn find_on_path(name: &str) -> Option<PathBuf> {
+    let path_var = std::env::var_os("PATH")?;
+
+    std::env::split_paths(&path_var).find_map(|dir| {
+        [
+            name.to_string(),
+            format!("{name}.cmd"),
+            format!("{name}.exe"),
+        ]
+        .into_iter()
+        .map(|candidate| dir.join(candidate))
+        .find(|candidate| candidate.is_file())
+    })
+}
+
+/// Read an XDG base directory variable, ignoring empty or relative values as the spec requires
+fn xdg_dir(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
@@ -0,0 +1,201 @@
+//! Windows' record of installed apps
+//!
+//! Directory checks alone miss per-machine installs that were never started
+//! and mistake folders left behind in the profile for installs. On Windows,
+//! clients naming their app ([`ClientConfigPlugin::windows_app_names`]) are
+//! looked up in the `Uninstall` registry keys and the Start Menu instead.
+
+use std::path::PathBuf;
+
+use crate::{ClientConfigPlugin, Platform, paths};
+
+/// An app listed in the `Uninstall` registry keys
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstalledApp {
+    /// Name shown in the installed apps list (e.g. `Microsoft Visual Studio Code (User)`)
+    pub display_name: String,
+    /// Version recorded by the installer
+    pub display_version: Option<String>,
+    /// Directory the app is installed in
+    pub install_location: Option<PathBuf>,
+    /// Whether it's installed for every user (listed under `HKEY_LOCAL_MACHINE`)
+    pub machine_wide: bool,
+}
+
+/// Get the apps listed in the `Uninstall` registry keys
+///
+/// Reads the machine's 64- and 32-bit keys and, unless another user is being
+/// configured (see [`paths::with_user_home`]), the current user's. Empty on
+/// other platforms.
+#[must_use]
+pub fn installed_apps() -> Vec<InstalledApp> {
+    #[cfg(windows)]
+    {
+        read_uninstall_keys()
+    }
+
+    #[cfg(not(windows))]
+    {
+        Vec::new()
+    }
+}
+
+/// Find an installed app by the names it may be listed under
+///
+/// A listed name matches when it equals one of `names` (ignoring case) or
+/// adds a parenthesized suffix like ` (User)`.
+#[must_use]
+pub fn find_app(names: &[&str]) -> Option<InstalledApp> {
+    if names.is_empty() {
+        return None;
+    }
+
+    installed_apps().into_iter().find(|app| {
+        names
+            .iter()
+            .any(|name| matches_name(&app.display_name, name))
+    })
+}
+
+/// Get the Start Menu `Programs` folders: the user's, then the machine's
+#[must_use]
+pub fn start_menu_dirs() -> Vec<PathBuf> {
+    if Platform::native() != Platform::Windows {
+        return Vec::new();
+    }
+
+    let programs = ["Microsoft", "Windows", "Start Menu", "Programs"];
+    paths::app_config_dir()
+        .into_iter()
+        .chain(std::env::var_os("ProgramData").map(PathBuf::from))
+        .map(|root| programs.iter().fold(root, |dir, name| dir.join(name)))
+        .collect()
+}
+
+/// Check whether Windows lists a client as installed
+///
+/// Returns `None` where that can't be told: on other platforms, for clients
+/// naming no app, when the registry can't be read, and for another user's
+/// per-user installs (their registry hive isn't loaded).
+#[must_use]
+pub fn is_client_registered(client: &dyn ClientConfigPlugin) -> Option<bool> {
+    let names = client.windows_app_names();
+    if Platform::native() != Platform::Windows || names.is_empty() {
+        return None;
+    }
+
+    let apps = installed_apps();
+    let listed = apps.iter().any(|app| {
+        names
+            .iter()
+            .any(|name| matches_name(&app.display_name, name))
+    });
+    if listed || has_start_menu_shortcut(names) {
+        return Some(true);
+    }
+
+    if apps.is_empty() || paths::user_home_override().is_some() {
+        return None;
+    }
+    Some(false)
+}
+
+/// Check whether an `Uninstall` entry's name is one of an app's names
+fn matches_name(display_name: &str, name: &str) -> bool {
+    let display_name = display_name.to_lowercase();
+    let name = name.to_lowercase();
+    display_name
+        .strip_prefix(&name)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(" ("))
+}
+
+/// Check for a shortcut named after the app in the Start Menu or one of its folders
+fn has_start_menu_shortcut(names: &[&str]) -> bool {
+    start_menu_dirs().into_iter().any(|programs| {
+        let folders = std::fs::read_dir(&programs)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir());
+
+        std::iter::once(programs.clone())
+            .chain(folders)
+            .any(|folder| {
+                names
+                    .iter()
+                    .any(|name| folder.join(format!("{name}.lnk")).is_file())
+            })
+    })
+}
+
+/// Read every entry with a display name from the `Uninstall` keys
+#[cfg(windows)]
+fn read_uninstall_keys() -> Vec<InstalledApp> {
+    use winreg::RegKey;
+    use winreg::enums::{
+        HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, KEY_READ, KEY_WOW64_32KEY, KEY_WOW64_64KEY,
+    };
+
+    const UNINSTALL: &str = r"SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall";
+
+    let mut hives = vec![
+        (HKEY_LOCAL_MACHINE, KEY_WOW64_64KEY, true),
+        (HKEY_LOCAL_MACHINE, KEY_WOW64_32KEY, true),
+    ];
+    // The current user's hive says nothing about the user being configured
+    if paths::user_home_override().is_none() {
+        hives.push((HKEY_CURRENT_USER, 0, false));
+    }
+
+    let mut apps = Vec::new();
+    for (hive, view, machine_wide) in hives {
+        let Ok(uninstall) = RegKey::predef(hive).open_subkey_with_flags(UNINSTALL, KEY_READ | view)
+        else {
+            continue;
+        };
+
+        for name in uninstall.enum_keys().flatten() {
+            let Ok(key) = uninstall.open_subkey_with_flags(&name, KEY_READ | view) else {
+                continue;
+            };
+            let Ok(display_name) = key.get_value::<String, _>("DisplayName") else {
+                continue;
+            };
+
+            let value = |name: &str| {
+                key.get_value::<String, _>(name)
+                    .ok()
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty())
+            };
+            // Installers without `InstallLocation` still point `DisplayIcon` at the executable
+            let install_location = value("InstallLocation")
+                .map(|location| PathBuf::from(location.trim_matches('"')))
+                .or_else(|| value("DisplayIcon").and_then(|icon| icon_dir(&icon)));
+
+            if !apps
+                .iter()
+                .any(|app: &InstalledApp| app.display_name == display_name)
+            {
+                apps.push(InstalledApp {
+                    display_name,
+                    display_version: value("DisplayVersion"),
+                    install_location,
+                    machine_wide,
+                });
+            }
+        }
+    }
+
+    apps
+}
+
+/// Get the directory of the executable a `DisplayIcon` value (`"C:\...\app.exe",0`) names
+#[cfg(windows)]
+fn icon_dir(icon: &str) -> Option<PathBuf> {
+    let path = icon.rsplit_once(',').map_or(icon, |(path, _)| path);
+    PathBuf::from(path.trim().trim_matches('"'))
+        .parent()
+        .map(std::path::Path::to_path_buf)
+}
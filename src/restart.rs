@@ -0,0 +1,321 @@
+//! Restarting clients after their config changed
+//!
+//! Most clients only read MCP config at startup. This opt-in step finds
+//! running clients whose config was just written and, after the embedding
+//! application confirms, quits them gracefully and launches them again.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use log::{debug, info, warn};
+
+use crate::install::InstallResult;
+use crate::{ClientConfigPlugin, Platform};
+
+/// How long to wait for a client to exit after asking it to quit
+const QUIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A running client process
+#[derive(Debug, Clone)]
+pub struct RunningClient {
+    pub client_id: String,
+    pub client_name: String,
+    pub pid: u32,
+    /// Name the process runs under (e.g. "Cursor")
+    pub process_name: String,
+    pub executable: Option<PathBuf>,
+}
+
+/// What happened when restarting a client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RestartOutcome {
+    Restarted,
+    /// The confirmation hook declined the restart
+    Declined,
+    Failed(String),
+}
+
+/// Find running clients whose config was changed by an install
+#[must_use]
+pub fn running_configured_clients(results: &[InstallResult]) -> Vec<RunningClient> {
+    let clients = crate::clients::registry();
+    let processes = list_processes();
+
+    results
+        .iter()
+        .filter(|result| result.config_changed())
+        .filter_map(|result| clients.get(&result.client_id))
+        .filter_map(|client| matching_process(client.as_ref(), &processes))
+        .collect()
+}
+
+/// Restart running clients whose config was changed, asking `confirm` before each one
+///
+/// `confirm` is the embedding application's hook (e.g. a dialog); returning
+/// `false` leaves that client running.
+pub fn restart_configured_clients<F>(
+    results: &[InstallResult],
+    mut confirm: F,
+) -> Vec<(RunningClient, RestartOutcome)>
+where
+    F: FnMut(&RunningClient) -> bool,
+{
+    running_configured_clients(results)
+        .into_iter()
+        .map(|running| {
+            if !confirm(&running) {
+                debug!("Restart of {} declined", running.client_name);
+                return (running, RestartOutcome::Declined);
+            }
+
+            let outcome = match restart(&running) {
+                Ok(()) => {
+                    info!("🔁 Restarted {}", running.client_name);
+                    RestartOutcome::Restarted
+                }
+                Err(e) => {
+                    warn!("Failed to restart {}: {e}", running.client_name);
+                    RestartOutcome::Failed(e.to_string())
+                }
+            };
+
+            (running, outcome)
+        })
+        .collect()
+}
+
+/// Running processes of the current user as `(pid, name, executable)`
+///
+/// Other users' clients are never touched, even when running elevated (e.g.
+/// after a machine-wide install).
+fn list_processes() -> Vec<(u32, String, Option<PathBuf>)> {
+    match Platform::native() {
+        Platform::Linux => list_processes_linux(),
+        Platform::MacOS => list_processes_ps(),
+        Platform::Windows => list_processes_windows(),
+        Platform::All => Vec::new(),
+    }
+}
+
+#[cfg(unix)]
+fn list_processes_linux() -> Vec<(u32, String, Option<PathBuf>)> {
+    use std::os::unix::fs::MetadataExt;
+
+    // A process's directory is owned by its effective user
+    let Ok(own_uid) = std::fs::metadata("/proc/self").map(|metadata| metadata.uid()) else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .metadata()
+                .is_ok_and(|metadata| metadata.uid() == own_uid)
+        })
+        .filter_map(|entry| {
+            let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+            let name = std::fs::read_to_string(entry.path().join("comm")).ok()?;
+            let executable = std::fs::read_link(entry.path().join("exe")).ok();
+            Some((pid, name.trim().to_string(), executable))
+        })
+        .collect()
+}
+
+#[cfg(not(unix))]
+fn list_processes_linux() -> Vec<(u32, String, Option<PathBuf>)> {
+    Vec::new()
+}
+
+fn list_processes_ps() -> Vec<(u32, String, Option<PathBuf>)> {
+    let Some(uid) = Command::new("id")
+        .arg("-u")
+        .output()
+        .ok()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|uid| !uid.is_empty())
+    else {
+        return Vec::new();
+    };
+    let Ok(output) = Command::new("ps")
+        .args(["-x", "-U", &uid, "-o", "pid=,comm="])
+        .output()
+    else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (pid, command) = line.trim().split_once(char::is_whitespace)?;
+            let executable = PathBuf::from(command.trim());
+            let name = executable.file_name()?.to_string_lossy().into_owned();
+            Some((pid.parse().ok()?, name, Some(executable)))
+        })
+        .collect()
+}
+
+fn list_processes_windows() -> Vec<(u32, String, Option<PathBuf>)> {
+    let mut command = Command::new("tasklist");
+    command.args(["/FO", "CSV", "/NH"]);
+    if let Ok(user) = std::env::var("USERNAME") {
+        command.args(["/FI", &format!("USERNAME eq {user}")]);
+    }
+    let Ok(output) = command.output() else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split("\",\"").map(|field| field.trim_matches('"'));
+            let name = fields.next()?.trim_end_matches(".exe").to_string();
+            let pid = fields.next()?.parse().ok()?;
+            Some((pid, name, None))
+        })
+        .collect()
+}
+
+/// Find a client's main process (helpers often share its name, so take the oldest pid)
+fn matching_process(
+    client: &dyn ClientConfigPlugin,
+    processes: &[(u32, String, Option<PathBuf>)],
+) -> Option<RunningClient> {
+    let names = client.process_names();
+
+    processes
+        .iter()
+        .filter(|(_, name, _)| names.iter().any(|candidate| candidate == name))
+        .min_by_key(|(pid, _, _)| *pid)
+        .map(|(pid, name, executable)| RunningClient {
+            client_id: client.client_id().to_string(),
+            client_name: client.client_name().to_string(),
+            pid: *pid,
+            process_name: name.clone(),
+            executable: executable.clone(),
+        })
+}
+
+/// Quit a client gracefully, wait for it to exit, then launch it again
+fn restart(running: &RunningClient) -> Result<()> {
+    let executable = match (&running.executable, Platform::native()) {
+        (Some(executable), _) => Some(executable.clone()),
+        (None, Platform::Windows) => windows_executable(running.pid),
+        (None, _) => None,
+    };
+
+    quit(running)?;
+
+    let deadline = Instant::now() + QUIT_TIMEOUT;
+    while is_running(running.pid) {
+        if Instant::now() > deadline {
+            bail!(
+                "{} did not quit within {QUIT_TIMEOUT:?}",
+                running.client_name
+            );
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+
+    launch(running, executable)
+}
+
+fn quit(running: &RunningClient) -> Result<()> {
+    let pid = running.pid.to_string();
+    let status = match Platform::native() {
+        // Ask the app to quit so it can save state, like choosing Quit from the menu
+        Platform::MacOS => Command::new("osascript")
+            .args([
+                "-e",
+                &format!("tell application \"{}\" to quit", app_name(running)),
+            ])
+            .status(),
+        // Without /F, taskkill asks the window to close
+        Platform::Windows => Command::new("taskkill").args(["/PID", &pid]).status(),
+        Platform::Linux | Platform::All => Command::new("kill").args(["-TERM", &pid]).status(),
+    }
+    .context("Failed to ask client to quit")?;
+
+    if !status.success() {
+        bail!("quit command exited with {status}");
+    }
+
+    Ok(())
+}
+
+fn is_running(pid: u32) -> bool {
+    match Platform::native() {
+        Platform::Linux => PathBuf::from(format!("/proc/{pid}")).exists(),
+        Platform::Windows => list_processes_windows()
+            .iter()
+            .any(|(running, _, _)| *running == pid),
+        Platform::MacOS | Platform::All => Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success()),
+    }
+}
+
+fn launch(running: &RunningClient, executable: Option<PathBuf>) -> Result<()> {
+    let mut command = if Platform::native() == Platform::MacOS {
+        let mut command = Command::new("open");
+        command.args(["-a", &app_name(running)]);
+        command
+    } else {
+        let Some(executable) = executable else {
+            bail!(
+                "could not determine how to relaunch {}",
+                running.client_name
+            );
+        };
+        Command::new(executable)
+    };
+
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to relaunch client")?;
+
+    Ok(())
+}
+
+/// Get the name of the macOS app a client runs from (e.g. "Visual Studio Code" for `Code`)
+///
+/// That's the `.app` bundle the executable sits in; the process name is a
+/// fallback for executables outside a bundle.
+fn app_name(running: &RunningClient) -> String {
+    running
+        .executable
+        .as_deref()
+        .into_iter()
+        .flat_map(Path::ancestors)
+        .find(|dir| dir.extension().is_some_and(|extension| extension == "app"))
+        .and_then(Path::file_stem)
+        .map_or_else(
+            || running.process_name.clone(),
+            |name| name.to_string_lossy().into_owned(),
+        )
+}
+
+/// Look up a Windows process's executable path, which `tasklist` doesn't report
+fn windows_executable(pid: u32) -> Option<PathBuf> {
+    let output = Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-Command",
+            &format!("(Get-Process -Id {pid}).Path"),
+        ])
+        .output()
+        .ok()?;
+
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!path.is_empty()).then(|| PathBuf::from(path))
+}
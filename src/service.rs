@@ -0,0 +1,336 @@
+//! Running the watcher at login
+//!
+//! [`install_service`] registers a program that runs the watcher (usually the
+//! bundler's own binary) with the platform's per-user service manager: a
+//! LaunchAgent on macOS, a systemd user unit on Linux and a scheduled task
+//! started at logon on Windows. It's started right away and again at every
+//! login until [`uninstall_service`] removes it. No elevation is needed.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+use log::{debug, info};
+
+use crate::{Platform, paths};
+
+/// Name the service is registered under (the LaunchAgent label, systemd unit and task name)
+pub const SERVICE_NAME: &str = "ai.kodegen.autoconfig";
+
+/// Program the service runs and how
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceSpec {
+    /// Executable that runs the watcher
+    pub program: PathBuf,
+    /// Arguments passed to the program
+    pub args: Vec<String>,
+}
+
+impl ServiceSpec {
+    /// Run `program` without arguments
+    #[must_use]
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+        }
+    }
+
+    /// Run the current executable
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path of the current executable can't be determined.
+    pub fn current_exe() -> Result<Self> {
+        Ok(Self::new(
+            std::env::current_exe().context("Failed to locate the current executable")?,
+        ))
+    }
+
+    /// Pass arguments to the program (e.g. the subcommand that starts watching)
+    #[must_use]
+    pub fn with_args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Render the service definition for a platform
+    ///
+    /// This is the LaunchAgent plist on macOS, the unit file on Linux and the
+    /// command line of the scheduled task on Windows.
+    #[must_use]
+    pub fn render(&self, platform: Platform) -> String {
+        match platform {
+            Platform::MacOS => self.launch_agent(),
+            Platform::Windows => self.task_command(),
+            Platform::Linux | Platform::All => self.systemd_unit(),
+        }
+    }
+
+    /// LaunchAgent keeping the watcher running for the logged-in user
+    fn launch_agent(&self) -> String {
+        let arguments: String = std::iter::once(self.program.display().to_string())
+            .chain(self.args.iter().cloned())
+            .map(|arg| format!("        <string>{}</string>\n", xml_escape(&arg)))
+            .collect();
+
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{SERVICE_NAME}</string>
+    <key>ProgramArguments</key>
+    <array>
+{arguments}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <dict>
+        <key>SuccessfulExit</key>
+        <false/>
+    </dict>
+    <key>ProcessType</key>
+    <string>Background</string>
+</dict>
+</plist>
+"#
+        )
+    }
+
+    /// systemd user unit, restarted if the watcher fails
+    fn systemd_unit(&self) -> String {
+        let exec_start: Vec<String> = std::iter::once(self.program.display().to_string())
+            .chain(self.args.iter().cloned())
+            .map(|arg| systemd_quote(&arg))
+            .collect();
+
+        format!(
+            "[Unit]
+Description=KODEGEN.ᴀɪ MCP client auto-configuration
+
+[Service]
+ExecStart={}
+Restart=on-failure
+RestartSec=5
+
+[Install]
+WantedBy=default.target
+",
+            exec_start.join(" ")
+        )
+    }
+
+    /// Command line of the scheduled task, quoted for `CreateProcess`
+    fn task_command(&self) -> String {
+        std::iter::once(self.program.display().to_string())
+            .chain(self.args.iter().cloned())
+            .map(|arg| windows_quote(&arg))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Get the file the service definition is written to, if the platform uses one
+///
+/// Windows keeps scheduled tasks in the Task Scheduler instead.
+#[must_use]
+pub fn service_path() -> Option<PathBuf> {
+    match Platform::native() {
+        Platform::MacOS => paths::home_dir().map(|home| {
+            home.join("Library")
+                .join("LaunchAgents")
+                .join(format!("{SERVICE_NAME}.plist"))
+        }),
+        Platform::Windows => None,
+        Platform::Linux | Platform::All => paths::config_home().map(|config_home| {
+            config_home
+                .join("systemd")
+                .join("user")
+                .join(systemd_unit_name())
+        }),
+    }
+}
+
+/// Register the watcher with the service manager and start it
+///
+/// An existing registration is replaced, so this also updates the program or
+/// its arguments.
+///
+/// # Errors
+///
+/// Returns an error if the service definition can't be written or the
+/// service manager rejects it.
+pub fn install_service(spec: &ServiceSpec) -> Result<()> {
+    let definition = spec.render(Platform::native());
+
+    match Platform::native() {
+        Platform::Windows => {
+            run(Command::new("schtasks").args([
+                "/Create",
+                "/F",
+                "/SC",
+                "ONLOGON",
+                "/RL",
+                "LIMITED",
+                "/TN",
+                SERVICE_NAME,
+                "/TR",
+                &definition,
+            ]))?;
+            // Logon triggers only fire at the next login
+            run(Command::new("schtasks").args(["/Run", "/TN", SERVICE_NAME]))?;
+        }
+        platform => {
+            let path = service_path().context("Failed to determine the home directory")?;
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+
+            if platform == Platform::MacOS && path.exists() {
+                // Reloading is the only way to have launchd pick up changes
+                let _ = run(Command::new("launchctl").arg("unload").arg(&path));
+            }
+            std::fs::write(&path, definition)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+
+            if platform == Platform::MacOS {
+                run(Command::new("launchctl").args(["load", "-w"]).arg(&path))?;
+            } else {
+                run(Command::new("systemctl").args(["--user", "daemon-reload"]))?;
+                run(Command::new("systemctl").args([
+                    "--user",
+                    "enable",
+                    "--now",
+                    &systemd_unit_name(),
+                ]))?;
+            }
+            debug!("Service definition written to {}", path.display());
+        }
+    }
+
+    info!("✅ Installed the autoconfig service ({SERVICE_NAME})");
+    Ok(())
+}
+
+/// Stop the watcher service and remove its registration
+///
+/// Returns `false` if no service was installed.
+///
+/// # Errors
+///
+/// Returns an error if the service manager fails to remove the service.
+pub fn uninstall_service() -> Result<bool> {
+    match Platform::native() {
+        Platform::Windows => {
+            let exists = Command::new("schtasks")
+                .args(["/Query", "/TN", SERVICE_NAME])
+                .output()
+                .is_ok_and(|output| output.status.success());
+            if !exists {
+                return Ok(false);
+            }
+
+            let _ = run(Command::new("schtasks").args(["/End", "/TN", SERVICE_NAME]));
+            run(Command::new("schtasks").args(["/Delete", "/F", "/TN", SERVICE_NAME]))?;
+        }
+        platform => {
+            let Some(path) = service_path().filter(|path| path.exists()) else {
+                return Ok(false);
+            };
+
+            if platform == Platform::MacOS {
+                run(Command::new("launchctl").args(["unload", "-w"]).arg(&path))?;
+                std::fs::remove_file(&path)?;
+            } else {
+                run(Command::new("systemctl").args([
+                    "--user",
+                    "disable",
+                    "--now",
+                    &systemd_unit_name(),
+                ]))?;
+                std::fs::remove_file(&path)?;
+                run(Command::new("systemctl").args(["--user", "daemon-reload"]))?;
+            }
+        }
+    }
+
+    info!("🗑️  Removed the autoconfig service ({SERVICE_NAME})");
+    Ok(true)
+}
+
+/// Name of the systemd unit file
+fn systemd_unit_name() -> String {
+    format!("{SERVICE_NAME}.service")
+}
+
+/// Run a service manager command, failing with its output if it fails
+fn run(command: &mut Command) -> Result<()> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let output = command
+        .output()
+        .with_context(|| format!("Failed to run {program}"))?;
+
+    if !output.status.success() {
+        bail!(
+            "{program} exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Escape text for a plist `<string>`
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Quote an `ExecStart=` word when it contains spaces or special characters
+fn systemd_quote(arg: &str) -> String {
+    let escaped = arg.replace('%', "%%");
+    if !arg.is_empty()
+        && !arg
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '\\' | ';' | '$'))
+    {
+        return escaped;
+    }
+
+    let escaped = escaped
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "$$");
+    format!("\"{escaped}\"")
+}
+
+/// Quote a Windows command line argument when it contains spaces or quotes
+fn windows_quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.chars().any(|c| c.is_whitespace() || c == '"') {
+        return arg.to_string();
+    }
+
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            }
+            c => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                quoted.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,274 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::binary::BinaryCheck;
+use crate::config::{ConfigMerger, ConflictResolution, TransportPreference};
+use crate::template::TemplateVars;
+use crate::watcher::WatcherConfig;
+use crate::{
+    ClientConfigPlugin, ConfigPath, KodegenConfig, KodegenHttpConfig, Platform, Scope, paths,
+    registry,
+};
+
+/// User settings loaded from `autoconfig.toml` in kodegen's config directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Directory for backups, journals, state hashes and logs
+    ///
+    /// Overridden by the `KODEGEN_AUTOCONFIG_HOME` environment variable.
+    pub home: Option<PathBuf>,
+
+    /// Canonical kodegen server definition injected into every client
+    ///
+    /// When this changes, the watcher updates every managed client entry.
+    pub server: KodegenConfig,
+
+    /// Transport clients use to reach kodegen, where they support it
+    pub transport: TransportPreference,
+
+    /// Endpoint injected for the HTTP transports
+    pub http: KodegenHttpConfig,
+
+    /// Key the server entry is injected under (defaults to `kodegen`)
+    pub server_name: Option<String>,
+
+    /// Further kodegen-provided servers injected next to `server`, keyed by entry name
+    /// (e.g. `[servers.kodegen-github]`)
+    pub servers: BTreeMap<String, KodegenConfig>,
+
+    /// Show a desktop notification whenever a client config is changed
+    pub notifications: bool,
+
+    /// How to handle an unrelated server that already uses kodegen's name
+    pub conflict_resolution: ConflictResolution,
+
+    /// Whether configs are still written when the server's command can't be run
+    pub binary_check: BinaryCheck,
+
+    /// Number of backups kept per config file (defaults to 10)
+    pub backup_retention: Option<usize>,
+
+    /// How the watcher reacts to config changes
+    pub watcher: WatcherConfig,
+
+    /// Per-client settings keyed by client id (e.g. `[clients.claude-desktop]`)
+    pub clients: HashMap<String, ClientSettings>,
+
+    /// Named variants of the server definition (e.g. `[profiles.work]`)
+    pub profiles: BTreeMap<String, Profile>,
+
+    /// Profile applied on load, unless an install asks for another one
+    pub profile: Option<String>,
+}
+
+/// A named variant of the server definition, e.g. a company endpoint
+///
+/// What a profile sets replaces the top-level setting of the same name;
+/// what it leaves out is taken from there.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    /// Server definition (binary path, args, env) replacing `server`
+    pub server: Option<KodegenConfig>,
+
+    /// Transport replacing `transport`
+    pub transport: Option<TransportPreference>,
+
+    /// Endpoint replacing `http`
+    pub http: Option<KodegenHttpConfig>,
+}
+
+/// Settings for a single client
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClientSettings {
+    /// Extra config files to manage in addition to the plugin's built-in locations
+    ///
+    /// A leading `~` is expanded to the home directory.
+    pub config_paths: Vec<PathBuf>,
+}
+
+impl Settings {
+    /// Get the location of the settings file
+    #[must_use]
+    pub fn path() -> Option<PathBuf> {
+        paths::config_home().map(|config_home| config_home.join("kodegen").join("autoconfig.toml"))
+    }
+
+    /// Get the location of the opt-out marker that excludes a user from machine-wide installs
+    #[must_use]
+    pub fn opt_out_marker_path() -> Option<PathBuf> {
+        paths::config_home()
+            .map(|config_home| config_home.join("kodegen").join("autoconfig.opt-out"))
+    }
+
+    /// Check whether the user has opted out of machine-wide installs
+    #[must_use]
+    pub fn is_opted_out() -> bool {
+        Self::opt_out_marker_path().is_some_and(|marker| marker.exists())
+    }
+
+    /// Load settings, falling back to defaults when the file doesn't exist
+    ///
+    /// The default `profile`, if one is set, is applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings file exists but cannot be read or
+    /// parsed, or names a profile it doesn't define.
+    pub fn load() -> Result<Self> {
+        Self::load_with_profile(None)
+    }
+
+    /// Load settings with the named profile applied instead of the default one
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings file exists but cannot be read or
+    /// parsed, or the profile isn't defined in it.
+    pub fn load_with_profile(profile: Option<&str>) -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return match profile {
+                Some(name) => Self::default().with_profile(name),
+                None => Ok(Self::default()),
+            };
+        };
+
+        let settings: Self = match std::fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content)
+                .with_context(|| format!("Failed to parse settings at {}", path.display()))?,
+            Err(e) if e.kind() == ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                return Err(e).context(format!("Failed to read settings at {}", path.display()));
+            }
+        };
+
+        match profile
+            .map(str::to_string)
+            .or_else(|| settings.profile.clone())
+        {
+            Some(name) => settings
+                .with_profile(&name)
+                .with_context(|| format!("Invalid settings at {}", path.display())),
+            None => Ok(settings),
+        }
+    }
+
+    /// Apply the named profile over the top-level server definition
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there's no such profile.
+    pub fn with_profile(mut self, name: &str) -> Result<Self> {
+        let Some(profile) = self.profiles.get(name).cloned() else {
+            bail!("Unknown profile `{name}`");
+        };
+
+        if let Some(server) = profile.server {
+            self.server = server;
+        }
+        if let Some(transport) = profile.transport {
+            self.transport = transport;
+        }
+        if let Some(http) = profile.http {
+            self.http = http;
+        }
+        self.profile = Some(name.to_string());
+        Ok(self)
+    }
+
+    /// Build a config merger honoring these settings
+    #[must_use]
+    pub fn merger(&self) -> ConfigMerger {
+        self.merger_with(&TemplateVars::current())
+    }
+
+    /// Build a config merger honoring these settings, with the given placeholder values
+    #[must_use]
+    pub fn merger_with(&self, vars: &TemplateVars) -> ConfigMerger {
+        let merger = ConfigMerger::new()
+            .with_config(self.server.clone())
+            .with_http_config(self.http.clone())
+            .with_transport(self.transport)
+            .with_additional_servers(self.servers.clone())
+            .with_template_vars(vars)
+            .with_conflict_resolution(self.conflict_resolution);
+
+        match &self.server_name {
+            Some(server_name) => merger.with_server_name(server_name),
+            None => merger,
+        }
+    }
+
+    /// Build a config merger for one client, falling back to stdio if it can't use the transport
+    #[must_use]
+    pub fn merger_for(&self, client: &dyn ClientConfigPlugin) -> ConfigMerger {
+        self.merger_with(&TemplateVars::for_client(client))
+            .for_client(client)
+    }
+
+    /// Get the number of backups to keep per config file
+    #[must_use]
+    pub fn backup_retention(&self) -> usize {
+        self.backup_retention
+            .unwrap_or(crate::backup::DEFAULT_RETENTION)
+    }
+
+    /// Get the user-declared config paths for a client
+    #[must_use]
+    pub fn config_path_overrides(&self, client: &dyn ClientConfigPlugin) -> Vec<ConfigPath> {
+        self.clients
+            .get(client.client_id())
+            .map(|client_settings| client_settings.config_paths.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|path| ConfigPath {
+                path: paths::expand_home(path),
+                format: client.config_format(),
+                platform: Platform::current(),
+                scope: Scope::User,
+            })
+            .collect()
+    }
+
+    /// Get a client's config paths: user overrides first, then the plugin's defaults
+    #[must_use]
+    pub fn config_paths_for(&self, client: &dyn ClientConfigPlugin) -> Vec<ConfigPath> {
+        let mut configs = self.config_path_overrides(client);
+
+        for config in client.config_paths() {
+            if !configs.iter().any(|existing| existing.path == config.path) {
+                configs.push(config);
+            }
+        }
+
+        configs
+    }
+
+    /// Check whether a client is installed, either by plugin detection or
+    /// because a declared config path's directory exists
+    ///
+    /// On Windows, clients naming their app are judged by the installed apps
+    /// list for folders in the user's profile (see [`registry`]), as those
+    /// outlive uninstalls and don't exist before a first run.
+    #[must_use]
+    pub fn is_client_installed(&self, client: &dyn ClientConfigPlugin) -> bool {
+        let registered = registry::is_client_registered(client);
+        let home = paths::home_dir();
+
+        client.watch_paths().iter().any(|path| match registered {
+            Some(registered) if home.as_ref().is_some_and(|home| path.starts_with(home)) => {
+                registered
+            }
+            _ => client.is_installed(path),
+        }) || self
+            .config_path_overrides(client)
+            .iter()
+            .any(|config| config.path.parent().is_some_and(Path::is_dir))
+    }
+}
@@ -0,0 +1,145 @@
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+
+use crate::paths;
+use crate::settings::Settings;
+
+/// Environment variable overriding where autoconfig keeps its own files
+pub const HOME_ENV: &str = "KODEGEN_AUTOCONFIG_HOME";
+
+/// Layout of the directory holding autoconfig's backups, journals, state hashes, logs and locks
+///
+/// Keeping these in one place (instead of next to each client config) lets
+/// users with synced or read-only config directories relocate them.
+#[derive(Debug, Clone)]
+pub struct StateDirs {
+    root: PathBuf,
+}
+
+impl StateDirs {
+    /// Create a layout rooted at an explicit directory
+    #[must_use]
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Resolve the root from `KODEGEN_AUTOCONFIG_HOME`, then settings, then the platform default
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings file is invalid or no default location can be determined.
+    pub fn resolve() -> Result<Self> {
+        if let Some(home) = paths::session_env_path(HOME_ENV) {
+            return Ok(Self::new(home));
+        }
+
+        if let Some(home) = Settings::load()?.home {
+            return Ok(Self::new(home));
+        }
+
+        paths::data_dir()
+            .map(|data_dir| Self::new(data_dir.join("kodegen").join("autoconfig")))
+            .ok_or_else(|| anyhow!("Could not determine a directory for autoconfig state"))
+    }
+
+    /// Root directory of the layout
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Directory holding backups of client configs
+    #[must_use]
+    pub fn backups_dir(&self) -> PathBuf {
+        self.root.join("backups")
+    }
+
+    /// Directory holding the change journal
+    #[must_use]
+    pub fn journal_dir(&self) -> PathBuf {
+        self.root.join("journal")
+    }
+
+    /// Directory holding hashes of the last written config contents
+    #[must_use]
+    pub fn hashes_dir(&self) -> PathBuf {
+        self.root.join("hashes")
+    }
+
+    /// Directory holding autoconfig's logs
+    #[must_use]
+    pub fn logs_dir(&self) -> PathBuf {
+        self.root.join("logs")
+    }
+
+    /// Directory holding the lock files that serialize config writes
+    #[must_use]
+    pub fn locks_dir(&self) -> PathBuf {
+        self.root.join("locks")
+    }
+
+    /// Lock file serializing updates to the journal
+    #[must_use]
+    pub fn journal_lock_path(&self) -> PathBuf {
+        self.locks_dir().join("journal.lock")
+    }
+
+    /// Socket a running [`Daemon`](crate::daemon::Daemon) accepts commands on
+    #[must_use]
+    pub fn daemon_socket_path(&self) -> PathBuf {
+        self.root.join("daemon.sock")
+    }
+
+    /// Get the location of a backup of a client's config file
+    ///
+    /// The config's absolute path is mirrored below the client's backup
+    /// directory so configs sharing a filename (e.g. per-profile
+    /// `settings.json`) never overwrite each other's backups, and the backup
+    /// is named `<filename>.<timestamp>.backup` with the time it was taken in
+    /// milliseconds since the Unix epoch.
+    #[must_use]
+    pub fn backup_path(&self, client_id: &str, config_path: &Path, timestamp: u64) -> PathBuf {
+        let mut backup_path = mirror_path(&self.backups_dir().join(client_id), config_path);
+
+        if let Some(filename) = backup_path.file_name() {
+            let mut new_name = filename.to_os_string();
+            new_name.push(format!(".{timestamp}.backup"));
+            backup_path.set_file_name(new_name);
+        }
+
+        backup_path
+    }
+
+    /// Get the lock file guarding writes to a config file
+    #[must_use]
+    pub fn lock_path(&self, config_path: &Path) -> PathBuf {
+        let mut lock_path = mirror_path(&self.locks_dir(), config_path);
+
+        if let Some(filename) = lock_path.file_name() {
+            let mut new_name = filename.to_os_string();
+            new_name.push(".lock");
+            lock_path.set_file_name(new_name);
+        }
+
+        lock_path
+    }
+}
+
+/// Append an absolute path below `root`, turning drive prefixes into plain components
+fn mirror_path(root: &Path, path: &Path) -> PathBuf {
+    let mut mirrored = root.to_path_buf();
+
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => {
+                let prefix = prefix.as_os_str().to_string_lossy();
+                mirrored.push(prefix.trim_end_matches(':').replace(['\\', '?', ':'], ""));
+            }
+            Component::Normal(part) => mirrored.push(part),
+            Component::RootDir | Component::CurDir | Component::ParentDir => {}
+        }
+    }
+
+    mirrored
+}
@@ -0,0 +1,232 @@
+//! Reading and writing client configs
+//!
+//! Configs are merged as text, but not every config is a text file. Binary
+//! plists, which many macOS apps keep their preferences in, are read as XML
+//! and written back as binary, so the app finds them in the encoding it wrote.
+//!
+//! [`ConfigFormat::Sqlite`] configs are the JSON value under one key of a
+//! SQLite key-value table, the way VS Code's `state.vscdb` holds extension
+//! state (see [`ClientConfigPlugin::sqlite_key`]). Only that row is read and
+//! written, in a transaction, and the rest of the database is left alone.
+//! They need the `sqlite` feature.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::{ClientConfigPlugin, ConfigFormat, atomic};
+
+/// Magic bytes at the start of a binary plist
+const BINARY_PLIST_MAGIC: &[u8] = b"bplist00";
+
+/// Where a config is kept: a file, or a row of a SQLite database
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ConfigStore<'a> {
+    path: &'a Path,
+    /// `ItemTable` key of a config kept in a SQLite database
+    sqlite_key: Option<&'a str>,
+}
+
+impl<'a> ConfigStore<'a> {
+    /// Get where a client keeps the config at `path`
+    pub(crate) fn new(client: &'a dyn ClientConfigPlugin, path: &'a Path) -> Self {
+        let sqlite_key = client
+            .sqlite_key()
+            .filter(|_| client.config_format() == ConfigFormat::Sqlite);
+        Self { path, sqlite_key }
+    }
+
+    /// Get the config's file (the database, for SQLite configs)
+    pub(crate) fn path(&self) -> &'a Path {
+        self.path
+    }
+
+    /// Read the config as text
+    ///
+    /// Fails with [`io::ErrorKind::NotFound`] if the file (or database) doesn't
+    /// exist; a database without the key reads as an empty config.
+    pub(crate) fn read(&self) -> io::Result<String> {
+        match self.sqlite_key {
+            Some(key) => sqlite::read_item(self.path, key),
+            None => decode(std::fs::read(self.path)?),
+        }
+    }
+
+    /// Read the config through an open handle to its file, like [`read`](Self::read)
+    pub(crate) fn read_from(&self, mut file: &File) -> io::Result<String> {
+        if self.sqlite_key.is_some() {
+            return self.read();
+        }
+
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        decode(bytes)
+    }
+
+    /// Replace the config with `content`
+    ///
+    /// Files are replaced atomically, in the encoding of the file they replace.
+    pub(crate) fn write(&self, content: &str) -> io::Result<()> {
+        match self.sqlite_key {
+            Some(key) => sqlite::write_item(self.path, key, content),
+            None => atomic::write_atomic(self.path, self.encode(content)?),
+        }
+    }
+
+    /// Get the bytes to write to the config's file, for writers other than [`write`](Self::write)
+    ///
+    /// Binary if the file is currently a binary plist. SQLite configs can't be
+    /// written as a whole file, so those fail.
+    pub(crate) fn encode(&self, content: &str) -> io::Result<Vec<u8>> {
+        if self.sqlite_key.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "SQLite configs can only be written through the database",
+            ));
+        }
+
+        let mut magic = [0; BINARY_PLIST_MAGIC.len()];
+        let binary = File::open(self.path)
+            .and_then(|mut file| file.read_exact(&mut magic))
+            .is_ok_and(|()| magic == BINARY_PLIST_MAGIC);
+
+        if binary {
+            xml_to_binary_plist(content)
+        } else {
+            Ok(content.as_bytes().to_vec())
+        }
+    }
+}
+
+/// Decode the bytes of a config file as text
+fn decode(bytes: Vec<u8>) -> io::Result<String> {
+    if bytes.starts_with(BINARY_PLIST_MAGIC) {
+        return binary_plist_to_xml(&bytes);
+    }
+
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(target_os = "macos")]
+fn binary_plist_to_xml(bytes: &[u8]) -> io::Result<String> {
+    let value = plist::Value::from_reader(io::Cursor::new(bytes)).map_err(io::Error::other)?;
+    let mut xml = Vec::new();
+    value.to_writer_xml(&mut xml).map_err(io::Error::other)?;
+    String::from_utf8(xml).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(target_os = "macos")]
+fn xml_to_binary_plist(content: &str) -> io::Result<Vec<u8>> {
+    let value = plist::Value::from_reader(io::Cursor::new(content.as_bytes()))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let mut binary = Vec::new();
+    value
+        .to_writer_binary(&mut binary)
+        .map_err(io::Error::other)?;
+    Ok(binary)
+}
+
+/// Binary plists can only be read on macOS
+#[cfg(not(target_os = "macos"))]
+fn binary_plist_to_xml(_bytes: &[u8]) -> io::Result<String> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "binary plists are only supported on macOS",
+    ))
+}
+
+/// Binary plists can only be written on macOS
+#[cfg(not(target_os = "macos"))]
+fn xml_to_binary_plist(_content: &str) -> io::Result<Vec<u8>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "binary plists are only supported on macOS",
+    ))
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use std::io;
+    use std::path::Path;
+    use std::time::Duration;
+
+    use rusqlite::types::Value;
+    use rusqlite::{Connection, OpenFlags, OptionalExtension, TransactionBehavior};
+
+    /// How long to wait for the app to release the database
+    const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Open an existing database; a config never creates one
+    fn open(path: &Path, flags: OpenFlags) -> io::Result<Connection> {
+        if !path.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} doesn't exist", path.display()),
+            ));
+        }
+
+        let connection = Connection::open_with_flags(path, flags).map_err(io::Error::other)?;
+        connection
+            .busy_timeout(BUSY_TIMEOUT)
+            .map_err(io::Error::other)?;
+        Ok(connection)
+    }
+
+    pub(super) fn read_item(path: &Path, key: &str) -> io::Result<String> {
+        let connection = open(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let value: Option<Value> = connection
+            .query_row("SELECT value FROM ItemTable WHERE key = ?1", [key], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(io::Error::other)?;
+
+        match value {
+            None | Some(Value::Null) => Ok(String::new()),
+            Some(Value::Text(text)) => Ok(text),
+            Some(Value::Blob(bytes)) => {
+                String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            }
+            Some(_) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("`{key}` in {} doesn't hold text", path.display()),
+            )),
+        }
+    }
+
+    pub(super) fn write_item(path: &Path, key: &str, value: &str) -> io::Result<()> {
+        let mut connection = open(path, OpenFlags::SQLITE_OPEN_READ_WRITE)?;
+        let transaction = connection
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .map_err(io::Error::other)?;
+        transaction
+            .execute(
+                "INSERT OR REPLACE INTO ItemTable (key, value) VALUES (?1, ?2)",
+                (key, value),
+            )
+            .map_err(io::Error::other)?;
+        transaction.commit().map_err(io::Error::other)
+    }
+}
+
+/// SQLite configs without the `sqlite` feature
+#[cfg(not(feature = "sqlite"))]
+mod sqlite {
+    use std::io;
+    use std::path::Path;
+
+    pub(super) fn read_item(_path: &Path, _key: &str) -> io::Result<String> {
+        Err(unsupported())
+    }
+
+    pub(super) fn write_item(_path: &Path, _key: &str, _value: &str) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    fn unsupported() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "SQLite configs need the `sqlite` feature",
+        )
+    }
+}
@@ -0,0 +1,197 @@
+//! Where an install writes configs
+//!
+//! Installs normally configure the clients on this machine. An
+//! [`InstallTarget`] can instead point at another machine reached over SSH
+//! (for VS Code Remote and similar setups) or at a workspace's dev container
+//! definition (for Dev Containers and Codespaces); see [`install_to`].
+//!
+//! [`install_to`]: crate::install::install_to
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+
+use crate::elevation::REPLACE_SCRIPT;
+
+/// Where an install writes configs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum InstallTarget {
+    /// The clients on this machine
+    #[default]
+    Local,
+    /// The clients on a machine reached with the `ssh` command
+    Ssh(SshTarget),
+    /// The dev container definition of a workspace (`.devcontainer/devcontainer.json`)
+    Devcontainer(PathBuf),
+}
+
+/// A machine reached with the system's `ssh` command
+///
+/// Uses the user's SSH config, keys and agent; it never prompts, so the
+/// destination must accept a non-interactive login. Files are read and
+/// written with POSIX shell commands, so the remote machine must be Unix-like.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SshTarget {
+    /// `[user@]host`, or a host alias from the SSH config
+    destination: String,
+    /// Extra options for `ssh` (e.g. `-p 2222`)
+    ssh_args: Vec<String>,
+}
+
+impl SshTarget {
+    #[must_use]
+    pub fn new(destination: impl Into<String>) -> Self {
+        Self {
+            destination: destination.into(),
+            ssh_args: Vec::new(),
+        }
+    }
+
+    /// Pass extra options to `ssh` (e.g. `["-p", "2222"]` or `["-i", "key"]`)
+    #[must_use]
+    pub fn with_ssh_args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.ssh_args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Get the machine being configured
+    #[must_use]
+    pub fn destination(&self) -> &str {
+        &self.destination
+    }
+
+    /// Get the remote user's home directory
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the machine can't be reached.
+    pub fn home(&self) -> Result<PathBuf> {
+        let home = self.run(r#"printf %s "$HOME""#, None)?;
+        if home.is_empty() {
+            bail!("{} has no home directory", self.destination);
+        }
+        Ok(PathBuf::from(home))
+    }
+
+    /// Read a remote file, or `None` if it doesn't exist
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the machine can't be reached or the file can't be read.
+    pub fn read(&self, path: &Path) -> Result<Option<String>> {
+        let path = quote(path);
+        let output = self.run(
+            &format!("if [ -e {path} ]; then printf 1; cat -- {path}; else printf 0; fi"),
+            None,
+        )?;
+
+        Ok(output.strip_prefix('1').map(str::to_string))
+    }
+
+    /// Get the remote operating system, as `uname -s` names it (e.g. `Linux`, `Darwin`)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the machine can't be reached.
+    pub fn os(&self) -> Result<String> {
+        Ok(self.run("uname -s", None)?.trim().to_string())
+    }
+
+    /// Write a remote file, creating its directory
+    ///
+    /// The content goes to a temporary file in the config's directory, which
+    /// takes over the config's mode and owner and then replaces it, so the
+    /// client never reads half a file. New configs get mode 644.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the machine can't be reached or the file can't be written.
+    pub fn write(&self, path: &Path, content: &str) -> Result<()> {
+        let dir = path.parent().context("Config path has no directory")?;
+        self.run(
+            &format!(
+                "sh -c {} sh {} {}",
+                quote_arg(REPLACE_SCRIPT),
+                quote(dir),
+                quote(path)
+            ),
+            Some(content),
+        )
+        .map(drop)
+    }
+
+    /// Find which of `dirs` exist on the remote machine, in one round trip
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the machine can't be reached.
+    pub fn existing_dirs(&self, dirs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        if dirs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let quoted: Vec<String> = dirs.iter().map(|dir| quote(dir)).collect();
+        let output = self.run(
+            &format!(
+                r#"for dir in {}; do [ -d "$dir" ] && printf '%s\n' "$dir"; done; true"#,
+                quoted.join(" ")
+            ),
+            None,
+        )?;
+
+        let found: Vec<&str> = output.lines().collect();
+        Ok(dirs
+            .iter()
+            .filter(|dir| found.contains(&remote_path(dir).as_str()))
+            .cloned()
+            .collect())
+    }
+
+    /// Run a shell command on the remote machine, feeding it `input`
+    fn run(&self, script: &str, input: Option<&str>) -> Result<String> {
+        let mut child = Command::new("ssh")
+            .args(&self.ssh_args)
+            .args(["-o", "BatchMode=yes", "--", &self.destination, script])
+            .stdin(if input.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to run ssh")?;
+
+        if let Some(input) = input {
+            let mut stdin = child.stdin.take().context("ssh stdin is not piped")?;
+            stdin.write_all(input.as_bytes())?;
+        }
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!(
+                "ssh {} failed: {}",
+                self.destination,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// Write a path with `/` separators, whatever the local platform
+fn remote_path(path: &Path) -> String {
+    path.display().to_string().replace('\\', "/")
+}
+
+/// Quote a path for a POSIX shell
+fn quote(path: &Path) -> String {
+    quote_arg(&remote_path(path))
+}
+
+/// Quote an argument for a POSIX shell
+fn quote_arg(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
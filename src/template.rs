@@ -0,0 +1,139 @@
+//! Placeholders in server definitions
+//!
+//! `${HOME}`, `${KODEGEN_BIN}` and `${WORKSPACE}` in the command, args and env
+//! of a [`KodegenConfig`] are replaced when it's injected, so one definition
+//! serves Windows and Unix installs alike. Other `${...}` text (e.g. a
+//! client's own `${env:NAME}`) is left for the client to expand.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{ClientConfigPlugin, KodegenConfig, binary, paths};
+
+/// Values of the placeholders for one injection
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TemplateVars {
+    /// Value of `${HOME}`
+    pub home: Option<PathBuf>,
+    /// Value of `${KODEGEN_BIN}`
+    pub kodegen_bin: Option<PathBuf>,
+    /// Value of `${WORKSPACE}`, a path or a client's own variable for it
+    pub workspace: Option<String>,
+}
+
+impl TemplateVars {
+    /// Resolve the placeholders for the current platform and user
+    ///
+    /// `${WORKSPACE}` is left unresolved; see [`Self::for_client`] and
+    /// [`Self::with_workspace`].
+    #[must_use]
+    pub fn current() -> Self {
+        Self {
+            home: paths::home_dir(),
+            kodegen_bin: kodegen_bin(),
+            workspace: None,
+        }
+    }
+
+    /// Resolve the placeholders for a client's user config
+    ///
+    /// `${WORKSPACE}` becomes the client's variable for the open workspace,
+    /// if it has one (see [`ClientConfigPlugin::workspace_variable`]).
+    #[must_use]
+    pub fn for_client(client: &dyn ClientConfigPlugin) -> Self {
+        Self {
+            workspace: client.workspace_variable().map(str::to_string),
+            ..Self::current()
+        }
+    }
+
+    /// Resolve `${WORKSPACE}` to a project directory
+    #[must_use]
+    pub fn with_workspace(mut self, workspace: &Path) -> Self {
+        self.workspace = Some(workspace.display().to_string());
+        self
+    }
+
+    /// Get the value of a placeholder, if it's known and resolved
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<String> {
+        match name {
+            "HOME" => self.home.as_ref().map(|home| home.display().to_string()),
+            "KODEGEN_BIN" => self
+                .kodegen_bin
+                .as_ref()
+                .map(|bin| bin.display().to_string()),
+            "WORKSPACE" => self.workspace.clone(),
+            _ => None,
+        }
+    }
+
+    /// Replace the placeholders in `text`
+    ///
+    /// Unknown names and placeholders that can't be resolved here are kept.
+    #[must_use]
+    pub fn expand(&self, text: &str) -> String {
+        let mut expanded = String::with_capacity(text.len());
+        let mut rest = text;
+
+        while let Some(start) = rest.find("${") {
+            expanded.push_str(&rest[..start]);
+            let placeholder = &rest[start..];
+            let Some(end) = placeholder.find('}') else {
+                rest = placeholder;
+                break;
+            };
+
+            let name = &placeholder[2..end];
+            match self.get(name) {
+                Some(value) => expanded.push_str(&value),
+                None => {
+                    if matches!(name, "HOME" | "KODEGEN_BIN" | "WORKSPACE") {
+                        log::debug!("${{{name}}} can't be resolved here, leaving it as is");
+                    }
+                    expanded.push_str(&placeholder[..=end]);
+                }
+            }
+            rest = &placeholder[end + 1..];
+        }
+
+        expanded.push_str(rest);
+        expanded
+    }
+
+    /// Replace the placeholders in every string of a JSON value
+    fn expand_json(&self, value: &JsonValue) -> JsonValue {
+        match value {
+            JsonValue::String(text) => JsonValue::String(self.expand(text)),
+            JsonValue::Array(items) => {
+                JsonValue::Array(items.iter().map(|item| self.expand_json(item)).collect())
+            }
+            JsonValue::Object(map) => JsonValue::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), self.expand_json(value)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    /// Replace the placeholders in a server definition's command, args and env
+    #[must_use]
+    pub fn expand_config(&self, config: &KodegenConfig) -> KodegenConfig {
+        KodegenConfig {
+            command: self.expand(&config.command),
+            args: config.args.iter().map(|arg| self.expand(arg)).collect(),
+            env: config.env.as_ref().map(|env| self.expand_json(env)),
+        }
+    }
+}
+
+/// Get the kodegen binary `${KODEGEN_BIN}` stands for
+///
+/// This is the binary [`binary::resolve_kodegen_binary`] finds, or the
+/// standard install location if there is none yet.
+#[must_use]
+pub fn kodegen_bin() -> Option<PathBuf> {
+    binary::resolve_kodegen_binary().or_else(binary::default_install_path)
+}
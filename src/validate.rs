@@ -0,0 +1,571 @@
+//! Checking configs against the shape each client accepts
+//!
+//! Every supported client has a schema (see [`ClientConfigPlugin::schema`])
+//! describing where its MCP servers live and what a server entry needs,
+//! written in a subset of JSON Schema: `type`, `properties`, `required`,
+//! `additionalProperties`, `items`, `enum`, `anyOf`, `minLength` and
+//! `minItems`. TOML, YAML and property list configs are checked as the JSON
+//! value they convert to; XML configs only have to parse.
+//!
+//! Installs validate a merged config before writing it and refuse to write
+//! one that has issues the original didn't, so a config the client would
+//! reject is never persisted. Keys the schemas don't mention are allowed.
+//! They also check that the merge kept every setting the user had (see
+//! [`ConfigError::SettingsLost`]), so a merger bug can't silently destroy them.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+
+use crate::config::{ConfigMerger, ConflictResolution};
+use crate::error::ConfigError;
+use crate::{ClientConfigPlugin, ConfigFormat, jsonc};
+
+/// Something in a config its client would reject
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    /// JSON Pointer to the offending value (e.g. `/mcpServers/kodegen/command`), empty for the root
+    pub pointer: String,
+    /// What is wrong with it
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "config: {}", self.message)
+        } else {
+            write!(f, "{}: {}", self.pointer, self.message)
+        }
+    }
+}
+
+/// Check a config of a client (in its usual format) against the client's schema
+///
+/// An empty config is valid. Clients without a schema, unknown ids included,
+/// have no issues.
+#[must_use]
+pub fn validate_config(client_id: &str, content: &str) -> Vec<ValidationIssue> {
+    let registry = crate::clients::registry();
+    let Some(client) = registry.get(client_id) else {
+        return Vec::new();
+    };
+    validate_config_as(client_id, content, client.config_format())
+}
+
+/// Check a config of the given format against a client's schema
+///
+/// Like [`validate_config`], for clients whose configs come in more than one
+/// format (e.g. Continue's legacy `config.json`).
+#[must_use]
+pub fn validate_config_as(
+    client_id: &str,
+    content: &str,
+    format: ConfigFormat,
+) -> Vec<ValidationIssue> {
+    let Some(schema) = schema(client_id) else {
+        return Vec::new();
+    };
+    if content.trim().is_empty() {
+        return Vec::new();
+    }
+
+    match to_json_value(content, format) {
+        // XML holds only text, so there are no types to check it against
+        Ok(Some(_)) if format == ConfigFormat::Xml => Vec::new(),
+        Ok(Some(value)) => {
+            let mut issues = Vec::new();
+            check(&value, &schema, &mut String::new(), &mut issues);
+            issues
+        }
+        // Property lists can only be read on macOS
+        Ok(None) => Vec::new(),
+        Err(message) => vec![ValidationIssue {
+            pointer: String::new(),
+            message,
+        }],
+    }
+}
+
+/// Get the schema of a client's config, if it has one
+///
+/// See [`ClientConfigPlugin::schema`].
+#[must_use]
+pub fn schema(client_id: &str) -> Option<JsonValue> {
+    crate::clients::registry().get(client_id)?.schema()
+}
+
+/// Refuse a merged config that has issues its original didn't have
+///
+/// Issues already in the user's config aren't kodegen's to fix, so they
+/// don't stop an install.
+pub(crate) fn ensure_valid(
+    client: &dyn ClientConfigPlugin,
+    format: ConfigFormat,
+    original: &str,
+    merged: &str,
+) -> Result<(), ConfigError> {
+    if merged == original {
+        return Ok(());
+    }
+
+    let before = validate_config_as(client.client_id(), original, format);
+    let issues: Vec<ValidationIssue> = validate_config_as(client.client_id(), merged, format)
+        .into_iter()
+        .filter(|issue| !before.contains(issue))
+        .collect();
+
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid { issues })
+    }
+}
+
+/// Refuse a merged config that lost any of the original's settings
+///
+/// Both configs are compared with kodegen's own servers removed, as those may
+/// legitimately change; every other value of the original has to be in the
+/// merged config. Entries taken over with [`ConflictResolution::Replace`] may
+/// change too.
+pub(crate) fn ensure_preserved(
+    client: &dyn ClientConfigPlugin,
+    format: ConfigFormat,
+    original: &str,
+    merged: &str,
+    merger: &ConfigMerger,
+) -> anyhow::Result<()> {
+    if merged == original || original.trim().is_empty() {
+        return Ok(());
+    }
+
+    let without_ours = |content: &str| -> anyhow::Result<Option<JsonValue>> {
+        let stripped = client.remove_servers(content, format, merger)?;
+        to_json_value(&stripped, format).map_err(anyhow::Error::msg)
+    };
+    let (Some(before), Some(after)) = (without_ours(original)?, without_ours(merged)?) else {
+        return Ok(());
+    };
+
+    let replaced: Vec<String> = if merger.conflict_resolution() == ConflictResolution::Replace {
+        merger
+            .servers()
+            .map(|server| server.server_name().to_string())
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let mut lost = Vec::new();
+    find_lost(&before, &after, &replaced, &mut String::new(), &mut lost);
+    if lost.is_empty() {
+        Ok(())
+    } else {
+        Err(ConfigError::SettingsLost { pointers: lost }.into())
+    }
+}
+
+/// Inject a merger's servers into a config and check the result before it's written
+pub(crate) fn inject_validated(
+    client: &dyn ClientConfigPlugin,
+    content: &str,
+    merger: &ConfigMerger,
+) -> anyhow::Result<String> {
+    let format = merger.config_format(client.config_format());
+    let merged = client.inject_servers(content, format, merger)?;
+    ensure_preserved(client, format, content, &merged, merger)?;
+    ensure_valid(client, format, content, &merged)?;
+    Ok(merged)
+}
+
+/// Collect the pointers of values in `before` that `after` doesn't have
+///
+/// Objects may gain keys and lists may gain items; empty objects and lists
+/// have nothing to lose. `replaced` names entries that may change.
+fn find_lost(
+    before: &JsonValue,
+    after: &JsonValue,
+    replaced: &[String],
+    pointer: &mut String,
+    lost: &mut Vec<String>,
+) {
+    match (before, after) {
+        (JsonValue::Object(before), JsonValue::Object(after)) => {
+            for (key, value) in before {
+                if replaced.contains(key) {
+                    continue;
+                }
+                match after.get(key) {
+                    Some(after) => {
+                        let len = pointer.len();
+                        push_segment(pointer, key);
+                        find_lost(value, after, replaced, pointer, lost);
+                        pointer.truncate(len);
+                    }
+                    None if is_empty_container(value) => {}
+                    None => {
+                        let mut lost_pointer = pointer.clone();
+                        push_segment(&mut lost_pointer, key);
+                        lost.push(lost_pointer);
+                    }
+                }
+            }
+        }
+        (JsonValue::Array(before), JsonValue::Array(after)) => {
+            for (index, item) in before.iter().enumerate() {
+                let name = ["name", "uuid"]
+                    .iter()
+                    .find_map(|key| item.get(key).and_then(JsonValue::as_str));
+                if name.is_some_and(|name| replaced.iter().any(|replaced| replaced == name)) {
+                    continue;
+                }
+
+                let kept = after.iter().any(|candidate| {
+                    let mut item_lost = Vec::new();
+                    find_lost(
+                        item,
+                        candidate,
+                        replaced,
+                        &mut String::new(),
+                        &mut item_lost,
+                    );
+                    item_lost.is_empty()
+                });
+                if !kept {
+                    let mut lost_pointer = pointer.clone();
+                    push_segment(&mut lost_pointer, &index.to_string());
+                    lost.push(lost_pointer);
+                }
+            }
+        }
+        (before, _) if is_empty_container(before) => {}
+        (before, after) => {
+            if before != after {
+                lost.push(pointer.clone());
+            }
+        }
+    }
+}
+
+fn is_empty_container(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Object(object) => object.is_empty(),
+        JsonValue::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+/// Append an escaped key to a JSON Pointer
+fn push_segment(pointer: &mut String, key: &str) {
+    pointer.push('/');
+    pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+}
+
+/// Parse a config into the JSON value it's validated as
+///
+/// Returns `None` for formats that can't be read on this platform, and for
+/// custom formats without a registered codec.
+fn to_json_value(content: &str, format: ConfigFormat) -> Result<Option<JsonValue>, String> {
+    let value = match format {
+        ConfigFormat::Json | ConfigFormat::Sqlite => {
+            serde_json::from_str(jsonc::Document::new(content).stripped())
+                .map_err(|e| format!("not valid JSON: {e}"))?
+        }
+        ConfigFormat::Toml => {
+            toml::from_str(content).map_err(|e| format!("not valid TOML: {e}"))?
+        }
+        ConfigFormat::Yaml => {
+            serde_yaml::from_str(content).map_err(|e| format!("not valid YAML: {e}"))?
+        }
+        ConfigFormat::Plist => return plist_to_json(content),
+        ConfigFormat::Ini => {
+            crate::ini::parse(content).map_err(|e| format!("not valid INI: {e}"))?
+        }
+        ConfigFormat::Xml => {
+            crate::xml::parse(content).map_err(|e| format!("not valid XML: {e}"))?
+        }
+        ConfigFormat::Custom(name) => {
+            let Some(codec) = crate::codec::get(format) else {
+                return Ok(None);
+            };
+            codec
+                .parse(content)
+                .map_err(|e| format!("not valid {name}: {e}"))?
+        }
+    };
+    Ok(Some(value))
+}
+
+#[cfg(target_os = "macos")]
+fn plist_to_json(content: &str) -> Result<Option<JsonValue>, String> {
+    let value: plist::Value = plist::from_reader(std::io::Cursor::new(content.as_bytes()))
+        .map_err(|e| format!("not a valid property list: {e}"))?;
+    Ok(Some(crate::config::to_json(&value)))
+}
+
+#[cfg(not(target_os = "macos"))]
+#[allow(clippy::unnecessary_wraps)]
+fn plist_to_json(_content: &str) -> Result<Option<JsonValue>, String> {
+    Ok(None)
+}
+
+/// Check a value against a schema, collecting issues under `pointer`
+fn check(
+    value: &JsonValue,
+    schema: &JsonValue,
+    pointer: &mut String,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    if let Some(expected) = schema.get("type")
+        && !type_matches(value, expected)
+    {
+        push_issue(
+            issues,
+            pointer,
+            format!(
+                "expected {}, found {}",
+                type_names(expected),
+                type_name(value)
+            ),
+        );
+        return;
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(JsonValue::as_array)
+        && !allowed.contains(value)
+    {
+        let allowed: Vec<String> = allowed.iter().map(ToString::to_string).collect();
+        push_issue(
+            issues,
+            pointer,
+            format!("must be one of {}", allowed.join(", ")),
+        );
+    }
+
+    if let Some(min) = schema.get("minLength").and_then(JsonValue::as_u64)
+        && value
+            .as_str()
+            .is_some_and(|text| (text.chars().count() as u64) < min)
+    {
+        push_issue(issues, pointer, "must not be empty".to_string());
+    }
+
+    if let Some(min) = schema.get("minItems").and_then(JsonValue::as_u64)
+        && value
+            .as_array()
+            .is_some_and(|items| (items.len() as u64) < min)
+    {
+        push_issue(issues, pointer, format!("needs at least {min} item(s)"));
+    }
+
+    if let Some(object) = value.as_object() {
+        for key in schema
+            .get("required")
+            .and_then(JsonValue::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(JsonValue::as_str)
+        {
+            if !object.contains_key(key) {
+                push_issue(issues, pointer, format!("missing required key `{key}`"));
+            }
+        }
+
+        let properties = schema.get("properties").and_then(JsonValue::as_object);
+        for (key, child) in object {
+            let child_schema = match properties.and_then(|properties| properties.get(key)) {
+                Some(child_schema) => child_schema,
+                None => match schema.get("additionalProperties") {
+                    Some(JsonValue::Bool(false)) => {
+                        push_issue(issues, pointer, format!("unexpected key `{key}`"));
+                        continue;
+                    }
+                    Some(child_schema) if child_schema.is_object() => child_schema,
+                    _ => continue,
+                },
+            };
+            descend(child, child_schema, pointer, key, issues);
+        }
+    }
+
+    if let (Some(items), Some(item_schema)) = (value.as_array(), schema.get("items")) {
+        for (index, item) in items.iter().enumerate() {
+            descend(item, item_schema, pointer, &index.to_string(), issues);
+        }
+    }
+
+    if let Some(branches) = schema.get("anyOf").and_then(JsonValue::as_array) {
+        // Report the branch that came closest, as the shape the user most likely
+        // meant: the one knowing the most of the value's keys, then with the fewest issues
+        let closest = branches
+            .iter()
+            .map(|branch| {
+                let mut branch_issues = Vec::new();
+                check(value, branch, pointer, &mut branch_issues);
+                (unknown_keys(value, branch), branch_issues)
+            })
+            .min_by_key(|(unknown, branch_issues)| (*unknown, branch_issues.len()));
+        if let Some((_, branch_issues)) = closest {
+            issues.extend(branch_issues);
+        }
+    }
+}
+
+fn push_issue(issues: &mut Vec<ValidationIssue>, pointer: &str, message: String) {
+    issues.push(ValidationIssue {
+        pointer: pointer.to_string(),
+        message,
+    });
+}
+
+/// Check a child value, with its key appended to the pointer
+fn descend(
+    value: &JsonValue,
+    schema: &JsonValue,
+    pointer: &mut String,
+    key: &str,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let len = pointer.len();
+    push_segment(pointer, key);
+    check(value, schema, pointer, issues);
+    pointer.truncate(len);
+}
+
+/// Count the keys of an object value that a schema has no property for
+fn unknown_keys(value: &JsonValue, schema: &JsonValue) -> usize {
+    let properties = schema.get("properties").and_then(JsonValue::as_object);
+    value.as_object().map_or(0, |object| {
+        object
+            .keys()
+            .filter(|key| !properties.is_some_and(|properties| properties.contains_key(*key)))
+            .count()
+    })
+}
+
+/// Check a value against a `type` keyword (a name or a list of names)
+fn type_matches(value: &JsonValue, expected: &JsonValue) -> bool {
+    match expected {
+        JsonValue::String(name) => is_type(value, name),
+        JsonValue::Array(names) => names
+            .iter()
+            .filter_map(JsonValue::as_str)
+            .any(|name| is_type(value, name)),
+        _ => true,
+    }
+}
+
+fn is_type(value: &JsonValue, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_names(expected: &JsonValue) -> String {
+    match expected {
+        JsonValue::Array(names) => names
+            .iter()
+            .filter_map(JsonValue::as_str)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        other => other.as_str().unwrap_or("value").to_string(),
+    }
+}
+
+fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "boolean",
+        JsonValue::Number(_) => "number",
+        JsonValue::String(_) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "object",
+    }
+}
+
+/// An object with the given property schemas
+pub(crate) fn object(properties: JsonValue) -> JsonValue {
+    json!({ "type": "object", "properties": properties })
+}
+
+/// An object with required keys and the given property schemas
+pub(crate) fn required(keys: &[&str], properties: JsonValue) -> JsonValue {
+    json!({ "type": "object", "required": keys, "properties": properties })
+}
+
+/// A config root with `schema` nested under `path`
+pub(crate) fn at(path: &[&str], schema: JsonValue) -> JsonValue {
+    path.iter()
+        .rev()
+        .fold(schema, |inner, key| object(json!({ *key: inner })))
+}
+
+/// Servers keyed by name
+pub(crate) fn server_map(entry: JsonValue) -> JsonValue {
+    json!({ "type": "object", "additionalProperties": entry })
+}
+
+/// Servers in a list
+pub(crate) fn server_list(entry: JsonValue) -> JsonValue {
+    json!({ "type": "array", "items": entry })
+}
+
+/// A listed server, which needs a name
+pub(crate) fn named(mut entry: JsonValue) -> JsonValue {
+    entry["required"] = json!(["name", "command"]);
+    entry["properties"]["name"] = json!({ "type": "string", "minLength": 1 });
+    entry
+}
+
+/// A list of strings
+pub(crate) fn string_list() -> JsonValue {
+    json!({ "type": "array", "items": { "type": "string" } })
+}
+
+/// An object of strings
+pub(crate) fn string_map() -> JsonValue {
+    json!({ "type": "object", "additionalProperties": { "type": "string" } })
+}
+
+/// A server started as a local command
+pub(crate) fn stdio_server() -> JsonValue {
+    required(
+        &["command"],
+        json!({
+            "type": { "type": "string" },
+            "command": { "type": "string", "minLength": 1 },
+            "args": string_list(),
+            "env": string_map(),
+        }),
+    )
+}
+
+/// A server reached over HTTP
+pub(crate) fn remote_server() -> JsonValue {
+    required(
+        &["url"],
+        json!({
+            "type": { "type": "string" },
+            "url": { "type": "string", "minLength": 1 },
+            "headers": string_map(),
+        }),
+    )
+}
+
+/// A server started as a local command or reached over HTTP
+pub(crate) fn any_server() -> JsonValue {
+    json!({ "anyOf": [stdio_server(), remote_server()] })
+}
+
+/// The schema of clients keeping their servers in the standard `mcpServers` object
+pub(crate) fn mcp_servers_schema() -> JsonValue {
+    at(&["mcpServers"], server_map(any_server()))
+}
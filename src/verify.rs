@@ -0,0 +1,298 @@
+//! Checking that an install actually works
+//!
+//! A written config only means the client will try to start kodegen.
+//! [`verify_install`] goes one step further and talks to the server the
+//! client was configured with: over stdio it runs the command and performs
+//! MCP's `initialize` handshake, over HTTP (with the `verify-http` feature)
+//! it checks that the endpoint answers.
+
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::config::{ConfigMerger, TransportPreference};
+use crate::install::InstallResult;
+use crate::settings::Settings;
+use crate::{KodegenConfig, binary, paths};
+
+/// How long the server gets to answer
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// MCP protocol version offered in the handshake
+const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// What the configured server reported about itself
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerInfo {
+    /// Transport the server was reached over
+    pub transport: TransportPreference,
+    /// Server name from the handshake (e.g. `kodegen`)
+    pub name: Option<String>,
+    /// Server version from the handshake
+    pub version: Option<String>,
+    /// Protocol version the server agreed to
+    pub protocol_version: Option<String>,
+    /// Capabilities the server announced (tools, resources, ...)
+    pub capabilities: Option<JsonValue>,
+    /// Status of the HTTP endpoint's response
+    pub http_status: Option<u16>,
+}
+
+/// Check that the server an install configured a client with starts and answers
+///
+/// The server definition is taken from the settings of the user the result
+/// belongs to, along with the transport recorded in the result. Gives up
+/// after [`DEFAULT_TIMEOUT`].
+///
+/// For a result from a machine-wide install, that user's command runs as the
+/// owner of their home directory rather than with our own (elevated)
+/// privileges. Such results can't be verified on Windows.
+///
+/// # Errors
+///
+/// Returns an error if the install didn't succeed, the server can't be
+/// started or reached, or it doesn't complete the handshake in time.
+pub fn verify_install(result: &InstallResult) -> Result<ServerInfo> {
+    verify_install_with_timeout(result, DEFAULT_TIMEOUT)
+}
+
+/// Like [`verify_install`], giving the server `timeout` to answer
+///
+/// # Errors
+///
+/// Returns an error if the install didn't succeed, the server can't be
+/// started or reached, or it doesn't complete the handshake in time.
+pub fn verify_install_with_timeout(
+    result: &InstallResult,
+    timeout: Duration,
+) -> Result<ServerInfo> {
+    if !result.success || result.config_path.is_none() {
+        bail!(
+            "{} was not configured ({})",
+            result.client_name,
+            result.code
+        );
+    }
+
+    let merger = match &result.user_home {
+        Some(home) => paths::with_user_home(home, || merger_for_result(result))?,
+        None => merger_for_result(result)?,
+    };
+
+    if merger.transport().is_http() {
+        return verify_http(&merger, timeout);
+    }
+    spawn_handshake(merger.server_config(), timeout, result.user_home.as_deref())
+}
+
+/// Rebuild the merger the install used for the result's client
+fn merger_for_result(result: &InstallResult) -> Result<ConfigMerger> {
+    let client = crate::clients::registry()
+        .into_iter()
+        .find(|client| client.client_id() == result.client_id)
+        .ok_or_else(|| anyhow!("Unknown client `{}`", result.client_id))?;
+
+    Ok(Settings::load()?
+        .merger_for(client.as_ref())
+        .with_transport(result.transport)
+        .for_client(client.as_ref()))
+}
+
+/// Run the server's command and perform the `initialize` handshake over stdio
+///
+/// # Errors
+///
+/// Returns an error if the command can't be started or doesn't answer the
+/// handshake within `timeout`.
+pub fn verify_stdio(server: &KodegenConfig, timeout: Duration) -> Result<ServerInfo> {
+    spawn_handshake(server, timeout, None)
+}
+
+/// Run the server's command (as the owner of `user_home`, if given) and perform the handshake
+fn spawn_handshake(
+    server: &KodegenConfig,
+    timeout: Duration,
+    user_home: Option<&Path>,
+) -> Result<ServerInfo> {
+    let program = binary::verify_command(&server.command)?;
+
+    let mut command = Command::new(&program);
+    command
+        .args(&server.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    if let Some(env) = server.env.as_ref().and_then(JsonValue::as_object) {
+        for (key, value) in env {
+            match value {
+                JsonValue::String(value) => command.env(key, value),
+                other => command.env(key, other.to_string()),
+            };
+        }
+    }
+
+    if let Some(home) = user_home {
+        run_as_home_owner(&mut command, home)?;
+    }
+
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("Failed to start {}", program.display()))?;
+    let response = handshake(&mut child, timeout);
+    let _ = child.kill();
+    let _ = child.wait();
+
+    let response = response?;
+    if let Some(error) = response.get("error") {
+        bail!("Server rejected the handshake: {error}");
+    }
+    let result = response
+        .get("result")
+        .ok_or_else(|| anyhow!("Handshake response has no result: {response}"))?;
+
+    let server_info = result.get("serverInfo");
+    let field = |value: Option<&JsonValue>, key: &str| {
+        value
+            .and_then(|value| value.get(key))
+            .and_then(JsonValue::as_str)
+            .map(str::to_string)
+    };
+
+    Ok(ServerInfo {
+        transport: TransportPreference::Stdio,
+        name: field(server_info, "name"),
+        version: field(server_info, "version"),
+        protocol_version: field(Some(result), "protocolVersion"),
+        capabilities: result.get("capabilities").cloned(),
+        http_status: None,
+    })
+}
+
+/// Drop to the owner of `home` so a user's configured command never runs with our privileges
+#[cfg(unix)]
+fn run_as_home_owner(command: &mut Command, home: &Path) -> Result<()> {
+    use std::os::unix::fs::MetadataExt;
+    use std::os::unix::process::CommandExt;
+
+    let owner = std::fs::metadata(home)
+        .with_context(|| format!("Failed to read the owner of {}", home.display()))?;
+    command.uid(owner.uid()).gid(owner.gid()).env("HOME", home);
+    Ok(())
+}
+
+/// Windows has no way to start a process as another user without their credentials
+#[cfg(not(unix))]
+fn run_as_home_owner(_command: &mut Command, home: &Path) -> Result<()> {
+    bail!(
+        "Can't run the server configured for {} as its user",
+        home.display()
+    )
+}
+
+/// Send `initialize` and wait for its response, skipping anything else the server prints
+fn handshake(child: &mut Child, timeout: Duration) -> Result<JsonValue> {
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": {
+                "name": "kodegen-autoconfig",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        },
+    });
+
+    let mut stdin = child.stdin.take().context("Server stdin is not piped")?;
+    writeln!(stdin, "{request}").context("Failed to send the handshake")?;
+    stdin.flush()?;
+
+    // Reading blocks, so it happens on a thread the timeout can abandon
+    let stdout = child.stdout.take().context("Server stdout is not piped")?;
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let Ok(message) = serde_json::from_str::<JsonValue>(&line) else {
+                continue;
+            };
+            if message.get("id") == Some(&JsonValue::from(1)) {
+                let _ = sender.send(message);
+                return;
+            }
+        }
+    });
+
+    let response = receiver.recv_timeout(timeout).map_err(|e| match e {
+        mpsc::RecvTimeoutError::Timeout => {
+            anyhow!("Server didn't answer the handshake within {timeout:?}")
+        }
+        mpsc::RecvTimeoutError::Disconnected => anyhow!("Server exited without answering"),
+    });
+    drop(stdin);
+    response
+}
+
+/// Check that the HTTP endpoint answers a GET
+///
+/// Any HTTP response counts, as MCP endpoints may answer a plain GET with
+/// 405 or an event stream; server errors (5xx) and network failures don't.
+///
+/// # Errors
+///
+/// Returns an error if the endpoint can't be reached or fails.
+#[cfg(feature = "verify-http")]
+fn verify_http(merger: &ConfigMerger, timeout: Duration) -> Result<ServerInfo> {
+    let http = merger.http_config();
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .http_status_as_error(false)
+        .build()
+        .into();
+
+    let mut request = agent.get(&http.url);
+    let api_key = http
+        .api_key_env
+        .as_deref()
+        .and_then(|var| std::env::var(var).ok());
+    let headers = match api_key {
+        Some(api_key) => http.headers_with(|_| api_key),
+        None => http.headers.clone(),
+    };
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+
+    let response = request
+        .call()
+        .with_context(|| format!("Failed to reach {}", http.url))?;
+    let status = response.status().as_u16();
+    if status >= 500 {
+        bail!("{} answered with HTTP {status}", http.url);
+    }
+
+    Ok(ServerInfo {
+        transport: merger.transport(),
+        name: None,
+        version: None,
+        protocol_version: None,
+        capabilities: None,
+        http_status: Some(status),
+    })
+}
+
+/// HTTP checks need the `verify-http` feature
+#[cfg(not(feature = "verify-http"))]
+fn verify_http(_merger: &ConfigMerger, _timeout: Duration) -> Result<ServerInfo> {
+    bail!("Checking HTTP transports requires the `verify-http` feature")
+}
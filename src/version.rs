@@ -0,0 +1,80 @@
+//! Client versions
+//!
+//! Plugins report the version of an installed client through
+//! [`ClientConfigPlugin::detect_version`](crate::ClientConfigPlugin::detect_version)
+//! when its config schema changed between releases (e.g. VS Code moving MCP
+//! servers out of `settings.json` in 1.102).
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// A client's release, compared component by component
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(into = "String", try_from = "String")]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    #[must_use]
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+/// A version string without a leading number
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid version `{0}`")]
+pub struct ParseVersionError(String);
+
+impl FromStr for Version {
+    type Err = ParseVersionError;
+
+    /// Parse versions like `1.99.3`, `v0.3.17-beta` or `2024.3`
+    ///
+    /// A leading `v` and anything after the numeric components (pre-release
+    /// tags, build metadata) are ignored; missing components are zero.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let trimmed = text.trim();
+        let numeric = trimmed.strip_prefix(['v', 'V']).unwrap_or(trimmed);
+        let end = numeric
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(numeric.len());
+
+        let mut components = numeric[..end].split('.').map(str::parse::<u32>);
+        let mut next = || components.next().and_then(Result::ok);
+        let major = next().ok_or_else(|| ParseVersionError(text.to_string()))?;
+
+        Ok(Self::new(major, next().unwrap_or(0), next().unwrap_or(0)))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl From<Version> for String {
+    fn from(version: Version) -> Self {
+        version.to_string()
+    }
+}
+
+impl TryFrom<String> for Version {
+    type Error = ParseVersionError;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
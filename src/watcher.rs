@@ -1,23 +1,137 @@
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
 
-use anyhow::Result;
-use dashmap::DashMap;
+use anyhow::{Result, bail};
+use dashmap::{DashMap, DashSet};
 use log::{debug, error, info, warn};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use tokio::fs;
-use watchexec::Watchexec;
-use watchexec_events::Tag;
+use tokio::sync::Notify;
+use tokio::time::{Instant, sleep_until};
+use watchexec::error::{CriticalError, FsWatcherError, RuntimeError};
+use watchexec::sources::fs::Watcher;
+use watchexec::{ErrorHook, WatchedPath, Watchexec};
+use watchexec_events::filekind::FileEventKind;
+use watchexec_events::{Event, Priority, Tag};
 use watchexec_signals::Signal;
 
-use crate::ClientConfigPlugin;
+use crate::backup::{self, Undo};
+use crate::binary::{self, BinaryCheck};
+use crate::config::ConfigMerger;
+use crate::journal::{EntryState, Journal};
+use crate::lock;
+use crate::observer::{InstallEvent, InstallObserver};
+use crate::settings::Settings;
+use crate::state::StateDirs;
+use crate::store::ConfigStore;
+use crate::{ClientConfigPlugin, Platform, notify, paths, validate};
+
+/// Default quiet period before a changed config is processed
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Default interval between scans when polling for changes
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Watcher settings (the `[watcher]` table of the settings file)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatcherConfig {
+    /// Milliseconds a config has to stay unchanged before it's processed (defaults to 500)
+    ///
+    /// Editors save in bursts (temp file, rename, metadata), so every burst
+    /// of events for one config is coalesced into a single update.
+    pub debounce_ms: Option<u64>,
+
+    /// What to do when kodegen disappears from a config the watcher manages
+    pub drift: DriftPolicy,
+
+    /// How changes are noticed
+    pub backend: WatchBackend,
+
+    /// Milliseconds between scans when polling (defaults to 2000)
+    pub poll_interval_ms: Option<u64>,
+}
+
+/// How the watcher notices config changes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WatchBackend {
+    /// Native notifications, switching to polling on network filesystems and
+    /// when the platform runs out of watches (e.g. inotify's `max_user_watches`)
+    #[default]
+    Auto,
+    /// Native notifications only (inotify, FSEvents, `ReadDirectoryChangesW`)
+    Native,
+    /// Scan the watched directories periodically
+    Poll,
+}
+
+/// How the watcher handles a config that lost its kodegen entry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DriftPolicy {
+    /// Add the entry back (e.g. after an editor update reset its settings)
+    #[default]
+    Reinject,
+    /// Treat the removal as deliberate and leave the config alone from then on
+    Respect,
+}
+
+impl WatcherConfig {
+    /// Get the quiet period before a changed config is processed
+    #[must_use]
+    pub fn debounce(&self) -> Duration {
+        self.debounce_ms
+            .map_or(DEFAULT_DEBOUNCE, Duration::from_millis)
+    }
+
+    /// Get the interval between scans when polling
+    #[must_use]
+    pub fn poll_interval(&self) -> Duration {
+        self.poll_interval_ms
+            .map_or(DEFAULT_POLL_INTERVAL, Duration::from_millis)
+    }
+}
+
+/// Why watching stopped with an error
+#[derive(Debug, thiserror::Error)]
+enum WatchError {
+    #[error(transparent)]
+    Critical(Box<CriticalError>),
+    #[error("Watcher task failed: {0}")]
+    Task(#[from] tokio::task::JoinError),
+}
+
+impl From<CriticalError> for WatchError {
+    fn from(error: CriticalError) -> Self {
+        Self::Critical(Box::new(error))
+    }
+}
 
 /// Simple auto-configuration watcher
+///
+/// Clones share their state, which is how a [`Daemon`](crate::daemon::Daemon)
+/// controls the watcher it runs.
+#[derive(Clone)]
 pub struct AutoConfigWatcher {
     clients: Vec<Arc<dyn ClientConfigPlugin>>,
-    processing_files: Arc<DashMap<PathBuf, ()>>,
+    settings: Arc<RwLock<Arc<Settings>>>,
+    /// Configs waiting out their debounce window, with the time of their latest event
+    pending_changes: Arc<DashMap<PathBuf, Instant>>,
     active_tasks: Arc<AtomicUsize>,
+    /// Clients found installed so far; the others are looked out for while running
+    installed_clients: Arc<DashSet<String>>,
+    observer: Option<Arc<dyn InstallObserver>>,
+    /// Config events are ignored while set
+    paused: Arc<AtomicBool>,
+    /// Rebuilds the watched paths, e.g. when a client shows up
+    refresh_paths: Arc<Notify>,
+    /// Ends [`Self::run`] once the active tasks are done
+    shutdown: Arc<Notify>,
 }
 
 impl AutoConfigWatcher {
@@ -25,15 +139,28 @@ impl AutoConfigWatcher {
     ///
     /// # Errors
     ///
-    /// Returns an error if the watcher cannot be initialized.
+    /// Returns an error if the watcher cannot be initialized or the settings file is invalid.
     pub fn new(clients: Vec<Arc<dyn ClientConfigPlugin>>) -> Result<Self> {
         Ok(Self {
             clients,
-            processing_files: Arc::new(DashMap::new()),
+            settings: Arc::new(RwLock::new(Arc::new(Settings::load()?))),
+            pending_changes: Arc::new(DashMap::new()),
             active_tasks: Arc::new(AtomicUsize::new(0)),
+            installed_clients: Arc::new(DashSet::new()),
+            observer: None,
+            paused: Arc::new(AtomicBool::new(false)),
+            refresh_paths: Arc::new(Notify::new()),
+            shutdown: Arc::new(Notify::new()),
         })
     }
 
+    /// Report what the watcher does (such as [`InstallEvent::DriftDetected`]) to an observer
+    #[must_use]
+    pub fn with_observer(mut self, observer: Arc<dyn InstallObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
     /// Run the watcher with event-driven file system watching
     ///
     /// # Errors
@@ -48,36 +175,126 @@ impl AutoConfigWatcher {
         info!("✅ Initial scan complete. Setting up file watchers...");
 
         // Build list of all paths to watch
-        let watch_paths: Vec<PathBuf> = self
-            .clients
-            .iter()
-            .flat_map(|client| {
-                client.watch_paths().into_iter().chain(
-                    client
-                        .config_paths()
-                        .into_iter()
-                        .filter_map(|cp| cp.path.parent().map(std::path::Path::to_path_buf)),
-                )
-            })
-            .collect();
-
+        let settings_path = Settings::path();
+        let watch_paths = Self::watch_paths(&self.clients, &self.settings.read());
         if watch_paths.is_empty() {
             warn!("No paths to watch - exiting");
             return Ok(());
         }
 
+        let watcher_config = self.settings.read().watcher.clone();
+        let mut file_watcher = match watcher_config.backend {
+            WatchBackend::Poll => Watcher::Poll(watcher_config.poll_interval()),
+            WatchBackend::Auto if home_on_network_filesystem() => {
+                warn!(
+                    "⚠️  Home directory is on a network filesystem, polling every {:?} instead of watching",
+                    watcher_config.poll_interval()
+                );
+                Watcher::Poll(watcher_config.poll_interval())
+            }
+            WatchBackend::Auto | WatchBackend::Native => Watcher::Native,
+        };
+
+        loop {
+            match self
+                .watch(settings_path.clone(), watch_paths.clone(), file_watcher)
+                .await
+            {
+                Ok(()) => {
+                    info!("✅ Watcher shut down gracefully");
+                    return Ok(());
+                }
+                // Native watching can't even start (e.g. out of inotify instances)
+                Err(WatchError::Critical(critical))
+                    if watcher_config.backend == WatchBackend::Auto
+                        && file_watcher == Watcher::Native
+                        && matches!(*critical, CriticalError::FsWatcherInit { .. }) =>
+                {
+                    warn!(
+                        "⚠️  Native file watching failed ({critical}), falling back to polling every {:?}",
+                        watcher_config.poll_interval()
+                    );
+                    file_watcher = Watcher::Poll(watcher_config.poll_interval());
+                }
+                Err(e) => {
+                    error!("❌ Watcher error: {e}");
+                    return Err(e.into());
+                }
+            }
+        }
+    }
+
+    /// Watch the paths with the given backend until a signal or shutdown request
+    async fn watch(
+        &self,
+        settings_path: Option<PathBuf>,
+        watch_paths: Vec<WatchedPath>,
+        file_watcher: Watcher,
+    ) -> std::result::Result<(), WatchError> {
         // Create the watchexec instance with event handler
         let clients = self.clients.clone();
-        let processing_files = self.processing_files.clone();
+        let settings = self.settings.clone();
+        let pending_changes = self.pending_changes.clone();
         let active_tasks = self.active_tasks.clone();
+        let installed_clients = self.installed_clients.clone();
+        let observer = self.observer.clone();
+        let paused = self.paused.clone();
+        let stopping = Arc::new(AtomicBool::new(false));
+        let stop_requested = stopping.clone();
+        let refresh = self.refresh_paths.clone();
         let wx = Watchexec::new(move |mut action| {
+            // While paused events are dropped (a rescan catches up afterwards), as
+            // they are once shutting down
+            let events: &[Event] =
+                if paused.load(Ordering::SeqCst) || stopping.load(Ordering::SeqCst) {
+                    &[]
+                } else {
+                    &action.events
+                };
+
             // Extract file system events
-            for event in action.events.iter() {
+            for event in events {
+                // Opening or reading a config (as we do when processing it) changes nothing
+                if event
+                    .tags
+                    .iter()
+                    .any(|tag| matches!(tag, Tag::FileEventKind(FileEventKind::Access(_))))
+                {
+                    continue;
+                }
+
                 for tag in &event.tags {
                     if let Tag::Path { path, .. } = tag {
+                        if settings_path.as_deref() == Some(path.as_path()) {
+                            Self::reload_settings(&clients, &settings, observer.clone());
+                            refresh.notify_one();
+                            continue;
+                        }
+
+                        let settings = settings.read().clone();
+
+                        // A client installed since startup gets configured right away
+                        for client in clients
+                            .iter()
+                            .filter(|client| !installed_clients.contains(client.client_id()))
+                        {
+                            if client.watch_paths().iter().any(|dir| path.starts_with(dir))
+                                && settings.is_client_installed(client.as_ref())
+                            {
+                                installed_clients.insert(client.client_id().to_string());
+                                refresh.notify_one();
+                                Self::configure_new_client(
+                                    client.clone(),
+                                    settings.clone(),
+                                    active_tasks.clone(),
+                                    observer.clone(),
+                                );
+                            }
+                        }
+
                         // Find which client owns this path
                         for client in &clients {
-                            for config_path in client.config_paths() {
+                            for config_path in settings.config_paths_for(client.as_ref()) {
                                 // Only process the exact config file
                                 if config_path.path == *path {
                                     info!(
@@ -88,32 +305,43 @@ impl AutoConfigWatcher {
 
                                     let config_path_clone = config_path.path.clone();
 
-                                    // Check if already processing this file
-                                    if processing_files.contains_key(&config_path_clone) {
+                                    // Coalesce bursts: a config already waiting just restarts its window
+                                    if pending_changes
+                                        .insert(config_path_clone.clone(), Instant::now())
+                                        .is_some()
+                                    {
                                         debug!(
-                                            "Already processing {}, skipping duplicate event",
+                                            "Coalescing event for {}",
                                             config_path_clone.display()
                                         );
                                         break;
                                     }
 
-                                    // Mark as in-progress
-                                    processing_files.insert(config_path_clone.clone(), ());
                                     active_tasks.fetch_add(1, Ordering::SeqCst);
 
-                                    // Process the config file asynchronously
+                                    // Process the config file once it has settled
                                     let client_clone = client.clone();
-                                    let processing_files_clone = processing_files.clone();
+                                    let settings_clone = settings.clone();
+                                    let pending_changes_clone = pending_changes.clone();
                                     let active_tasks_clone = active_tasks.clone();
+                                    let observer_clone = observer.clone();
                                     tokio::spawn(async move {
+                                        Self::wait_until_settled(
+                                            &pending_changes_clone,
+                                            &config_path_clone,
+                                            settings_clone.watcher.debounce(),
+                                        )
+                                        .await;
+
                                         let result = Self::process_config_file_static(
                                             client_clone.as_ref(),
                                             &config_path_clone,
+                                            &settings_clone.merger_for(client_clone.as_ref()),
+                                            &settings_clone,
+                                            observer_clone.as_deref(),
                                         )
                                         .await;
 
-                                        // Remove from in-progress when done
-                                        processing_files_clone.remove(&config_path_clone);
                                         active_tasks_clone.fetch_sub(1, Ordering::SeqCst);
 
                                         if let Err(e) = result {
@@ -129,13 +357,14 @@ impl AutoConfigWatcher {
                 }
             }
 
-            // Handle shutdown signals
-            if action
-                .signals()
-                .any(|sig| matches!(sig, Signal::Interrupt | Signal::Terminate))
+            // Handle shutdown signals and requests
+            if stopping.load(Ordering::SeqCst)
+                || action
+                    .signals()
+                    .any(|sig| matches!(sig, Signal::Interrupt | Signal::Terminate))
             {
                 info!(
-                    "🛑 Received shutdown signal, waiting for {} active tasks...",
+                    "🛑 Shutting down, waiting for {} active tasks...",
                     active_tasks.load(Ordering::SeqCst)
                 );
 
@@ -151,66 +380,424 @@ impl AutoConfigWatcher {
             action
         })?;
 
+        // Native watches can run out while running (e.g. inotify's
+        // max_user_watches); switch to polling instead of missing changes
+        let watcher_config = self.settings.read().watcher.clone();
+        let config = Arc::downgrade(&wx.config);
+        let fell_back = AtomicBool::new(false);
+        wx.config.on_error(move |hook: ErrorHook| {
+            if let RuntimeError::FsWatcher {
+                kind: Watcher::Native,
+                err:
+                    err @ (FsWatcherError::Create(_)
+                    | FsWatcherError::TooManyWatches(_)
+                    | FsWatcherError::TooManyHandles(_)),
+            } = &hook.error
+                && watcher_config.backend == WatchBackend::Auto
+                && !fell_back.swap(true, Ordering::SeqCst)
+                && let Some(config) = config.upgrade()
+            {
+                warn!(
+                    "⚠️  Native file watching failed ({err}), falling back to polling every {:?}",
+                    watcher_config.poll_interval()
+                );
+                config.file_watcher(Watcher::Poll(watcher_config.poll_interval()));
+                return;
+            }
+            debug!("File watcher error: {}", hook.error);
+        });
+        wx.config.file_watcher(file_watcher);
+
         // Configure the paths to watch
         info!("👁️  Watching {} directories for changes", watch_paths.len());
-        for path in &watch_paths {
+        for path in watch_paths.iter().map(AsRef::<Path>::as_ref) {
             if path.exists() {
                 info!("   - {}", path.display());
             }
         }
         wx.config.pathset(watch_paths);
 
+        let refresher = tokio::spawn({
+            let wx = wx.clone();
+            let clients = self.clients.clone();
+            let settings = self.settings.clone();
+            let refresh_paths = self.refresh_paths.clone();
+            async move {
+                loop {
+                    refresh_paths.notified().await;
+                    let watch_paths = Self::watch_paths(&clients, &settings.read());
+                    debug!("Now watching {} directories", watch_paths.len());
+                    wx.config.pathset(watch_paths);
+                }
+            }
+        });
+
         // Start the watchexec main loop
-        let main = wx.main();
+        let mut main = wx.main();
 
-        // Run until shutdown
-        match main.await {
-            Ok(_) => {
-                info!("✅ Watcher shut down gracefully");
-                Ok(())
+        // Run until a signal or a shutdown request. Requested shutdowns let the
+        // active tasks finish here rather than blocking in the handler, then
+        // wake the handler up with an empty event to quit
+        let result = tokio::select! {
+            result = &mut main => result,
+            () = self.shutdown.notified() => {
+                stop_requested.store(true, Ordering::SeqCst);
+                while self.active_tasks.load(Ordering::SeqCst) > 0 {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+                wx.send_event(Event::default(), Priority::Urgent).await?;
+                main.await
             }
-            Err(e) => {
-                error!("❌ Watcher error: {e}");
-                Err(e.into())
+        };
+        refresher.abort();
+        Ok(result??)
+    }
+
+    /// Stop a running [`Self::run`] once its active tasks are done
+    pub(crate) fn request_shutdown(&self) {
+        self.shutdown.notify_one();
+    }
+
+    /// Stop or resume reacting to config changes
+    pub(crate) fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::SeqCst);
+    }
+
+    /// Check whether config changes are currently ignored
+    pub(crate) fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Scan every client again, configuring new installations and catching up on missed changes
+    pub(crate) async fn rescan(&self) -> Result<()> {
+        self.perform_initial_scan().await?;
+        self.refresh_paths.notify_one();
+        Ok(())
+    }
+
+    /// Get the ids of the clients found installed so far
+    pub(crate) fn installed_clients(&self) -> Vec<String> {
+        let mut client_ids: Vec<String> =
+            self.installed_clients.iter().map(|id| id.clone()).collect();
+        client_ids.sort();
+        client_ids
+    }
+
+    /// Get the number of configs waiting out their debounce window
+    pub(crate) fn pending_changes(&self) -> usize {
+        self.pending_changes.len()
+    }
+
+    /// Get the number of configs being processed
+    pub(crate) fn active_tasks(&self) -> usize {
+        self.active_tasks.load(Ordering::SeqCst)
+    }
+
+    /// Get the directories to watch for the clients and our own settings
+    ///
+    /// Config files can sit directly in the home directory (e.g. `~/.claude.json`),
+    /// so their parents are watched without descending into subdirectories. The
+    /// parents of client directories (such as `~/Library/Application Support`)
+    /// are watched the same way, to notice clients installed while running.
+    fn watch_paths(
+        clients: &[Arc<dyn ClientConfigPlugin>],
+        settings: &Settings,
+    ) -> Vec<WatchedPath> {
+        let mut watch_paths: Vec<WatchedPath> = clients
+            .iter()
+            .flat_map(|client| {
+                let client_dirs = client.watch_paths();
+                let parent_dirs: Vec<WatchedPath> = client_dirs
+                    .iter()
+                    .filter_map(|dir| dir.parent().map(WatchedPath::non_recursive))
+                    .collect();
+
+                client_dirs
+                    .into_iter()
+                    .map(WatchedPath::recursive)
+                    .chain(parent_dirs)
+                    .chain(
+                        settings
+                            .config_paths_for(client.as_ref())
+                            .into_iter()
+                            .filter_map(|cp| cp.path.parent().map(WatchedPath::non_recursive)),
+                    )
+            })
+            .collect();
+
+        // Watch our own settings so server definition changes reach every client
+        if let Some(settings_dir) = Settings::path().as_deref().and_then(Path::parent) {
+            watch_paths.push(WatchedPath::non_recursive(settings_dir));
+        }
+
+        watch_paths.sort_by(|a, b| AsRef::<Path>::as_ref(a).cmp(b.as_ref()));
+        watch_paths.dedup();
+        watch_paths
+    }
+
+    /// Configure a client that was installed while the watcher is running
+    fn configure_new_client(
+        client: Arc<dyn ClientConfigPlugin>,
+        settings: Arc<Settings>,
+        active_tasks: Arc<AtomicUsize>,
+        observer: Option<Arc<dyn InstallObserver>>,
+    ) {
+        info!("🆕 Found new {} installation", client.client_name());
+        if let Some(observer) = &observer {
+            observer.on_event(&InstallEvent::ClientDetected {
+                client_id: client.client_id().to_string(),
+                client_name: client.client_name().to_string(),
+            });
+        }
+
+        active_tasks.fetch_add(1, Ordering::SeqCst);
+        tokio::spawn(async move {
+            let merger = settings.merger_for(client.as_ref());
+            for config_path in Self::config_paths_to_write(client.as_ref(), &settings) {
+                if let Err(e) = Self::process_config_file_impl(
+                    client.as_ref(),
+                    &config_path,
+                    &merger,
+                    &settings,
+                    observer.as_deref(),
+                )
+                .await
+                {
+                    error!(
+                        "Failed to process config for {}: {}",
+                        client.client_name(),
+                        e
+                    );
+                }
+            }
+            active_tasks.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+
+    /// Wait until a config has had no events for the debounce window
+    ///
+    /// The config stops being pending once this returns, so events arriving
+    /// while it's processed schedule another pass rather than being lost.
+    async fn wait_until_settled(
+        pending_changes: &DashMap<PathBuf, Instant>,
+        path: &Path,
+        debounce: Duration,
+    ) {
+        loop {
+            let Some(last_event) = pending_changes.get(path).map(|entry| *entry) else {
+                return;
+            };
+
+            let deadline = last_event + debounce;
+            if Instant::now() < deadline {
+                sleep_until(deadline).await;
+                continue;
+            }
+
+            // Only stop waiting if no event slipped in since the window was checked
+            if pending_changes
+                .remove_if(path, |_, latest| *latest == last_event)
+                .is_some()
+            {
+                return;
             }
         }
     }
 
     /// Perform the initial scan of all clients
     async fn perform_initial_scan(&self) -> Result<()> {
+        let settings = self.settings.read().clone();
+
         for client in &self.clients {
             info!("Checking for {} installation", client.client_name());
 
-            for watch_path in client.watch_paths() {
-                if client.is_installed(&watch_path) {
-                    info!("Found {} at {}", client.client_name(), watch_path.display());
+            if !settings.is_client_installed(client.as_ref()) {
+                continue;
+            }
 
-                    for config_path in client.config_paths() {
-                        if let Err(e) = self
-                            .process_config_file(client.as_ref(), &config_path.path)
-                            .await
-                        {
-                            error!(
-                                "Failed to process config for {}: {}",
-                                client.client_name(),
-                                e
-                            );
-                        }
-                    }
+            info!("Found {} installation", client.client_name());
+            self.installed_clients
+                .insert(client.client_id().to_string());
+
+            let merger = settings.merger_for(client.as_ref());
+            for config_path in Self::config_paths_to_write(client.as_ref(), &settings) {
+                if let Err(e) = self
+                    .process_config_file(client.as_ref(), &config_path, &merger, &settings)
+                    .await
+                {
+                    error!(
+                        "Failed to process config for {}: {}",
+                        client.client_name(),
+                        e
+                    );
                 }
             }
         }
         Ok(())
     }
 
+    /// Reload settings after they change on disk, propagating a changed server definition
+    fn reload_settings(
+        clients: &[Arc<dyn ClientConfigPlugin>],
+        settings: &Arc<RwLock<Arc<Settings>>>,
+        observer: Option<Arc<dyn InstallObserver>>,
+    ) {
+        let new_settings = match Settings::load() {
+            Ok(new_settings) => Arc::new(new_settings),
+            Err(e) => {
+                error!("Failed to reload settings, keeping previous ones: {e}");
+                return;
+            }
+        };
+
+        let old_settings = std::mem::replace(&mut *settings.write(), new_settings.clone());
+        if old_settings.server == new_settings.server
+            && old_settings.transport == new_settings.transport
+            && old_settings.http == new_settings.http
+            && old_settings.servers == new_settings.servers
+        {
+            debug!("Settings reloaded, server definition unchanged");
+            return;
+        }
+
+        info!("🔄 kodegen server definition changed, updating all clients");
+        let clients = clients.to_vec();
+        tokio::spawn(async move {
+            Self::propagate_server_change(&clients, &new_settings, observer.as_deref()).await;
+        });
+    }
+
+    /// Rewrite every managed kodegen entry to match the current server definition
+    async fn propagate_server_change(
+        clients: &[Arc<dyn ClientConfigPlugin>],
+        settings: &Settings,
+        observer: Option<&dyn InstallObserver>,
+    ) {
+        for client in clients {
+            if !settings.is_client_installed(client.as_ref()) {
+                continue;
+            }
+
+            let merger = settings
+                .merger_for(client.as_ref())
+                .with_update_existing(true);
+
+            for config_path in Self::config_paths_to_write(client.as_ref(), settings) {
+                if let Err(e) = Self::process_config_file_impl(
+                    client.as_ref(),
+                    &config_path,
+                    &merger,
+                    settings,
+                    observer,
+                )
+                .await
+                {
+                    error!(
+                        "Failed to update config for {}: {}",
+                        client.client_name(),
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    /// Get the config paths to configure for a client, creating only its primary config
+    fn config_paths_to_write(client: &dyn ClientConfigPlugin, settings: &Settings) -> Vec<PathBuf> {
+        paths::primary_or_existing(
+            settings
+                .config_paths_for(client)
+                .into_iter()
+                .map(|config_path| config_path.path),
+        )
+    }
+
     /// Process a single config file (shared implementation)
-    async fn process_config_file_impl(client: &dyn ClientConfigPlugin, path: &Path) -> Result<()> {
+    async fn process_config_file_impl(
+        client: &dyn ClientConfigPlugin,
+        path: &Path,
+        merger: &ConfigMerger,
+        settings: &Settings,
+        observer: Option<&dyn InstallObserver>,
+    ) -> Result<()> {
+        if settings.binary_check == BinaryCheck::Refuse
+            && let Err(e) = binary::verify_merger(merger)
+        {
+            warn!("Not configuring {}: {e}", path.display());
+            return Ok(());
+        }
+
+        let state_dirs = StateDirs::resolve()?;
+        let merger = &merger.clone().for_config(client, path);
+
+        // Hold the file against a concurrent install until we're done
+        let _lock = lock::lock_config(&state_dirs, path).await?;
+
+        // Never fight the user: leave configs they took kodegen out of (or undid) alone
+        let mut journal = Journal::load(&state_dirs)?;
+        if journal
+            .get(path)
+            .is_some_and(|entry| entry.state.is_removed())
+        {
+            debug!(
+                "Skipping {}: kodegen was removed by the user",
+                path.display()
+            );
+            return Ok(());
+        }
+
+        // The editor may rewrite the config again while we work; recompute until we win
+        for _ in 0..lock::MAX_UPDATE_ATTEMPTS {
+            if Self::update_config_file(
+                client,
+                path,
+                merger,
+                settings,
+                observer,
+                &mut journal,
+                &state_dirs,
+            )
+            .await?
+            {
+                return Ok(());
+            }
+            debug!("{} changed while being updated, retrying", path.display());
+        }
+
+        bail!("{} kept changing while being updated", path.display())
+    }
+
+    /// Read, update and write back a config once
+    ///
+    /// A config we configured before that lost its kodegen entry has drifted;
+    /// it's handled according to [`WatcherConfig::drift`]. Returns `false` if
+    /// the config changed before the update could be written.
+    async fn update_config_file(
+        client: &dyn ClientConfigPlugin,
+        path: &Path,
+        merger: &ConfigMerger,
+        settings: &Settings,
+        observer: Option<&dyn InstallObserver>,
+        journal: &mut Journal,
+        state_dirs: &StateDirs,
+    ) -> Result<bool> {
+        let managed = journal
+            .get(path)
+            .is_some_and(|entry| entry.state == EntryState::Installed);
+
         // Read existing config if it exists
-        let config_content = match fs::read_to_string(path).await {
+        let store = ConfigStore::new(client, path);
+        let config_content = match store.read() {
             Ok(content) => content,
             Err(e) if e.kind() == ErrorKind::NotFound => {
+                if managed
+                    && !Self::reinject_drift(client, path, settings, observer, journal, state_dirs)?
+                {
+                    return Ok(true);
+                }
+
                 // Config doesn't exist yet - create it
-                let new_config = client.inject_kodegen("{}", client.config_format())?;
+                let new_config = validate::inject_validated(client, "", merger)?;
 
                 // Ensure directory exists
                 if let Some(parent) = path.parent() {
@@ -218,14 +805,32 @@ impl AutoConfigWatcher {
                 }
 
                 // Write new config
-                fs::write(path, &new_config).await?;
+                let written = lock::commit_if_unchanged(store, None, || {
+                    store.write(&new_config).map_err(Into::into)
+                })
+                .await?;
+                if !written {
+                    return Ok(false);
+                }
+
+                record_in_journal(client, path, journal, state_dirs, EntryState::Installed)?;
                 info!(
                     "Created KODEGEN.ᴀɪ config for {} at {}",
                     client.client_name(),
                     path.display()
                 );
 
-                return Ok(());
+                if settings.notifications {
+                    notify::notify_configured(
+                        client,
+                        path,
+                        Some(Undo::RemoveConfig {
+                            written: new_config,
+                        }),
+                    );
+                }
+
+                return Ok(true);
             }
             Err(e) => {
                 // Propagate real errors (permissions, I/O, etc.)
@@ -233,49 +838,102 @@ impl AutoConfigWatcher {
             }
         };
 
-        // Check if already configured (fast string search)
-        if config_content.contains("kodegen") {
+        // Inject configuration
+        let updated_config = validate::inject_validated(client, &config_content, merger)?;
+
+        // Already configured: the merge left the config untouched
+        if updated_config == config_content {
             debug!("KODEGEN.ᴀɪ already configured for {}", client.client_name());
-            return Ok(());
+            record_in_journal(client, path, journal, state_dirs, EntryState::Installed)?;
+            return Ok(true);
         }
 
-        // Inject configuration
-        let updated_config = client.inject_kodegen(&config_content, client.config_format())?;
-
-        // Create backup with preserved filename
-        let backup_path = {
-            let mut bp = path.to_path_buf();
-            if let Some(filename) = bp.file_name() {
-                let mut new_name = filename.to_os_string();
-                new_name.push(".backup");
-                bp.set_file_name(new_name);
-            }
-            bp
-        };
+        // An entry that is merely outdated gets updated; a missing one has drifted
+        if managed
+            && !client.is_kodegen_configured(&config_content, merger)
+            && !Self::reinject_drift(client, path, settings, observer, journal, state_dirs)?
+        {
+            return Ok(true);
+        }
 
-        // Fail-fast if backup fails (don't risk data loss)
-        fs::copy(path, &backup_path)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to create backup: {e}"))?;
+        let mut backup_path = None;
+        let written = lock::commit_if_unchanged(store, Some(&config_content), || {
+            // Create backup in the autoconfig state directory
+            // Fail-fast if backup fails (don't risk data loss)
+            backup_path = Some(backup::create_backup(state_dirs, client.client_id(), path)?);
 
-        // Write updated config
-        fs::write(path, &updated_config).await?;
+            // Write updated config
+            store.write(&updated_config).map_err(Into::into)
+        })
+        .await?;
+        if !written {
+            return Ok(false);
+        }
 
+        record_in_journal(client, path, journal, state_dirs, EntryState::Installed)?;
         info!(
             "Injected KODEGEN.ᴀɪ config for {} at {}",
             client.client_name(),
             path.display()
         );
 
-        Ok(())
+        if settings.notifications {
+            let undo = backup_path.map(|backup_path| Undo::RestoreBackup {
+                backup_path,
+                written: updated_config,
+            });
+            notify::notify_configured(client, path, undo);
+        }
+
+        Ok(true)
+    }
+
+    /// Report a config whose kodegen entry disappeared and decide whether to add it back
+    ///
+    /// With [`DriftPolicy::Respect`] the config is journaled as removed by the
+    /// user, so neither the watcher nor later installs touch it again.
+    fn reinject_drift(
+        client: &dyn ClientConfigPlugin,
+        path: &Path,
+        settings: &Settings,
+        observer: Option<&dyn InstallObserver>,
+        journal: &mut Journal,
+        state_dirs: &StateDirs,
+    ) -> Result<bool> {
+        let reinject = settings.watcher.drift == DriftPolicy::Reinject;
+        if reinject {
+            info!(
+                "🔁 kodegen was removed from {}, adding it back",
+                path.display()
+            );
+        } else {
+            info!(
+                "⏭️  kodegen was removed from {}, leaving it out",
+                path.display()
+            );
+            record_in_journal(client, path, journal, state_dirs, EntryState::UserRemoved)?;
+        }
+
+        if let Some(observer) = observer {
+            observer.on_event(&InstallEvent::DriftDetected {
+                client_id: client.client_id().to_string(),
+                config_path: path.to_path_buf(),
+                reinjected: reinject,
+            });
+        }
+
+        Ok(reinject)
     }
 
     /// Static version for use in watchexec callback
     async fn process_config_file_static(
         client: &dyn ClientConfigPlugin,
         path: &Path,
+        merger: &ConfigMerger,
+        settings: &Settings,
+        observer: Option<&dyn InstallObserver>,
     ) -> Result<()> {
-        Self::process_config_file_impl(client, path).await
+        Self::process_config_file_impl(client, path, merger, settings, observer).await
     }
 
     /// Process a single config file
@@ -283,7 +941,100 @@ impl AutoConfigWatcher {
         &self,
         client: &dyn ClientConfigPlugin,
         path: &Path,
+        merger: &ConfigMerger,
+        settings: &Settings,
     ) -> Result<()> {
-        Self::process_config_file_impl(client, path).await
+        Self::process_config_file_impl(client, path, merger, settings, self.observer.as_deref())
+            .await
+    }
+}
+
+/// Record a config file's state in the journal, saving it only when something changed
+fn record_in_journal(
+    client: &dyn ClientConfigPlugin,
+    path: &Path,
+    journal: &mut Journal,
+    state_dirs: &StateDirs,
+    state: EntryState,
+) -> Result<()> {
+    if journal
+        .get(path)
+        .is_some_and(|entry| entry.state == state && entry.client_id == client.client_id())
+    {
+        return Ok(());
     }
+
+    journal.commit(state_dirs, client.client_id(), path, state)
+}
+
+/// Check whether the home directory is on a network filesystem, where native
+/// notifications miss changes made from other machines (or don't work at all)
+fn home_on_network_filesystem() -> bool {
+    let Some(home) = paths::home_dir() else {
+        return false;
+    };
+
+    match Platform::native() {
+        Platform::Windows => home.to_string_lossy().starts_with(r"\\"),
+        Platform::MacOS => std::process::Command::new("mount")
+            .output()
+            .is_ok_and(|output| {
+                // `<device> on <mount point> (<type>, <options>)`
+                let mounts = String::from_utf8_lossy(&output.stdout);
+                filesystem_type(
+                    &home,
+                    mounts.lines().filter_map(|line| {
+                        let (_, rest) = line.split_once(" on ")?;
+                        let (mount_point, options) = rest.rsplit_once(" (")?;
+                        let fs_type = options.split([',', ')']).next()?;
+                        Some((mount_point.to_string(), fs_type.trim().to_string()))
+                    }),
+                )
+                .is_some_and(|fs_type| is_network_filesystem(&fs_type))
+            }),
+        Platform::Linux | Platform::All => {
+            std::fs::read_to_string("/proc/self/mounts").is_ok_and(|mounts| {
+                // `<device> <mount point> <type> <options> ...`, spaces escaped as `\040`
+                filesystem_type(
+                    &home,
+                    mounts.lines().filter_map(|line| {
+                        let mut fields = line.split_whitespace().skip(1);
+                        let mount_point = fields.next()?.replace("\\040", " ");
+                        Some((mount_point, fields.next()?.to_string()))
+                    }),
+                )
+                .is_some_and(|fs_type| is_network_filesystem(&fs_type))
+            })
+        }
+    }
+}
+
+/// Find the type of the filesystem mounted closest above a path
+fn filesystem_type(path: &Path, mounts: impl Iterator<Item = (String, String)>) -> Option<String> {
+    mounts
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.len())
+        .map(|(_, fs_type)| fs_type)
+}
+
+/// Check whether a filesystem type is network-backed
+fn is_network_filesystem(fs_type: &str) -> bool {
+    matches!(
+        fs_type,
+        "nfs"
+            | "nfs4"
+            | "cifs"
+            | "smb3"
+            | "smbfs"
+            | "afpfs"
+            | "webdav"
+            | "davfs"
+            | "afs"
+            | "9p"
+            | "ceph"
+            | "glusterfs"
+            | "lustre"
+            | "fuse.sshfs"
+            | "fuse.rclone"
+    )
 }
@@ -0,0 +1,183 @@
+//! Configuring Windows-side clients from inside WSL
+//!
+//! Under WSL, clients such as Claude Desktop and Cursor usually run on the
+//! Windows host, with their configs in the Windows user profile (reachable
+//! as `/mnt/c/Users/<name>`). They can't start a Linux binary directly, so
+//! the server definition is rewritten to go through `wsl.exe` (or to a
+//! Windows path, when kodegen itself lives on a Windows drive).
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::{KodegenConfig, Platform, binary, paths};
+
+/// Check whether this process runs under WSL
+#[must_use]
+pub fn is_wsl() -> bool {
+    if !cfg!(target_os = "linux") {
+        return false;
+    }
+
+    std::env::var_os("WSL_DISTRO_NAME").is_some()
+        || std::fs::read_to_string("/proc/sys/kernel/osrelease")
+            .is_ok_and(|release| release.to_lowercase().contains("microsoft"))
+}
+
+/// Find the Windows user profile (`%USERPROFILE%`) as a WSL path
+///
+/// Asks `cmd.exe` and translates its answer with `wslpath`; without Windows
+/// interop, falls back to the only user folder in `/mnt/c/Users`.
+#[must_use]
+pub fn windows_user_profile() -> Option<PathBuf> {
+    if !is_wsl() {
+        return None;
+    }
+
+    let from_cmd = Command::new("cmd.exe")
+        .args(["/c", "echo %USERPROFILE%"])
+        // cmd.exe warns about, and ignores, a current directory inside WSL
+        .current_dir("/mnt/c")
+        .output()
+        .ok()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|profile| !profile.is_empty() && !profile.contains('%'))
+        .and_then(|profile| wsl_path(&profile))
+        .filter(|profile| profile.is_dir());
+    if from_cmd.is_some() {
+        return from_cmd;
+    }
+
+    let skip = ["Public", "Default", "Default User", "All Users"];
+    let mut profiles = std::fs::read_dir("/mnt/c/Users")
+        .ok()?
+        .flatten()
+        .filter(|entry| !skip.iter().any(|name| entry.file_name() == *name))
+        .map(|entry| entry.path())
+        .filter(|path| path.join("AppData").is_dir());
+    let profile = profiles.next()?;
+    profiles.next().is_none().then_some(profile)
+}
+
+/// Translate a Windows path (`C:\Users\...`) to the WSL path it's mounted at
+#[must_use]
+pub fn wsl_path(windows_path: &str) -> Option<PathBuf> {
+    let output = Command::new("wslpath")
+        .args(["-u", windows_path])
+        .output()
+        .ok()
+        .filter(|output| output.status.success());
+    if let Some(output) = output {
+        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !path.is_empty() {
+            return Some(PathBuf::from(path));
+        }
+    }
+
+    // The default automount layout, for when wslpath isn't available
+    let (drive, rest) = windows_path.split_once(":\\")?;
+    let drive = drive.to_lowercase();
+    (drive.len() == 1).then(|| {
+        rest.split('\\')
+            .filter(|part| !part.is_empty())
+            .fold(PathBuf::from("/mnt").join(drive), |path, part| {
+                path.join(part)
+            })
+    })
+}
+
+/// Translate a WSL path on a Windows drive (`/mnt/c/...`) to the Windows path
+///
+/// Returns `None` for paths only reachable inside WSL.
+#[must_use]
+pub fn windows_path(path: &Path) -> Option<String> {
+    let mut components = path.strip_prefix("/mnt").ok()?.components();
+    let drive = components.next()?.as_os_str().to_str()?;
+    if drive.len() != 1 || !drive.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let rest: Vec<String> = components
+        .map(|part| part.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    Some(format!("{}:\\{}", drive.to_uppercase(), rest.join("\\")))
+}
+
+/// Get the config files of the Windows-side clients to configure, by client id
+///
+/// Each registered client's config paths are laid out as on Windows for the
+/// user owning `profile` (see [`paths::with_platform`]). Clients the plugin
+/// detects as installed there get those of their primary and existing
+/// configs that are inside the profile and whose directory exists.
+#[must_use]
+pub fn host_config_paths(profile: &Path) -> Vec<(String, Vec<PathBuf>)> {
+    let clients = crate::clients::registry();
+
+    paths::with_platform(Platform::Windows, || {
+        paths::with_user_home(profile, || {
+            clients
+                .iter()
+                .filter(|client| {
+                    client
+                        .watch_paths()
+                        .iter()
+                        .any(|path| path.starts_with(profile) && client.is_installed(path))
+                })
+                .map(|client| {
+                    let config_paths: Vec<PathBuf> = paths::primary_or_existing(
+                        client
+                            .config_paths()
+                            .into_iter()
+                            .map(|config_path| config_path.path),
+                    )
+                    .into_iter()
+                    .filter(|path| path.starts_with(profile))
+                    .filter(|path| path.parent().is_some_and(Path::is_dir))
+                    .collect();
+                    (client.client_id().to_string(), config_paths)
+                })
+                .filter(|(_, config_paths)| !config_paths.is_empty())
+                .collect()
+        })
+    })
+}
+
+/// Rewrite a server definition so a Windows-side client can start it
+///
+/// A command on a Windows drive becomes its Windows path. Anything else runs
+/// inside this distribution through `wsl.exe`, by absolute path (clients don't
+/// start a login shell) and with the env passed through `env`.
+#[must_use]
+pub fn host_server_config(config: &KodegenConfig) -> KodegenConfig {
+    let command =
+        binary::verify_command(&config.command).unwrap_or_else(|_| PathBuf::from(&config.command));
+
+    if let Some(command) = windows_path(&command) {
+        return KodegenConfig {
+            command,
+            ..config.clone()
+        };
+    }
+
+    let mut args = Vec::new();
+    if let Ok(distro) = std::env::var("WSL_DISTRO_NAME") {
+        args.extend(["-d".to_string(), distro]);
+    }
+    args.push("--".to_string());
+
+    let env = config.env.as_ref().and_then(serde_json::Value::as_object);
+    if let Some(env) = env.filter(|env| !env.is_empty()) {
+        args.push("env".to_string());
+        args.extend(env.iter().map(|(key, value)| match value {
+            serde_json::Value::String(value) => format!("{key}={value}"),
+            other => format!("{key}={other}"),
+        }));
+    }
+    args.push(command.display().to_string());
+    args.extend(config.args.iter().cloned());
+
+    KodegenConfig {
+        command: "wsl.exe".to_string(),
+        args,
+        env: None,
+    }
+}
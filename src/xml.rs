@@ -0,0 +1,374 @@
+//! Reading and text editing of XML configs
+//!
+//! Servers are the children of a `<mcpServers>` element (or another `key`)
+//! under the root, each named by a `name` attribute:
+//!
+//! ```text
+//! <server name="kodegen">
+//!   <command>kodegen</command>
+//!   <args>
+//!     <item>--stdio</item>
+//!   </args>
+//!   <env>
+//!     <RUST_LOG>info</RUST_LOG>
+//!   </env>
+//! </server>
+//! ```
+//!
+//! Read as JSON, an element whose children are all `<item>`s is an array,
+//! one with other children an object keyed by their `name` (or `key`)
+//! attribute or else their tag, and one without children its text.
+//!
+//! Edits are made as text at the indentation of the surrounding elements, so
+//! comments, processing instructions and the rest of the file stay
+//! byte-for-byte intact.
+
+use std::ops::Range;
+
+use roxmltree::{Document, Node};
+use serde_json::{Map, Value as JsonValue};
+
+/// Deepest element nesting read, to keep the recursive conversion off the
+/// end of the stack; matches the limits of the other parsers
+const MAX_DEPTH: usize = 128;
+
+/// Indentation step when the document gives no hint
+const DEFAULT_INDENT: &str = "  ";
+
+/// Root element of a config created from scratch
+const ROOT: &str = "config";
+
+/// Why an XML config couldn't be read
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum XmlError {
+    #[error(transparent)]
+    Syntax(#[from] roxmltree::Error),
+    #[error("elements are nested deeper than {MAX_DEPTH} levels")]
+    TooDeep,
+}
+
+/// Read an XML config as JSON: the value of its root element
+pub(crate) fn parse(text: &str) -> Result<JsonValue, XmlError> {
+    let document = Document::parse(text)?;
+    to_json(document.root_element(), 0)
+}
+
+/// Read an element as JSON
+fn to_json(node: Node<'_, '_>, depth: usize) -> Result<JsonValue, XmlError> {
+    if depth > MAX_DEPTH {
+        return Err(XmlError::TooDeep);
+    }
+
+    let children: Vec<Node<'_, '_>> = node.children().filter(Node::is_element).collect();
+    if children.is_empty() {
+        let text: String = node
+            .children()
+            .filter(Node::is_text)
+            .filter_map(|child| child.text())
+            .collect();
+        return Ok(if text.trim().is_empty() {
+            String::new()
+        } else {
+            text
+        }
+        .into());
+    }
+
+    if children.iter().all(|child| child.has_tag_name("item")) {
+        return children
+            .into_iter()
+            .map(|child| to_json(child, depth + 1))
+            .collect::<Result<Vec<_>, _>>()
+            .map(JsonValue::Array);
+    }
+
+    let mut object = Map::new();
+    for child in children {
+        // The first of several elements with one key counts, like in edits
+        if !object.contains_key(key_of(child)) {
+            object.insert(key_of(child).to_string(), to_json(child, depth + 1)?);
+        }
+    }
+    Ok(object.into())
+}
+
+/// Key of an element in its parent's object
+fn key_of<'a>(node: Node<'a, '_>) -> &'a str {
+    node.attribute("name")
+        .or_else(|| node.attribute("key"))
+        .unwrap_or_else(|| node.tag_name().name())
+}
+
+/// First child element of `node` with the given key
+fn child<'a, 'input>(node: Node<'a, 'input>, key: &str) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|child| child.is_element() && key_of(*child) == key)
+}
+
+/// Write a whole config under a `<config>` root
+pub(crate) fn render_document(config: &JsonValue) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{}\n",
+        render(ROOT, config, "", DEFAULT_INDENT)
+    )
+}
+
+/// Get an entry the way it reads back once written
+///
+/// Empty arrays and objects come back as empty text, and other scalars as
+/// their text, so existing entries are compared against this rather than the
+/// entry itself.
+pub(crate) fn written_form(entry: &JsonValue) -> JsonValue {
+    let rendered = render("server", entry, "", DEFAULT_INDENT);
+    parse(&rendered).unwrap_or_else(|_| entry.clone())
+}
+
+/// Set server `name` under `key` to `entry`, replacing its element where it is
+/// or adding one after the last
+///
+/// `None` if the document can't be read.
+pub(crate) fn upsert_server(
+    text: &str,
+    key: &str,
+    name: &str,
+    entry: &JsonValue,
+) -> Option<String> {
+    if text.trim().is_empty() {
+        let server = render_server(
+            name,
+            entry,
+            &format!("{DEFAULT_INDENT}{DEFAULT_INDENT}"),
+            DEFAULT_INDENT,
+        );
+        return Some(format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<{ROOT}>\n{DEFAULT_INDENT}<{key}>\n{DEFAULT_INDENT}{DEFAULT_INDENT}{server}\n{DEFAULT_INDENT}</{key}>\n</{ROOT}>\n"
+        ));
+    }
+
+    let document = Document::parse(text).ok()?;
+    let root = document.root_element();
+    let unit = indent_unit(text, root);
+
+    let Some(servers) = child(root, key) else {
+        let indent = format!("{}{unit}", indent_of(text, root));
+        let server = render_server(name, entry, &format!("{indent}{unit}"), unit);
+        let rendered = format!("<{key}>\n{indent}{unit}{server}\n{indent}</{key}>");
+        return Some(append_child(text, root, &rendered));
+    };
+
+    match child(servers, name) {
+        Some(existing) => {
+            let rendered = render_server(name, entry, indent_of(text, existing), unit);
+            Some(apply(text, vec![(existing.range(), rendered)]))
+        }
+        None => {
+            let indent = format!("{}{unit}", indent_of(text, servers));
+            let rendered = render_server(name, entry, &indent, unit);
+            Some(append_child(text, servers, &rendered))
+        }
+    }
+}
+
+/// Remove servers `names` under `key`, and the `key` element too (with any
+/// comments in it) if no other elements are left in it
+pub(crate) fn remove_servers(text: &str, key: &str, names: &[String]) -> Option<String> {
+    let document = Document::parse(text).ok()?;
+    let servers = child(document.root_element(), key)?;
+    let removed: Vec<Node<'_, '_>> = names
+        .iter()
+        .map(|name| child(servers, name))
+        .collect::<Option<_>>()?;
+
+    if servers
+        .children()
+        .all(|node| !node.is_element() || removed.contains(&node))
+    {
+        return Some(apply(text, vec![(removal(text, servers), String::new())]));
+    }
+
+    Some(apply(
+        text,
+        removed
+            .into_iter()
+            .map(|node| (removal(text, node), String::new()))
+            .collect(),
+    ))
+}
+
+/// Render a server element, with lines after the first indented by `indent`
+fn render_server(name: &str, entry: &JsonValue, indent: &str, unit: &str) -> String {
+    let rendered = render("server", entry, indent, unit);
+    rendered.replacen(
+        "<server",
+        &format!("<server name=\"{}\"", escape(name, true)),
+        1,
+    )
+}
+
+/// Render `value` as a `tag` element, with lines after the first indented by `indent`
+fn render(tag: &str, value: &JsonValue, indent: &str, unit: &str) -> String {
+    let inner = format!("{indent}{unit}");
+    let children: Vec<String> = match value {
+        JsonValue::Null => return format!("<{tag}/>"),
+        JsonValue::String(text) if text.is_empty() => return format!("<{tag}/>"),
+        JsonValue::String(text) => return format!("<{tag}>{}</{tag}>", escape(text, false)),
+        JsonValue::Bool(_) | JsonValue::Number(_) => return format!("<{tag}>{value}</{tag}>"),
+        JsonValue::Array(items) => items
+            .iter()
+            .map(|item| render("item", item, &inner, unit))
+            .collect(),
+        JsonValue::Object(fields) => fields
+            .iter()
+            .map(|(field, value)| {
+                if is_plain_name(field) {
+                    render(field, value, &inner, unit)
+                } else {
+                    let rendered = render("entry", value, &inner, unit);
+                    rendered.replacen(
+                        "<entry",
+                        &format!("<entry key=\"{}\"", escape(field, true)),
+                        1,
+                    )
+                }
+            })
+            .collect(),
+    };
+
+    if children.is_empty() {
+        return format!("<{tag}/>");
+    }
+    let mut rendered = format!("<{tag}>");
+    for child in children {
+        rendered.push_str(&format!("\n{inner}{child}"));
+    }
+    rendered.push_str(&format!("\n{indent}</{tag}>"));
+    rendered
+}
+
+/// Whether a key can be written as a tag name of its own
+fn is_plain_name(key: &str) -> bool {
+    key != "item"
+        && key != "entry"
+        && key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Escape text (or an attribute value) for XML
+fn escape(text: &str, attribute: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' if attribute => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Add `rendered` as the last child of `parent`, on a line of its own
+fn append_child(text: &str, parent: Node<'_, '_>, rendered: &str) -> String {
+    let range = parent.range();
+    let indent = indent_of(text, parent);
+    let unit = indent_unit(text, parent.document().root_element());
+    let element = &text[range.clone()];
+
+    // `<parent/>` gets opened up
+    if let Some(start_tag) = element.strip_suffix("/>") {
+        let name = tag_name(element);
+        let opened = format!(
+            "{}>\n{indent}{unit}{rendered}\n{indent}</{name}>",
+            start_tag.trim_end()
+        );
+        return apply(text, vec![(range, opened)]);
+    }
+
+    // After the last thing in it, before the whitespace ahead of `</parent>`
+    let close = range.start + element.rfind("</").unwrap_or(element.len());
+    let after = text[..close]
+        .trim_end()
+        .len()
+        .max(start_tag_end(text, parent));
+    apply(
+        text,
+        vec![(
+            after..close,
+            format!("\n{indent}{unit}{rendered}\n{indent}"),
+        )],
+    )
+}
+
+/// Byte offset after the start tag of an element
+fn start_tag_end(text: &str, node: Node<'_, '_>) -> usize {
+    let range = node.range();
+    let mut quote = None;
+    for (offset, c) in text[range.clone()].char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(open)) if c == open => quote = None,
+            ('>', None) => return range.start + offset + 1,
+            _ => {}
+        }
+    }
+    range.end
+}
+
+/// Qualified tag name of an element from its text
+fn tag_name(element: &str) -> &str {
+    let name = &element[1..];
+    let end = name
+        .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+        .unwrap_or(name.len());
+    &name[..end]
+}
+
+/// Leading whitespace of the line an element starts on
+fn indent_of<'a>(text: &'a str, node: Node<'_, '_>) -> &'a str {
+    let start = node.range().start;
+    let line_start = text[..start].rfind('\n').map_or(0, |at| at + 1);
+    let line = &text[line_start..start];
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// Indentation step, from the root's first child element
+fn indent_unit<'a>(text: &'a str, root: Node<'_, '_>) -> &'a str {
+    let root_indent = indent_of(text, root).len();
+    root.children()
+        .find(Node::is_element)
+        .and_then(|child| indent_of(text, child).get(root_indent..))
+        .filter(|unit| !unit.is_empty())
+        .unwrap_or(DEFAULT_INDENT)
+}
+
+/// Byte range to cut an element out with: its whole line if nothing else is
+/// on it, otherwise just the element
+fn removal(text: &str, node: Node<'_, '_>) -> Range<usize> {
+    let range = node.range();
+    let line_start = text[..range.start].rfind('\n').map_or(0, |at| at + 1);
+    let line_end = text[range.end..]
+        .find('\n')
+        .map_or(text.len(), |at| range.end + at + 1);
+
+    if text[line_start..range.start].trim().is_empty()
+        && text[range.end..line_end].trim().is_empty()
+    {
+        line_start..line_end
+    } else {
+        range
+    }
+}
+
+/// Apply non-overlapping text replacements
+fn apply(text: &str, mut edits: Vec<(Range<usize>, String)>) -> String {
+    edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+
+    let mut out = text.to_string();
+    for (range, replacement) in edits {
+        out.replace_range(range, &replacement);
+    }
+    out
+}
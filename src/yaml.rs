@@ -0,0 +1,410 @@
+//! Line-based editing of block-style YAML
+//!
+//! Re-serializing a YAML config with serde_yaml drops its comments, expands
+//! its anchors and aliases and requotes every scalar. Edits to the servers
+//! under a top-level key are instead made as text: an entry's lines are
+//! replaced or removed, or new ones added at the indentation of its siblings,
+//! so everything else stays byte-for-byte intact.
+//!
+//! Only block style is handled. Anything else (a flow mapping holding
+//! servers, several documents, tab indentation) gives `None`, and callers
+//! fall back to rewriting the file; they also check every edit by parsing
+//! it back.
+
+use std::ops::Range;
+
+use serde_yaml::Value as YamlValue;
+
+/// Indentation step when the document gives no hint
+const DEFAULT_INDENT: usize = 2;
+
+/// Indentation step of serde_yaml's output
+const RENDERED_INDENT: usize = 2;
+
+/// A line of the document
+struct Line {
+    /// Byte offset of the line
+    start: usize,
+    /// Byte offset after its line break
+    end: usize,
+    /// Leading spaces
+    indent: usize,
+    /// Whether it holds more than whitespace and a comment
+    content: bool,
+    /// Whether it starts a sequence item (`- ...`)
+    dash: bool,
+}
+
+/// The value of a top-level key
+struct Block {
+    /// Line of the key
+    key: usize,
+    /// ` {}` or ` []` after the key, dropped when the value gets its first line
+    flow_empty: Option<Range<usize>>,
+    /// Lines of the value, without blank and comment lines
+    children: Vec<usize>,
+}
+
+/// A YAML document split into lines
+struct Document<'a> {
+    text: &'a str,
+    lines: Vec<Line>,
+    /// Indentation step, from the least indented nested line
+    unit: usize,
+}
+
+impl<'a> Document<'a> {
+    /// Split a document into lines, or `None` if it's not one we can edit
+    fn new(text: &'a str) -> Option<Self> {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for raw in text.split_inclusive('\n') {
+            let body = raw.trim_end_matches(['\n', '\r']);
+            let rest = body.trim_start_matches(' ');
+            let indent = body.len() - rest.len();
+            let content = !rest.trim().is_empty() && !rest.starts_with('#');
+
+            if content
+                && (rest.starts_with('\t')
+                    || (indent == 0
+                        && ["%", "---", "..."]
+                            .iter()
+                            .any(|marker| rest.starts_with(marker))))
+            {
+                return None;
+            }
+
+            lines.push(Line {
+                start,
+                end: start + raw.len(),
+                indent,
+                content,
+                dash: rest == "-" || rest.starts_with("- "),
+            });
+            start += raw.len();
+        }
+
+        let unit = lines
+            .iter()
+            .filter(|line| line.content && line.indent > 0)
+            .map(|line| line.indent)
+            .min()
+            .unwrap_or(DEFAULT_INDENT);
+
+        Some(Self { text, lines, unit })
+    }
+
+    /// Text of a line without its line break
+    fn line(&self, index: usize) -> &'a str {
+        let line = &self.lines[index];
+        self.text[line.start..line.end].trim_end_matches(['\n', '\r'])
+    }
+
+    /// Find the block value of a top-level key
+    ///
+    /// `Some(None)` if the key isn't there, `None` if its value isn't in
+    /// block style (or an empty flow collection).
+    fn block(&self, key: &str) -> Option<Option<Block>> {
+        let Some((index, rest)) = (0..self.lines.len())
+            .filter(|&index| self.lines[index].content && self.lines[index].indent == 0)
+            .find_map(|index| Some((index, key_rest(self.line(index), key)?)))
+        else {
+            return Some(None);
+        };
+
+        let value = strip_comment(rest).trim();
+        let flow_empty = if value == "{}" || value == "[]" {
+            // Drop the space before it too
+            let line = self.line(index);
+            let at = line.len() - rest.len() + rest.find(value)?;
+            let start = self.lines[index].start + at;
+            Some(start - 1..start + value.len())
+        } else if value.is_empty() || (value.starts_with('&') && !value.contains(' ')) {
+            None
+        } else {
+            return None;
+        };
+
+        let mut children = Vec::new();
+        for (child, line) in self.lines.iter().enumerate().skip(index + 1) {
+            if !line.content {
+                continue;
+            }
+            if line.indent == 0 && !line.dash {
+                break;
+            }
+            children.push(child);
+        }
+
+        if flow_empty.is_some() && !children.is_empty() {
+            return None;
+        }
+
+        Some(Some(Block {
+            key: index,
+            flow_empty,
+            children,
+        }))
+    }
+
+    /// Split a block into its entries (or items), as byte ranges from their
+    /// first line to the end of their last content line
+    fn members(&self, block: &Block) -> Vec<Range<usize>> {
+        let Some(indent) = self.indent(block) else {
+            return Vec::new();
+        };
+
+        let mut members: Vec<Range<usize>> = Vec::new();
+        for &child in &block.children {
+            let line = &self.lines[child];
+            match members.last_mut() {
+                Some(member) if line.indent > indent => member.end = line.end,
+                _ => members.push(line.start..line.end),
+            }
+        }
+        members
+    }
+
+    /// Indentation of a block's entries, if it has any
+    fn indent(&self, block: &Block) -> Option<usize> {
+        block
+            .children
+            .first()
+            .map(|&child| self.lines[child].indent)
+    }
+
+    /// Whether a block holds sequence items
+    fn is_sequence(&self, block: &Block) -> bool {
+        block
+            .children
+            .first()
+            .is_none_or(|&child| self.lines[child].dash)
+    }
+
+    /// Byte offset after a block's last line
+    fn block_end(&self, block: &Block) -> usize {
+        self.lines[*block.children.last().unwrap_or(&block.key)].end
+    }
+
+    /// Add `rendered` lines after a block's last line
+    fn append_to(&self, block: &Block, rendered: &str) -> String {
+        let indent = self.indent(block).unwrap_or(self.unit);
+        let end = self.block_end(block);
+
+        let mut edits = vec![(
+            end..end,
+            self.line_break_before(end) + &self.indented(rendered, indent),
+        )];
+        if let Some(flow_empty) = &block.flow_empty {
+            edits.push((flow_empty.clone(), String::new()));
+        }
+        apply(self.text, edits)
+    }
+
+    /// Add a top-level key holding `rendered` lines at the end of the document
+    fn append_key(&self, key: &str, rendered: &str) -> String {
+        let end = self.text.len();
+        let addition = format!(
+            "{}{key}:\n{}",
+            self.line_break_before(end),
+            self.indented(rendered, self.unit)
+        );
+        apply(self.text, vec![(end..end, addition)])
+    }
+
+    /// A line break to insert at `offset` if it's not at the start of a line
+    fn line_break_before(&self, offset: usize) -> String {
+        if offset == 0 || self.text[..offset].ends_with('\n') {
+            String::new()
+        } else {
+            "\n".to_string()
+        }
+    }
+
+    /// Indent serde_yaml's output by `indent`, with nesting at the document's step
+    fn indented(&self, rendered: &str, indent: usize) -> String {
+        let pad = " ".repeat(indent);
+        rendered
+            .lines()
+            .map(|line| {
+                if line.is_empty() {
+                    return "\n".to_string();
+                }
+                let rest = line.trim_start_matches(' ');
+                let nesting = (line.len() - rest.len()) / RENDERED_INDENT * self.unit;
+                format!("{pad}{}{rest}\n", " ".repeat(nesting))
+            })
+            .collect()
+    }
+
+    /// Remove members of a block, and the key too if none are left
+    fn remove_members(&self, block: &Block, removed: Vec<Range<usize>>, total: usize) -> String {
+        if removed.len() == total {
+            let start = self.lines[block.key].start;
+            return apply(
+                self.text,
+                vec![(start..self.block_end(block), String::new())],
+            );
+        }
+
+        apply(
+            self.text,
+            removed
+                .into_iter()
+                .map(|range| (range, String::new()))
+                .collect(),
+        )
+    }
+}
+
+/// Set entry `name` of the mapping under top-level `key`, adding the key if needed
+///
+/// An existing entry is replaced where it is; a new one goes after the last.
+pub(crate) fn upsert_entry(text: &str, key: &str, name: &str, value: &YamlValue) -> Option<String> {
+    let mut entry = serde_yaml::Mapping::new();
+    entry.insert(name.into(), value.clone());
+    let rendered = serde_yaml::to_string(&entry).ok()?;
+
+    let document = Document::new(text)?;
+    let Some(block) = document.block(key)? else {
+        return Some(document.append_key(key, &rendered));
+    };
+    if document.is_sequence(&block) && !block.children.is_empty() {
+        return None;
+    }
+
+    let indent = document.indent(&block).unwrap_or(document.unit);
+    match document
+        .members(&block)
+        .into_iter()
+        .find(|member| entry_key(&text[member.clone()]) == Some(name))
+    {
+        Some(member) => Some(apply(
+            text,
+            vec![(member, document.indented(&rendered, indent))],
+        )),
+        None => Some(document.append_to(&block, &rendered)),
+    }
+}
+
+/// Remove entries `names` of the mapping under top-level `key`, and the key
+/// too if it ends up empty
+pub(crate) fn remove_entries(text: &str, key: &str, names: &[String]) -> Option<String> {
+    let document = Document::new(text)?;
+    let block = document.block(key)??;
+
+    let members = document.members(&block);
+    let total = members.len();
+    let removed: Vec<Range<usize>> = members
+        .into_iter()
+        .filter(|member| {
+            entry_key(&text[member.clone()]).is_some_and(|key| names.iter().any(|name| name == key))
+        })
+        .collect();
+    if removed.len() != names.len() {
+        return None;
+    }
+
+    Some(document.remove_members(&block, removed, total))
+}
+
+/// Set an item of the sequence under top-level `key`, adding the key if needed
+///
+/// Replaces the item at `index`, or adds one after the last.
+pub(crate) fn upsert_item(
+    text: &str,
+    key: &str,
+    index: Option<usize>,
+    item: &YamlValue,
+) -> Option<String> {
+    let rendered = serde_yaml::to_string(&[item]).ok()?;
+
+    let document = Document::new(text)?;
+    let Some(block) = document.block(key)? else {
+        return Some(document.append_key(key, &rendered));
+    };
+    if !document.is_sequence(&block) {
+        return None;
+    }
+
+    let indent = document.indent(&block).unwrap_or(document.unit);
+    match index {
+        Some(index) => {
+            let member = document.members(&block).into_iter().nth(index)?;
+            Some(apply(
+                text,
+                vec![(member, document.indented(&rendered, indent))],
+            ))
+        }
+        None => Some(document.append_to(&block, &rendered)),
+    }
+}
+
+/// Remove the items at `indices` of the sequence under top-level `key`, and
+/// the key too if it ends up empty
+pub(crate) fn remove_items(text: &str, key: &str, indices: &[usize]) -> Option<String> {
+    let document = Document::new(text)?;
+    let block = document.block(key)??;
+    if !document.is_sequence(&block) {
+        return None;
+    }
+
+    let members = document.members(&block);
+    let total = members.len();
+    let removed: Vec<Range<usize>> = indices
+        .iter()
+        .map(|&index| members.get(index).cloned())
+        .collect::<Option<_>>()?;
+
+    Some(document.remove_members(&block, removed, total))
+}
+
+/// Get what follows `key:` on a top-level line, or `None` if it's another key
+fn key_rest<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let rest = [
+        format!("{key}:"),
+        format!("\"{key}\":"),
+        format!("'{key}':"),
+    ]
+    .iter()
+    .find_map(|prefix| line.strip_prefix(prefix.as_str()))?;
+
+    (rest.is_empty() || rest.starts_with([' ', '\t'])).then_some(rest)
+}
+
+/// Get the key of a mapping entry from its text
+fn entry_key(entry: &str) -> Option<&str> {
+    let entry = entry.trim_start_matches(' ');
+    for quote in ['"', '\''] {
+        if let Some(quoted) = entry.strip_prefix(quote) {
+            let (key, rest) = quoted.split_once(quote)?;
+            return rest.starts_with(':').then_some(key);
+        }
+    }
+
+    let line = entry.lines().next()?;
+    line.find(": ")
+        .or_else(|| line.ends_with(':').then(|| line.len() - 1))
+        .map(|colon| &line[..colon])
+        .filter(|key| !key.starts_with(['?', '-', '#']))
+}
+
+/// Cut a trailing `# comment` off a line
+fn strip_comment(line: &str) -> &str {
+    match line.find(" #").or_else(|| line.find("\t#")) {
+        Some(at) => &line[..at],
+        None if line.starts_with('#') => "",
+        None => line,
+    }
+}
+
+/// Apply non-overlapping text replacements
+fn apply(text: &str, mut edits: Vec<(Range<usize>, String)>) -> String {
+    edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+
+    let mut out = text.to_string();
+    for (range, replacement) in edits {
+        out.replace_range(range, &replacement);
+    }
+    out
+}